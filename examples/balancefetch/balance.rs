@@ -1,20 +1,31 @@
-pub async fn get_token_balance<C, U, V, I>(
-    sdk: &NamadaImpl<C, U, V, I>,
-) -> Result<token::Amount, Box<dyn Error>>
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    // Hardcoded addresses
-    let token_address = Address::from_str("tnam1qy440ynh9fwrx8aewjvvmu38zxqgukgc259fzp6h")?;
-    let owner_address = Address::from_str("tnam1qqteapc3ycthpehxtqadv6nx2grr5gptzs2ptyvy")?;
+use std::str::FromStr;
 
-    // Fetch balance using the SDK
-    let balance = sdk.rpc_client().await.get_token_balance(&token_address, &owner_address, None)
-        .await
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+use anyhow::Result;
+use namada_core::address::Address;
+use namada_sdk::Namada;
+use wallet_example::config::Config;
+use wallet_example::query::get_token_balance;
 
-    Ok(balance)
+const TOKEN_ADDRESS: &str = "tnam1qy440ynh9fwrx8aewjvvmu38zxqgukgc259fzp6h";
+const OWNER_ADDRESS: &str = "tnam1qqteapc3ycthpehxtqadv6nx2grr5gptzs2ptyvy";
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    // Network profile from namada-wallet.toml (or NAMADA_* env vars)
+    let (_network, profile) = Config::load()?.active_profile(None)?;
+    let sdk = profile.build_sdk().await?;
+
+    // Load existing wallet
+    if sdk.wallet_mut().await.load().is_ok() {
+        println!("Existing wallet found");
+    } else {
+        println!("No existing wallet found.");
+    }
+
+    let token = Address::from_str(TOKEN_ADDRESS)?;
+    let owner = Address::from_str(OWNER_ADDRESS)?;
+    let balance = get_token_balance(&sdk, &owner, &token).await?;
+    println!("Token Balance: {}", sdk.format_amount(&token, balance).await);
+
+    Ok(())
 }
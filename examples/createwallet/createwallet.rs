@@ -1,78 +1,46 @@
-use tokio;
-use std::str::FromStr;
-use namada_sdk::{
-    MaybeSend, 
-    MaybeSync,
-    args::{ TxBuilder}, 
-    io::{StdIo, Io, Client}, 
-    masp::{ShieldedUtils, fs::FsShieldedUtils}, 
-    wallet::{WalletIo, DerivationPath, WalletStorage, fs::FsWalletUtils}, 
-    Namada, 
-    NamadaImpl, 
-    chain::ChainId,
-    zeroize::Zeroizing,
-    bip39::Mnemonic,
-    key::SchemeType,
-};
-use tendermint_rpc::{HttpClient, Url};
-use anyhow::{Result, Context};
-
-const RPC_URL: &str = "https://rpc.knowable.run:443"; 
-const CHAIN_ID: &str = "housefire-reduce.e51ecf4264fc3"; 
-const WALLET_ALIAS: &str = "default_wallet"; 
+use anyhow::Result;
+use namada_sdk::key::SchemeType;
+use namada_sdk::Namada;
+use wallet_example::config::Config;
+use wallet_example::mnemonic::{MnemonicVault, PendingMnemonic};
+use wallet_example::wallet::{create_wallet, KeyOptions};
+use wallet_example::wallet_utils::{prompt_backup_check, prompt_new_password};
+
+const WALLET_ALIAS: &str = "default_wallet";
+const WORD_COUNT: usize = 24;
+// SchemeType::Secp256k1 for an Ethereum-style key
+const KEY_SCHEME: SchemeType = SchemeType::Ed25519;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let url = Url::from_str(RPC_URL)?;
-    let http_client = HttpClient::new(url)?;
-
-    let wallet = FsWalletUtils::new("./sdk-wallet".into());
-    let shielded_mut = FsShieldedUtils::new("./masp".into());
-    let std_io = StdIo;
-
-    let sdk = NamadaImpl::new(http_client, wallet, shielded_mut, std_io)
-        .await?
-        .chain_id(ChainId::from_str(CHAIN_ID)?);
+    // Network profile from namada-wallet.toml (or NAMADA_* env vars)
+    let (_network, profile) = Config::load()?.active_profile(None)?;
+    let sdk = profile.build_sdk().await?;
 
+    // Load existing wallet
     if sdk.wallet_mut().await.load().is_ok() {
-        println!("Existing wallet found. Continuing to create a new wallet...");
+        println!("Existing wallet found");
     } else {
-        println!("No existing wallet found. Creating a new wallet...");
+        println!("No existing wallet found.");
     }
-    
-    create_wallet(&sdk).await?;
-
-    Ok(())
-}
 
-// Create a new wallet
-async fn create_wallet<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<()>
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let mnemonic = Mnemonic::new(namada_sdk::bip39::MnemonicType::Words24, namada_sdk::bip39::Language::English);
-    let phrase = mnemonic.phrase();
-
-    println!("Generated mnemonic: {}", phrase);
-
-    let derivation_path = DerivationPath::default_for_transparent_scheme(SchemeType::Ed25519);
-    let result = sdk.wallet_mut().await
-        .derive_store_key_from_mnemonic_code(
-            SchemeType::Ed25519,
-            Some(WALLET_ALIAS.to_string()),
-            true,
-            derivation_path,
-            Some((mnemonic.clone(), Zeroizing::new("".to_owned()))),
-            true,
-            None,
-        );
-
-    let (_key_alias, _sk) = result.ok_or_else(|| anyhow::anyhow!("Failed to derive key from mnemonic code"))?;
-
-    sdk.wallet().await.save().context("Could not save wallet!")?;
+    // Show the mnemonic and check a few of its words before anything is stored
+    let pending = PendingMnemonic::generate(WORD_COUNT)?;
+    let mnemonic = prompt_backup_check(&pending)?;
+
+    // Encrypt the key with a password, prompted for unless NAMADA_WALLET_PASSWORD is set
+    let options = KeyOptions {
+        scheme: KEY_SCHEME,
+        password: prompt_new_password()?,
+        ..Default::default()
+    };
+    let alias = create_wallet(&sdk, WALLET_ALIAS.to_string(), &mnemonic, &options).await?;
+
+    // Keep the mnemonic encrypted with the same password so it can be shown again
+    if let Some(password) = &options.password {
+        MnemonicVault::new(&profile.wallet_dir).store(&alias, mnemonic.phrase(), password)?;
+    }
     println!("Wallet created and saved!");
+
     Ok(())
 }
@@ -1,85 +1,46 @@
-use tokio;
-use std::str::FromStr;
 use std::io::{self, Write};
-use namada_sdk::{
-    MaybeSend, 
-    MaybeSync,
-    io::{StdIo, Io, Client}, 
-    masp::{ShieldedUtils, fs::FsShieldedUtils}, 
-    wallet::{WalletIo, DerivationPath, WalletStorage, fs::FsWalletUtils}, 
-    Namada, 
-    NamadaImpl, 
-    chain::ChainId,
-    zeroize::Zeroizing,
-    bip39::Mnemonic,
-    key::SchemeType,
-    args::TxBuilder,
-};
-use tendermint_rpc::{HttpClient, Url};
-use anyhow::{Result, Context};
 
-const RPC_URL: &str = "https://rpc.knowable.run:443"; // RPC URL
-const CHAIN_ID: &str = "housefire-reduce.e51ecf4264fc3"; // Chain ID
+use anyhow::Result;
+use namada_sdk::key::SchemeType;
+use namada_sdk::Namada;
+use wallet_example::config::Config;
+use wallet_example::wallet::{add_key, KeyOptions};
+use wallet_example::wallet_utils::prompt_new_password;
+
+// SchemeType::Secp256k1 for an Ethereum-style key
+const KEY_SCHEME: SchemeType = SchemeType::Ed25519;
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    let url = Url::from_str(RPC_URL)?;
-    let http_client = HttpClient::new(url)?;
-
-    let wallet = FsWalletUtils::new("./sdk-wallet".into());
-    let shielded_mut = FsShieldedUtils::new("./masp".into());
-    let std_io = StdIo;
-
-    let sdk = NamadaImpl::new(http_client, wallet, shielded_mut, std_io)
-        .await?
-        .chain_id(ChainId::from_str(CHAIN_ID)?); 
+    // Network profile from namada-wallet.toml (or NAMADA_* env vars)
+    let (_network, profile) = Config::load()?.active_profile(None)?;
+    let sdk = profile.build_sdk().await?;
 
+    // Load existing wallet
     if sdk.wallet_mut().await.load().is_ok() {
-        println!("Existing wallet found.");
+        println!("Existing wallet found");
     } else {
-        println!("No existing wallet found. Creating a new wallet...");
+        println!("No existing wallet found.");
     }
-    add_key(&sdk).await?;
-
-    Ok(())
-}
 
-// Add a key from a mnemonic
-async fn add_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<()>
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
     let phrase = prompt_user("Enter the mnemonic: ");
     let alias = prompt_user("Enter an alias: ");
-    
-    let mnemonic = Mnemonic::from_phrase(&phrase, namada_sdk::bip39::Language::English)
-        .context("Invalid mnemonic")?;
-
-    let derivation_path = DerivationPath::default_for_transparent_scheme(SchemeType::Ed25519);
-    let (_key_alias, _sk) = sdk.wallet_mut().await
-        .derive_store_key_from_mnemonic_code(
-            SchemeType::Ed25519,
-            Some(alias.clone()),
-            true,
-            derivation_path,
-            Some((mnemonic.clone(), Zeroizing::new("".to_owned()))),
-            true,
-            None,
-        ).context("Unable to derive key from mnemonic code")?;
-
-    sdk.wallet().await.save().context("Could not save wallet!")?;
+    // Encrypt the key with a password, prompted for unless NAMADA_WALLET_PASSWORD is set
+    let options = KeyOptions {
+        scheme: KEY_SCHEME,
+        password: prompt_new_password()?,
+        ..Default::default()
+    };
+    let alias = add_key(&sdk, alias, &phrase, &options).await?;
     println!("Key added successfully with alias: {}", alias);
+
     Ok(())
 }
 
 fn prompt_user(prompt: &str) -> String {
     print!("{}", prompt);
-    io::stdout().flush().unwrap(); 
-
+    io::stdout().flush().expect("Failed to flush stdout");
     let mut input = String::new();
-    io::stdin().read_line(&mut input).unwrap();
+    io::stdin().read_line(&mut input).expect("Failed to read line");
     input.trim().to_string()
 }
@@ -0,0 +1,341 @@
+//! Established accounts controlled by a set of public keys and a signature
+//! threshold: their creation through init-account txs, and changes to their key
+//! set, threshold and validity predicate through update-account txs.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use namada_core::address::Address;
+use namada_sdk::account::Account;
+use namada_sdk::key::common;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::tx::ProcessTxResponse;
+use namada_sdk::{
+    args::TxBuilder,
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use serde::Serialize;
+use tracing::{info, info_span, instrument, Instrument};
+
+use crate::error::{Error, Result};
+use crate::query;
+use crate::signer::Signer;
+use crate::transfer::{sign_and_submit, TxSubmission};
+use crate::wallet::{self, check_alias, save_wallet, signing_keys_of};
+
+/// A key of the account before or after an update, with its index in the key set.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct KeyChange {
+    pub public_key: String,
+    /// Alias of the key in the wallet, if it holds it
+    pub alias: Option<String>,
+    /// Index in `public_keys_map` before the update, `None` for an added key
+    pub index_before: Option<u8>,
+    /// Index after the update, `None` for a removed key
+    pub index_after: Option<u8>,
+}
+
+/// What an update changes on an account.
+#[derive(Clone, Debug, Serialize)]
+pub struct AccountDiff {
+    pub address: String,
+    /// The new key set in order, then the removed keys
+    pub keys: Vec<KeyChange>,
+    pub threshold_before: u8,
+    pub threshold_after: u8,
+    /// New validity predicate code, `None` to keep the current one
+    pub vp_code: Option<String>,
+}
+
+impl AccountDiff {
+    /// Whether submitting the update would leave the account as it is.
+    pub fn is_empty(&self) -> bool {
+        self.threshold_before == self.threshold_after
+            && self.vp_code.is_none()
+            && self.keys.iter().all(|key| key.index_before == key.index_after)
+    }
+}
+
+/// A checked update of an established account, ready to be signed and submitted.
+#[derive(Clone, Debug)]
+pub struct AccountUpdate {
+    pub address: Address,
+    pub public_keys: Vec<common::PublicKey>,
+    pub threshold: u8,
+    pub vp_code_path: Option<PathBuf>,
+    pub diff: AccountDiff,
+}
+
+/// Check an update of the established account `account` (an alias or address) to
+/// the key set `public_keys` (aliases or keys), and compute what it changes.
+///
+/// Without a `threshold` the current one is kept, it must not exceed the new key
+/// count. Nothing is built or signed yet, see [`submit_account_update`].
+pub async fn prepare_account_update<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    account: &str,
+    public_keys: &[String],
+    threshold: Option<u8>,
+    vp_code_path: Option<&Path>,
+) -> Result<AccountUpdate>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let address = wallet::resolve_address(sdk, account).await?;
+    if !matches!(address, Address::Established(_)) {
+        return Err(Error::invalid(
+            "account",
+            format!("{} is not an established account, only those have a key set to update", address),
+        ));
+    }
+    let current = query::get_account_info(sdk, &address)
+        .await?
+        .ok_or_else(|| Error::invalid("account", format!("{} does not exist on chain", address)))?;
+
+    let keys = resolve_key_set(sdk, public_keys).await?;
+    let threshold = threshold.unwrap_or(current.threshold);
+    check_threshold(threshold, &keys)?;
+    check_vp_code(vp_code_path)?;
+
+    let aliases: HashMap<common::PublicKey, String> = sdk
+        .wallet()
+        .await
+        .get_public_keys()
+        .into_iter()
+        .map(|(alias, key)| (key, alias))
+        .collect();
+    let diff = diff_account(&current, &keys, threshold, vp_code_path, &aliases);
+    if diff.is_empty() {
+        return Err(Error::invalid("account update", "it would not change anything"));
+    }
+
+    Ok(AccountUpdate {
+        address,
+        public_keys: keys,
+        threshold,
+        vp_code_path: vp_code_path.map(Path::to_path_buf),
+        diff,
+    })
+}
+
+/// Sign `update` through `signer` with the keys it holds among the account's
+/// current ones and submit it.
+///
+/// Fails before anything is built if the signer holds fewer of them than the
+/// current threshold.
+#[instrument(skip_all, fields(account = %update.address, threshold = update.threshold, keys = update.public_keys.len()))]
+pub async fn submit_account_update<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    update: &AccountUpdate,
+) -> Result<TxSubmission>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    // The key set on chain signs, not the one being set
+    let signing_keys = signing_keys_of(sdk, signer, &update.address).await?;
+    let threshold = update.diff.threshold_before;
+    if signing_keys.len() < usize::from(threshold) {
+        return Err(Error::ThresholdNotMet {
+            address: update.address.clone(),
+            threshold,
+            held: signing_keys.len(),
+        });
+    }
+
+    let mut update_account = sdk
+        .new_update_account(update.address.clone(), update.public_keys.clone(), update.threshold)
+        .signing_keys(signing_keys);
+    update_account.vp_code_path = update.vp_code_path.clone();
+
+    let (update_tx, signing_data) = update_account
+        .build(sdk)
+        .instrument(info_span!("build", tx = "update account tx"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "update account tx", source })?;
+
+    let response =
+        sign_and_submit(sdk, signer, update_tx, &update_account.tx, signing_data, "update account tx").await?;
+    info!("account updated");
+
+    Ok(TxSubmission { reveal: Vec::new(), response })
+}
+
+/// An established account created by [`create_account`].
+#[derive(Debug)]
+pub struct CreatedAccount {
+    /// Address of the new account, `None` when the node did not report it (a
+    /// broadcast only or dry run submission), it is then not stored in the wallet
+    pub address: Option<Address>,
+    pub submission: TxSubmission,
+}
+
+/// Create an established account controlled by `public_keys` (aliases or keys),
+/// any `threshold` of which must sign its txs, and store it under `alias`.
+///
+/// The init-account tx is signed through `signer` by, and its fees paid by, the
+/// implicit account `fee_payer`. None of the account keys has to be in the
+/// wallet, so the keys of co-signers can be given as raw public keys.
+#[instrument(skip_all, fields(alias = alias, fee_payer = fee_payer, threshold = threshold, keys = public_keys.len()))]
+pub async fn create_account<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    alias: &str,
+    fee_payer: &str,
+    public_keys: &[String],
+    threshold: u8,
+    vp_code_path: Option<&Path>,
+) -> Result<CreatedAccount>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_alias(alias)?;
+    if sdk.wallet().await.find_address(alias).is_some() {
+        return Err(Error::AliasTaken(alias.to_string()));
+    }
+    let keys = resolve_key_set(sdk, public_keys).await?;
+    check_threshold(threshold, &keys)?;
+    check_vp_code(vp_code_path)?;
+
+    let fee_payer_address = wallet::find_address(sdk, fee_payer)
+        .await
+        .ok_or_else(|| Error::UnknownAlias(fee_payer.to_string()))?;
+    if !matches!(fee_payer_address, Address::Implicit(_)) {
+        return Err(Error::invalid(
+            "fee payer",
+            format!("{} is not an implicit account, only those can pay fees", fee_payer_address),
+        ));
+    }
+    let signing_keys = signing_keys_of(sdk, signer, &fee_payer_address).await?;
+
+    let mut init_account = sdk.new_init_account(keys, Some(threshold)).signing_keys(signing_keys);
+    if let Some(path) = vp_code_path {
+        init_account.vp_code_path = path.to_path_buf();
+    }
+
+    let (init_tx, signing_data) = init_account
+        .build(sdk)
+        .instrument(info_span!("build", tx = "init account tx"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "init account tx", source })?;
+
+    let response = sign_and_submit(sdk, signer, init_tx, &init_account.tx, signing_data, "init account tx").await?;
+
+    let address = initialized_accounts(&response).into_iter().next();
+    if let Some(address) = &address {
+        sdk.wallet_mut()
+            .await
+            .insert_address(alias.to_string(), address.clone(), false)
+            .ok_or_else(|| Error::WalletIo(format!("Address {} could not be inserted", alias)))?;
+        save_wallet(sdk).await?;
+        info!(%address, "account created");
+    }
+
+    Ok(CreatedAccount {
+        address,
+        submission: TxSubmission { reveal: Vec::new(), response },
+    })
+}
+
+// Keys given as aliases or raw keys, in order and without duplicates
+async fn resolve_key_set<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, public_keys: &[String]) -> Result<Vec<common::PublicKey>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let mut keys: Vec<common::PublicKey> = Vec::with_capacity(public_keys.len());
+    for value in public_keys {
+        let key = wallet::resolve_public_key(sdk, value).await?;
+        if keys.contains(&key) {
+            return Err(Error::invalid("public keys", format!("{} is listed twice", value)));
+        }
+        keys.push(key);
+    }
+    if keys.is_empty() || keys.len() > usize::from(u8::MAX) {
+        return Err(Error::invalid("public keys", format!("an account needs 1 to {} keys", u8::MAX)));
+    }
+    Ok(keys)
+}
+
+fn check_threshold(threshold: u8, keys: &[common::PublicKey]) -> Result<()> {
+    if threshold == 0 || usize::from(threshold) > keys.len() {
+        return Err(Error::invalid(
+            "threshold",
+            format!("{} is not between 1 and the {} key(s) of the account", threshold, keys.len()),
+        ));
+    }
+    Ok(())
+}
+
+fn check_vp_code(vp_code_path: Option<&Path>) -> Result<()> {
+    match vp_code_path {
+        Some(path) if !path.is_file() => Err(Error::invalid("vp code", format!("{} is not a file", path.display()))),
+        _ => Ok(()),
+    }
+}
+
+// Accounts created by the inner txs of an applied batch
+fn initialized_accounts(response: &ProcessTxResponse) -> Vec<Address> {
+    match response {
+        ProcessTxResponse::Applied(applied) => applied
+            .batch
+            .iter()
+            .flat_map(|batch| batch.batch_results.0.values())
+            .filter_map(|result| result.as_ref().ok())
+            .flat_map(|result| result.initialized_accounts.iter().cloned())
+            .collect(),
+        ProcessTxResponse::Broadcast(_) | ProcessTxResponse::DryRun(_) => Vec::new(),
+    }
+}
+
+// The new key set in order, then the removed keys in their old order
+fn diff_account(
+    current: &Account,
+    keys: &[common::PublicKey],
+    threshold: u8,
+    vp_code_path: Option<&Path>,
+    aliases: &HashMap<common::PublicKey, String>,
+) -> AccountDiff {
+    let index_before = |key: &common::PublicKey| current.public_keys_map.pk_to_idx.get(key).copied();
+    let change = |key: &common::PublicKey, index_after: Option<u8>| KeyChange {
+        public_key: key.to_string(),
+        alias: aliases.get(key).cloned(),
+        index_before: index_before(key),
+        index_after,
+    };
+
+    let mut changes: Vec<KeyChange> = keys
+        .iter()
+        .enumerate()
+        .map(|(index, key)| change(key, u8::try_from(index).ok()))
+        .collect();
+    let mut removed: Vec<(&u8, &common::PublicKey)> = current
+        .public_keys_map
+        .idx_to_pk
+        .iter()
+        .filter(|(_, key)| !keys.contains(key))
+        .collect();
+    removed.sort_by_key(|(index, _)| **index);
+    changes.extend(removed.into_iter().map(|(_, key)| change(key, None)));
+
+    AccountDiff {
+        address: current.address.to_string(),
+        keys: changes,
+        threshold_before: current.threshold,
+        threshold_after: threshold,
+        vp_code: vp_code_path.map(|path| path.display().to_string()),
+    }
+}
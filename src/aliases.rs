@@ -0,0 +1,129 @@
+//! Renaming and deleting wallet entries.
+//!
+//! The entries of an alias are taken out of the wallet's `Store` and stored again
+//! under the new alias, encrypted keys as they are so no password is needed, then
+//! the wallet is saved. A spending key and its viewing key are always moved, or
+//! deleted, together.
+
+use std::path::Path;
+
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::{
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+
+use crate::error::{Error, Result};
+use crate::inventory::EntryKind;
+use crate::lock::DirLock;
+use crate::mnemonic::MnemonicVault;
+use crate::store::{entries_of, replace_entries, StoredEntry};
+use crate::wallet::save_wallet;
+
+/// Move the entries of `kinds` (every kind when empty) from `alias` to `new_alias`.
+///
+/// Fails without touching the wallet if `new_alias` is already used by one of them.
+/// Returns the kinds that were renamed.
+pub async fn rename_alias<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    wallet_dir: &Path,
+    kinds: &[EntryKind],
+    alias: &str,
+    new_alias: &str,
+) -> Result<Vec<EntryKind>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    // Aliases are case-insensitive and stored lowercased
+    let (alias, new_alias) = (alias.to_lowercase(), new_alias.to_lowercase());
+    if new_alias.trim().is_empty() {
+        return Err(Error::invalid("alias", "the new alias is empty"));
+    }
+
+    let _lock = DirLock::acquire(wallet_dir)?;
+    let moved_kinds = {
+        let mut wallet = sdk.wallet_mut().await;
+        let store = wallet.store_mut();
+        let (moved, kept) = select_entries(entries_of(store, &alias), kinds, &alias)?;
+        let moved_kinds = kinds_of(&moved);
+
+        let target = entries_of(store, &new_alias);
+        if target.iter().any(|entry| entry.is_any_of(&moved_kinds)) {
+            return Err(Error::AliasTaken(new_alias));
+        }
+
+        let result = replace_entries::<U>(store, &alias, kept)
+            .and_then(|()| replace_entries::<U>(store, &new_alias, target.into_iter().chain(moved).collect()));
+        if let Err(e) = result {
+            // Drop the half-moved entries, the file still holds the wallet as it was
+            let _ = wallet.load();
+            return Err(e);
+        }
+        moved_kinds
+    };
+
+    save_wallet(sdk).await?;
+    if moved_kinds.contains(&EntryKind::PublicKey) {
+        MnemonicVault::new(wallet_dir).rename(&alias, &new_alias)?;
+    }
+    Ok(moved_kinds)
+}
+
+/// Delete the entries of `kinds` (every kind when empty) stored under `alias`.
+///
+/// Returns the kinds that were deleted.
+pub async fn delete_alias<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    wallet_dir: &Path,
+    kinds: &[EntryKind],
+    alias: &str,
+) -> Result<Vec<EntryKind>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let alias = alias.to_lowercase();
+    let _lock = DirLock::acquire(wallet_dir)?;
+    let deleted_kinds = {
+        let mut wallet = sdk.wallet_mut().await;
+        let store = wallet.store_mut();
+        let (deleted, kept) = select_entries(entries_of(store, &alias), kinds, &alias)?;
+        if let Err(e) = replace_entries::<U>(store, &alias, kept) {
+            let _ = wallet.load();
+            return Err(e);
+        }
+        kinds_of(&deleted)
+    };
+
+    save_wallet(sdk).await?;
+    if deleted_kinds.contains(&EntryKind::PublicKey) {
+        MnemonicVault::new(wallet_dir).remove(&alias)?;
+    }
+    Ok(deleted_kinds)
+}
+
+// Split the entries of `alias` into those of `kinds` (all when empty) and the others
+fn select_entries(
+    entries: Vec<StoredEntry>,
+    kinds: &[EntryKind],
+    alias: &str,
+) -> Result<(Vec<StoredEntry>, Vec<StoredEntry>)> {
+    let (selected, kept): (Vec<StoredEntry>, Vec<StoredEntry>) =
+        entries.into_iter().partition(|entry| entry.is_any_of(kinds));
+    if selected.is_empty() {
+        return Err(Error::invalid("alias", format!("no wallet entry named {}", alias)));
+    }
+    Ok((selected, kept))
+}
+
+fn kinds_of(entries: &[StoredEntry]) -> Vec<EntryKind> {
+    let mut kinds: Vec<EntryKind> = entries.iter().flat_map(|entry| entry.kinds().iter().copied()).collect();
+    kinds.sort();
+    kinds
+}
@@ -0,0 +1,408 @@
+//! Password-encrypted backups of the wallet, optionally with its shielded context.
+//!
+//! A backup file is `MAGIC || format version || encrypted JSON`, the header being
+//! authenticated along with the JSON. The JSON carries the encoded wallet `Store`,
+//! so every entry, alias, derivation path and birthday is kept, the mnemonic vault
+//! when there is one, and the files of the MASP directory when asked for. The MASP
+//! parameters are not included, they are downloaded again.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::zeroize::Zeroizing;
+use namada_sdk::{
+    io::{Client, Io},
+    wallet::{Store, WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use serde::{Deserialize, Serialize};
+use tracing::{info, instrument, warn};
+
+use crate::crypto;
+use crate::error::{Error, Result};
+use crate::inventory::EntryKind;
+use crate::lock::{DirLock, LOCK_FILE};
+use crate::mnemonic::MnemonicVault;
+use crate::store::{aliased_entries, entries_of, replace_entries};
+use crate::wallet::save_wallet;
+use crate::wallet_utils::write_atomic;
+
+const MAGIC: &[u8; 8] = b"NAMWBKP\0";
+
+/// Version of the backup format written by this crate.
+pub const BACKUP_VERSION: u8 = 2;
+
+/// One aliased entry of a backup.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct BackupEntry {
+    pub kind: EntryKind,
+    pub alias: String,
+}
+
+/// How a backup is applied to an existing wallet.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestoreMode {
+    /// Add what the wallet is missing, keep its entries when an alias is taken
+    Merge,
+    /// Replace the wallet and mnemonics with the backup, and the shielded context
+    /// when the backup was made on the same chain
+    Overwrite,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    version: u8,
+    /// Unix time in seconds
+    created_at: u64,
+    chain_id: String,
+    wallet: String,
+    mnemonics: Option<String>,
+    /// File name to hex encoded content
+    shielded: BTreeMap<String, String>,
+}
+
+/// A backup that was decrypted and validated.
+pub struct Backup {
+    contents: Contents,
+    store: Store,
+}
+
+impl Backup {
+    /// Decrypt and validate the backup at `path`.
+    ///
+    /// Fails on an unknown format, a wrong password, or a wallet that does not parse.
+    pub fn read(path: &Path, password: &str) -> Result<Self> {
+        let data = fs::read(path).map_err(|e| Error::WalletIo(format!("Unable to read {}: {}", path.display(), e)))?;
+        let encrypted = data
+            .strip_prefix(&MAGIC[..])
+            .ok_or_else(|| Error::invalid("backup", format!("{} is not a wallet backup", path.display())))?;
+        let (&version, encrypted) = encrypted
+            .split_first()
+            .ok_or_else(|| Error::invalid("backup", "the file is truncated"))?;
+        if version != BACKUP_VERSION {
+            return Err(Error::invalid(
+                "backup",
+                format!("format version {} is not supported, expected {}", version, BACKUP_VERSION),
+            ));
+        }
+
+        let plaintext = crypto::decrypt(password, encrypted, &header(version))?;
+        let contents: Contents = serde_json::from_slice(&plaintext)
+            .map_err(|e| Error::invalid("backup", format!("unreadable content: {}", e)))?;
+        if contents.version != version {
+            return Err(Error::invalid("backup", "the content does not match the format version"));
+        }
+        let store = decode_store(&contents.wallet)?;
+        if let Some(mnemonics) = &contents.mnemonics {
+            toml::from_str::<BTreeMap<String, String>>(mnemonics)
+                .map_err(|e| Error::invalid("backup", format!("unreadable mnemonics: {}", e)))?;
+        }
+        for (name, data) in &contents.shielded {
+            if !is_plain_file_name(name) || hex::decode(data).is_err() {
+                return Err(Error::invalid("backup", format!("invalid shielded context file {}", name)));
+            }
+        }
+        Ok(Self { contents, store })
+    }
+
+    pub fn version(&self) -> u8 {
+        self.contents.version
+    }
+
+    /// Unix time in seconds the backup was made at
+    pub fn created_at(&self) -> u64 {
+        self.contents.created_at
+    }
+
+    /// Chain the wallet was used with when the backup was made
+    pub fn chain_id(&self) -> &str {
+        &self.contents.chain_id
+    }
+
+    /// Every aliased entry of the backed up wallet, sorted by kind then alias.
+    pub fn entries(&self) -> Vec<BackupEntry> {
+        backup_entries(&self.store)
+    }
+
+    pub fn has_mnemonics(&self) -> bool {
+        self.contents.mnemonics.is_some()
+    }
+
+    /// Names of the shielded context files in the backup, empty when it has none
+    pub fn shielded_files(&self) -> Vec<String> {
+        self.contents.shielded.keys().cloned().collect()
+    }
+}
+
+/// What a backup holds, once written.
+#[derive(Clone, Debug)]
+pub struct BackupSummary {
+    pub path: PathBuf,
+    pub entries: Vec<BackupEntry>,
+    pub mnemonics: bool,
+    pub shielded_files: Vec<String>,
+}
+
+/// What a restore changed.
+#[derive(Clone, Debug)]
+pub struct RestoreSummary {
+    pub mode: RestoreMode,
+    pub imported: Vec<BackupEntry>,
+    /// Entries of the backup whose alias was already used by a different entry, left as they were
+    pub conflicts: Vec<BackupEntry>,
+    pub shielded_files: Vec<String>,
+}
+
+/// Write the wallet in `wallet_dir`, and the shielded context in `masp_dir` when
+/// given, to a backup at `path` encrypted with `password`.
+#[instrument(skip_all, fields(path = %path.display(), shielded = masp_dir.is_some()))]
+pub async fn export_backup<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    wallet_dir: &Path,
+    masp_dir: Option<&Path>,
+    path: &Path,
+    password: &str,
+) -> Result<BackupSummary>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if password.is_empty() {
+        return Err(Error::invalid("password", "a backup needs a password"));
+    }
+
+    // A consistent snapshot, no other process writes while it is read
+    let _wallet_lock = DirLock::acquire(wallet_dir)?;
+    let _masp_lock = masp_dir.map(DirLock::acquire).transpose()?;
+    let (wallet, entries) = {
+        let wallet = sdk.wallet().await;
+        let encoded = String::from_utf8(wallet.store().encode())
+            .map_err(|e| Error::WalletIo(format!("Unable to encode the wallet: {}", e)))?;
+        (encoded, backup_entries(wallet.store()))
+    };
+    let shielded = match masp_dir {
+        Some(masp_dir) => read_shielded_files(masp_dir)?,
+        None => BTreeMap::new(),
+    };
+    let created_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let contents = Contents {
+        version: BACKUP_VERSION,
+        created_at,
+        chain_id: sdk.tx_builder().chain_id.map(|id| id.to_string()).unwrap_or_default(),
+        wallet,
+        mnemonics: MnemonicVault::new(wallet_dir).export()?,
+        shielded,
+    };
+
+    // Unencrypted keys are in there, wipe them once encrypted
+    let json = serde_json::to_vec(&contents)
+        .map(Zeroizing::new)
+        .map_err(|e| Error::WalletIo(format!("Unable to encode the backup: {}", e)))?;
+    let mut data = header(BACKUP_VERSION);
+    data.extend(crypto::encrypt(password, &json, &data)?);
+    write_atomic(path, &data)?;
+
+    let summary = BackupSummary {
+        path: path.to_path_buf(),
+        entries,
+        mnemonics: contents.mnemonics.is_some(),
+        shielded_files: contents.shielded.into_keys().collect(),
+    };
+    info!(entries = summary.entries.len(), "wrote backup");
+    Ok(summary)
+}
+
+/// Apply `backup` to the wallet in `wallet_dir`, and its shielded context to
+/// `masp_dir` when given.
+///
+/// In [`RestoreMode::Merge`] an entry is only added when its alias is free for its
+/// kind; existing mnemonics and shielded context files are kept. Only aliased
+/// entries are merged, the rest of the backed up wallet comes with an overwrite,
+/// which also clears the shielded context files of `masp_dir` before writing
+/// those of the backup.
+///
+/// The shielded context of a backup made on another chain does not apply to this
+/// one: it is skipped, and the MASP directory is left as it is.
+#[instrument(skip_all, fields(mode = ?mode))]
+pub async fn restore_backup<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    wallet_dir: &Path,
+    masp_dir: Option<&Path>,
+    backup: &Backup,
+    mode: RestoreMode,
+) -> Result<RestoreSummary>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let _wallet_lock = DirLock::acquire(wallet_dir)?;
+    let _masp_lock = masp_dir.map(DirLock::acquire).transpose()?;
+    let overwrite = mode == RestoreMode::Overwrite;
+    let (imported, conflicts) = {
+        let mut wallet = sdk.wallet_mut().await;
+        if overwrite {
+            *wallet.store_mut() = decode_store(&backup.contents.wallet)?;
+            (backup.entries(), Vec::new())
+        } else {
+            match merge_store::<U>(wallet.store_mut(), &backup.store) {
+                Ok(merged) => merged,
+                Err(e) => {
+                    // Drop the half-merged entries, the file still holds the wallet as it was
+                    let _ = wallet.load();
+                    return Err(e);
+                }
+            }
+        }
+    };
+    for conflict in &conflicts {
+        warn!(kind = ?conflict.kind, alias = conflict.alias, "alias already in use, kept the wallet's entry");
+    }
+
+    save_wallet(sdk).await?;
+    // Overwriting with a backup that has no mnemonics leaves an empty vault
+    let mnemonics = backup.contents.mnemonics.as_deref().unwrap_or_default();
+    if overwrite || !mnemonics.is_empty() {
+        MnemonicVault::new(wallet_dir).import(mnemonics, overwrite)?;
+    }
+
+    let chain_id = sdk.tx_builder().chain_id.map(|id| id.to_string()).unwrap_or_default();
+    let masp_dir = match masp_dir {
+        Some(_) if backup.chain_id() != chain_id => {
+            warn!(
+                backup_chain = backup.chain_id(),
+                chain = chain_id,
+                "backup made on another chain, skipped its shielded context"
+            );
+            None
+        }
+        masp_dir => masp_dir,
+    };
+    let mut shielded_files = Vec::new();
+    if let Some(masp_dir) = masp_dir {
+        if overwrite && masp_dir.exists() {
+            for (_, path) in shielded_context_files(masp_dir)? {
+                fs::remove_file(&path)
+                    .map_err(|e| Error::Shielded(format!("Unable to remove {}: {}", path.display(), e)))?;
+            }
+        }
+        if !backup.contents.shielded.is_empty() {
+            fs::create_dir_all(masp_dir)
+                .map_err(|e| Error::Shielded(format!("Unable to create {}: {}", masp_dir.display(), e)))?;
+        }
+        for (name, data) in &backup.contents.shielded {
+            let path = masp_dir.join(name);
+            if !overwrite && path.exists() {
+                continue;
+            }
+            // Checked when the backup was read
+            let data = hex::decode(data).map_err(|e| Error::invalid("backup", e))?;
+            write_atomic(&path, &data)?;
+            shielded_files.push(name.clone());
+        }
+    }
+
+    info!(imported = imported.len(), conflicts = conflicts.len(), "restored backup");
+    Ok(RestoreSummary { mode, imported, conflicts, shielded_files })
+}
+
+// Add the entries of `backup` whose alias is free in `store` for their kind, with
+// their metadata. Returns the imported entries and the conflicting ones.
+fn merge_store<U: WalletIo>(store: &mut Store, backup: &Store) -> Result<(Vec<BackupEntry>, Vec<BackupEntry>)> {
+    let (mut imported, mut conflicts) = (Vec::new(), Vec::new());
+    let mut aliases: Vec<String> = aliased_entries(backup).into_iter().map(|(_, alias)| alias).collect();
+    aliases.sort();
+    aliases.dedup();
+
+    for alias in aliases {
+        let mut entries = entries_of(store, &alias);
+        let mut added = false;
+        for entry in entries_of(backup, &alias) {
+            let existing = entries.iter().find(|local| local.is_any_of(entry.kinds()));
+            let found = entry.kinds().iter().map(|&kind| BackupEntry { kind, alias: alias.clone() });
+            match existing {
+                Some(local) if local.same_as(&entry) => {}
+                Some(_) => conflicts.extend(found),
+                None => {
+                    imported.extend(found);
+                    entries.push(entry);
+                    added = true;
+                }
+            }
+        }
+        if added {
+            replace_entries::<U>(store, &alias, entries)?;
+        }
+    }
+
+    imported.sort();
+    conflicts.sort();
+    Ok((imported, conflicts))
+}
+
+fn backup_entries(store: &Store) -> Vec<BackupEntry> {
+    aliased_entries(store)
+        .into_iter()
+        .map(|(kind, alias)| BackupEntry { kind, alias })
+        .collect()
+}
+
+fn decode_store(wallet: &str) -> Result<Store> {
+    Store::decode(wallet.as_bytes().to_vec())
+        .map_err(|e| Error::invalid("backup", format!("unreadable wallet: {}", e)))
+}
+
+// The header of a backup file, authenticated with its content
+fn header(version: u8) -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(version);
+    header
+}
+
+// The shielded context files of `masp_dir` by name, hex encoded
+fn read_shielded_files(masp_dir: &Path) -> Result<BTreeMap<String, String>> {
+    let mut files = BTreeMap::new();
+    for (name, path) in shielded_context_files(masp_dir)? {
+        let data = fs::read(&path).map_err(|e| Error::Shielded(format!("Unable to read {}: {}", path.display(), e)))?;
+        files.insert(name, hex::encode(data));
+    }
+    if files.is_empty() {
+        return Err(Error::Shielded(format!(
+            "No shielded context in {}, run a shielded sync first",
+            masp_dir.display()
+        )));
+    }
+    Ok(files)
+}
+
+// The files of `masp_dir` that make up the shielded context, without the
+// downloadable parameters, the lock and leftover temporary files
+fn shielded_context_files(masp_dir: &Path) -> Result<Vec<(String, PathBuf)>> {
+    let read_error = |e: std::io::Error| Error::Shielded(format!("Unable to read {}: {}", masp_dir.display(), e));
+    let mut files = Vec::new();
+    for entry in fs::read_dir(masp_dir).map_err(read_error)? {
+        let entry = entry.map_err(read_error)?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let skipped = name.ends_with(".params") || name.ends_with(".tmp") || name == LOCK_FILE;
+        if skipped || !entry.file_type().map_err(read_error)?.is_file() {
+            continue;
+        }
+        files.push((name, entry.path()));
+    }
+    Ok(files)
+}
+
+// A backup must not write outside of the MASP directory
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\'])
+}
@@ -0,0 +1,537 @@
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use namada_sdk::key::SchemeType;
+use wallet_example::inventory::EntryKind;
+use wallet_example::recovery::DEFAULT_GAP_LIMIT;
+
+use crate::output::OutputFormat;
+
+/// Namada wallet. Runs the interactive menu when no subcommand is given.
+#[derive(Debug, Parser)]
+#[command(name = "wallet-example", version)]
+pub struct Cli {
+    /// Path to the config file (defaults to NAMADA_WALLET_CONFIG or ./namada-wallet.toml)
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Network profile to use (defaults to NAMADA_NETWORK or the config's default_network)
+    #[arg(long, global = true)]
+    pub network: Option<String>,
+
+    /// Dir of the named wallets (defaults to NAMADA_WALLET_HOME, the config's wallet_home or the platform data dir)
+    #[arg(long, global = true)]
+    pub wallet_home: Option<PathBuf>,
+
+    /// Named wallet to open (defaults to NAMADA_WALLET or the config's default_wallet)
+    #[arg(long, global = true)]
+    pub wallet: Option<String>,
+
+    /// Print results as text or as one JSON document per operation
+    #[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+    pub output: OutputFormat,
+
+    /// Also write logs as JSON lines to a daily rotated file in this dir
+    #[arg(long, global = true)]
+    pub log_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Wallet management
+    #[command(subcommand)]
+    Wallet(WalletCommand),
+    /// Named wallets of the wallet home
+    #[command(subcommand)]
+    Wallets(WalletsCommand),
+    /// Transparent key management
+    #[command(subcommand)]
+    Key(KeyCommand),
+    /// Address lookups
+    #[command(subcommand)]
+    Address(AddressCommand),
+    /// Shielded spending keys
+    #[command(name = "spending-key", subcommand)]
+    SpendingKey(SpendingKeyCommand),
+    /// Shielded payment addresses
+    #[command(name = "payment-address", subcommand)]
+    PaymentAddress(PaymentAddressCommand),
+    /// Watch-only addresses and viewing keys, stored without secrets
+    #[command(subcommand)]
+    Watch(WatchCommand),
+    /// Transparent to shielded transfer
+    Shield(ShieldArgs),
+    /// Check whether an account has revealed its public key
+    RevealStatus(RevealStatusArgs),
+    /// Sync the shielded context from the MASP indexer
+    ShieldedSync(ShieldedSyncArgs),
+    /// Transparent token transfer
+    Transfer(TransferArgs),
+    /// IBC token transfer
+    IbcTransfer(IbcTransferArgs),
+    /// Create an established account controlled by several keys and a threshold
+    InitAccount(InitAccountArgs),
+    /// Replace the key set, threshold or validity predicate of an established account
+    UpdateAccount(UpdateAccountArgs),
+    /// Transfers from multisig accounts, signed by co-signers through files
+    #[command(subcommand)]
+    Multisig(MultisigCommand),
+    /// Transparent token balance
+    Balance(BalanceArgs),
+    /// Last committed MASP epoch
+    MaspEpoch,
+    /// Node version, height and sync state, checked against the configured chain id
+    NodeStatus,
+    /// Run the steps of a TOML or JSON plan file in order
+    Batch(BatchArgs),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WalletCommand {
+    /// Generate a new mnemonic, check its backup and store its key
+    Create {
+        #[arg(long)]
+        alias: String,
+        /// Number of mnemonic words: 12, 15, 18, 21 or 24
+        #[arg(long, default_value_t = 24)]
+        words: usize,
+        /// Prompt for a BIP39 passphrase to derive the key with
+        #[arg(long)]
+        bip39_passphrase: bool,
+        #[command(flatten)]
+        derivation: DerivationArgs,
+        /// Overwrite an existing key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+    /// Show the mnemonic of a password protected key again
+    ShowMnemonic {
+        #[arg(long)]
+        alias: String,
+    },
+    /// List the addresses, keys and payment addresses stored in the wallet
+    List {
+        /// Only list entries of this kind (repeatable)
+        #[arg(long, value_enum)]
+        kind: Vec<EntryKindArg>,
+        /// Only list aliases containing this text
+        #[arg(long)]
+        alias: Option<String>,
+    },
+    /// Move the entries stored under an alias to a new alias
+    Rename {
+        #[arg(long)]
+        alias: String,
+        #[arg(long)]
+        new_alias: String,
+        /// Only rename entries of this kind (repeatable), all of them by default
+        #[arg(long, value_enum)]
+        kind: Vec<EntryKindArg>,
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Delete the entries stored under an alias
+    Delete {
+        #[arg(long)]
+        alias: String,
+        /// Only delete entries of this kind (repeatable), all of them by default
+        #[arg(long, value_enum)]
+        kind: Vec<EntryKindArg>,
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Write the wallet to a password-encrypted backup file
+    Backup {
+        #[arg(long)]
+        file: PathBuf,
+        /// Also back up the shielded context of the MASP directory
+        #[arg(long)]
+        shielded: bool,
+    },
+    /// Import a backup file into the wallet, adding the entries it is missing
+    Restore {
+        #[arg(long)]
+        file: PathBuf,
+        /// Replace the wallet with the backup instead of merging them
+        #[arg(long)]
+        overwrite: bool,
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WalletsCommand {
+    /// List the wallets of the wallet home
+    List,
+    /// Create an empty named wallet, open it with `--wallet <name>`
+    Create {
+        #[arg(long)]
+        name: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum EntryKindArg {
+    Address,
+    PublicKey,
+    SpendingKey,
+    ViewingKey,
+    PaymentAddress,
+}
+
+impl From<EntryKindArg> for EntryKind {
+    fn from(kind: EntryKindArg) -> Self {
+        match kind {
+            EntryKindArg::Address => EntryKind::Address,
+            EntryKindArg::PublicKey => EntryKind::PublicKey,
+            EntryKindArg::SpendingKey => EntryKind::SpendingKey,
+            EntryKindArg::ViewingKey => EntryKind::ViewingKey,
+            EntryKindArg::PaymentAddress => EntryKind::PaymentAddress,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum KeyCommand {
+    /// Derive and store a key from an existing mnemonic
+    Add {
+        #[arg(long)]
+        alias: String,
+        /// Mnemonic phrase, prompted for when omitted
+        #[arg(long)]
+        mnemonic: Option<String>,
+        /// Prompt for the BIP39 passphrase the key was derived with
+        #[arg(long)]
+        bip39_passphrase: bool,
+        #[command(flatten)]
+        derivation: DerivationArgs,
+        /// Overwrite an existing key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+    /// Scan the accounts of a mnemonic and import those with on-chain activity
+    Recover {
+        /// Alias of account 0, account N is stored as `<alias>-N`
+        #[arg(long)]
+        alias: String,
+        /// Mnemonic phrase, prompted for when omitted
+        #[arg(long)]
+        mnemonic: Option<String>,
+        /// Prompt for the BIP39 passphrase the keys were derived with
+        #[arg(long)]
+        bip39_passphrase: bool,
+        /// Scheme the keys were derived with
+        #[arg(long, value_enum, default_value_t = KeyScheme::Ed25519)]
+        scheme: KeyScheme,
+        /// Stop after this many unused accounts in a row
+        #[arg(long, default_value_t = DEFAULT_GAP_LIMIT)]
+        gap_limit: u32,
+        /// Other tokens whose balance marks an account as used, besides the native token
+        #[arg(long)]
+        token: Vec<String>,
+    },
+    /// Store a raw secret key, in hex as exported by namadaw
+    Import {
+        #[arg(long)]
+        alias: String,
+        /// File holding the secret key, `-` for stdin. Prompted for without echo
+        /// when omitted, a key is never taken on the command line
+        #[arg(long)]
+        secret_key_file: Option<PathBuf>,
+        /// Scheme of a bare 32 byte key, prefixed keys carry their own
+        #[arg(long, value_enum, default_value_t = KeyScheme::Ed25519)]
+        scheme: KeyScheme,
+        /// Overwrite an existing key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Which key of the mnemonic to derive.
+#[derive(Debug, Default, Args)]
+pub struct DerivationArgs {
+    /// Key scheme
+    #[arg(long, value_enum, default_value_t = KeyScheme::Ed25519)]
+    pub scheme: KeyScheme,
+    /// Account index N of the path m/44'/877'/0'/0'/N' (m/44'/60'/0'/0/N for secp256k1)
+    #[arg(long, default_value_t = 0)]
+    pub account: u32,
+    /// Full derivation path, instead of --account
+    #[arg(long, conflicts_with = "account")]
+    pub hd_path: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum KeyScheme {
+    #[default]
+    Ed25519,
+    /// Ethereum-style keys
+    Secp256k1,
+}
+
+impl From<KeyScheme> for SchemeType {
+    fn from(scheme: KeyScheme) -> Self {
+        match scheme {
+            KeyScheme::Ed25519 => SchemeType::Ed25519,
+            KeyScheme::Secp256k1 => SchemeType::Secp256k1,
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AddressCommand {
+    /// Print the address stored under an alias
+    Show {
+        #[arg(long)]
+        alias: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum WatchCommand {
+    /// Store a transparent address to query it
+    Address {
+        #[arg(long)]
+        alias: String,
+        #[arg(long)]
+        address: String,
+        /// Overwrite an existing address with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+    /// Store an extended viewing key to sync and query its shielded notes
+    ViewingKey {
+        #[arg(long)]
+        alias: String,
+        #[arg(long)]
+        viewing_key: String,
+        /// Block height the key was first used at, shielded syncs start there
+        #[arg(long)]
+        birthday: Option<u64>,
+        /// Overwrite an existing viewing key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum MultisigCommand {
+    /// Build a transfer from a multisig account and write it for the co-signers
+    Propose {
+        /// Alias or address of the multisig account
+        #[arg(long)]
+        account: String,
+        /// Target address or alias
+        #[arg(long)]
+        target: String,
+        #[arg(long)]
+        amount: String,
+        /// Token alias or address (defaults to the native token)
+        #[arg(long)]
+        token: Option<String>,
+        /// Alias or public key that signs the wrapper and pays the fees
+        #[arg(long)]
+        fee_payer: String,
+        /// Proposal file to write
+        #[arg(long)]
+        out: PathBuf,
+    },
+    /// Sign a proposal with the wallet's keys of the account, one file per key
+    Sign {
+        /// Proposal file, the signatures are written next to it
+        #[arg(long)]
+        proposal: PathBuf,
+        /// Only sign with this key alias or public key (repeatable)
+        #[arg(long = "key")]
+        keys: Vec<String>,
+        /// Do not ask for confirmation
+        #[arg(long)]
+        yes: bool,
+    },
+    /// Attach the co-signers' signatures to a proposal and submit it
+    Submit {
+        #[arg(long)]
+        proposal: PathBuf,
+        /// Signature file of a co-signer (repeatable)
+        #[arg(long = "signature")]
+        signatures: Vec<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum SpendingKeyCommand {
+    /// Derive and store a spending key from a mnemonic
+    Create {
+        #[arg(long)]
+        alias: String,
+        /// Mnemonic phrase, prompted for when omitted
+        #[arg(long)]
+        mnemonic: Option<String>,
+        /// Prompt for the BIP39 passphrase the key was derived with
+        #[arg(long)]
+        bip39_passphrase: bool,
+        /// Overwrite an existing spending key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+    /// Store a bech32 extended spending key (zsknam1...) with its viewing key
+    Import {
+        #[arg(long)]
+        alias: String,
+        /// File holding the spending key, `-` for stdin. Prompted for without echo
+        /// when omitted, a key is never taken on the command line
+        #[arg(long)]
+        spending_key_file: Option<PathBuf>,
+        /// Block height the key was first used at, shielded syncs start there
+        #[arg(long)]
+        birthday: Option<u64>,
+        /// Overwrite an existing spending key with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PaymentAddressCommand {
+    /// Generate a payment address from a viewing key
+    Gen {
+        #[arg(long)]
+        alias: String,
+        /// Viewing key or its alias
+        #[arg(long)]
+        viewing_key: String,
+        /// Overwrite an existing entry with the same alias
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ShieldArgs {
+    /// Alias of the transparent source account
+    #[arg(long)]
+    pub source: String,
+    /// Shielded payment address
+    #[arg(long)]
+    pub target: String,
+    #[arg(long)]
+    pub amount: String,
+    /// Token alias or address (defaults to the native token)
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RevealStatusArgs {
+    /// Address or alias to check
+    #[arg(long)]
+    pub owner: String,
+}
+
+#[derive(Debug, Args)]
+pub struct ShieldedSyncArgs {
+    /// Extended spending key, extended viewing key or viewing key alias to sync with
+    #[arg(long, alias = "spending-key")]
+    pub key: String,
+}
+
+#[derive(Debug, Args)]
+pub struct TransferArgs {
+    /// Alias of the source account
+    #[arg(long)]
+    pub source: String,
+    /// Target address or alias
+    #[arg(long)]
+    pub target: String,
+    #[arg(long)]
+    pub amount: String,
+    /// Token alias or address (defaults to the native token)
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct IbcTransferArgs {
+    /// Alias of the source account
+    #[arg(long)]
+    pub source: String,
+    /// Receiver address on the counterparty chain
+    #[arg(long)]
+    pub receiver: String,
+    #[arg(long, default_value = "channel-0")]
+    pub channel_id: String,
+    #[arg(long)]
+    pub amount: String,
+    /// Token alias or address (defaults to the native token)
+    #[arg(long)]
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct InitAccountArgs {
+    /// Alias the new account is stored under
+    #[arg(long)]
+    pub alias: String,
+    /// Alias or public key of the account's key set (repeatable, in order)
+    #[arg(long = "public-key", required = true)]
+    pub public_keys: Vec<String>,
+    /// Signatures needed for the account's txs
+    #[arg(long, default_value_t = 1)]
+    pub threshold: u8,
+    /// Alias of the implicit account that signs the tx and pays the fees
+    #[arg(long)]
+    pub fee_payer: String,
+    /// Wasm code of the validity predicate (defaults to the user VP)
+    #[arg(long)]
+    pub vp_code_path: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+pub struct UpdateAccountArgs {
+    /// Alias or address of the established account
+    #[arg(long)]
+    pub account: String,
+    /// Alias or public key of the new key set (repeatable, in order)
+    #[arg(long = "public-key", required = true)]
+    pub public_keys: Vec<String>,
+    /// Signatures needed by the new key set (defaults to the current threshold)
+    #[arg(long)]
+    pub threshold: Option<u8>,
+    /// Wasm code of a new validity predicate
+    #[arg(long)]
+    pub vp_code_path: Option<PathBuf>,
+    /// Do not ask for confirmation
+    #[arg(long)]
+    pub yes: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BatchArgs {
+    /// Plan file (`.json` for JSON, TOML otherwise)
+    pub plan: PathBuf,
+    /// Build and sign every tx but only simulate it on the node, nothing is broadcast.
+    /// Keys are unlocked as for a real run. Txs from a source that has not revealed
+    /// its key on chain can not be simulated and fail
+    #[arg(long)]
+    pub dry_run: bool,
+    /// Keep going after a failed step, overriding the plan's `on_error`
+    #[arg(long)]
+    pub continue_on_error: bool,
+}
+
+#[derive(Debug, Args)]
+pub struct BalanceArgs {
+    /// Owner address or alias
+    #[arg(long)]
+    pub owner: String,
+    /// Token alias or address (defaults to the native token)
+    #[arg(long)]
+    pub token: Option<String>,
+}
@@ -0,0 +1,302 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use namada_sdk::chain::ChainId;
+use namada_sdk::io::{Client, Io, StdIo};
+use namada_sdk::masp::fs::FsShieldedUtils;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::wallet::{WalletIo, WalletStorage};
+use namada_sdk::{MaybeSend, MaybeSync, NamadaImpl};
+use serde::{Deserialize, Serialize};
+use tendermint_rpc::{HttpClient, Url};
+
+use crate::error::{Error, Result};
+use crate::home::{check_wallet_name, WalletHome, DEFAULT_WALLET};
+use crate::signer::{RemoteSigner, Signer, SignerEndpoint, WalletSigner};
+use crate::store::WALLET_FILE;
+use crate::wallet_utils::CliWalletUtils;
+
+// Config file looked up in the working directory unless NAMADA_WALLET_CONFIG is set
+pub const CONFIG_FILE: &str = "namada-wallet.toml";
+
+// Env vars that override the config file
+pub const ENV_CONFIG: &str = "NAMADA_WALLET_CONFIG";
+pub const ENV_NETWORK: &str = "NAMADA_NETWORK";
+pub const ENV_RPC_URL: &str = "NAMADA_RPC_URL";
+pub const ENV_CHAIN_ID: &str = "NAMADA_CHAIN_ID";
+pub const ENV_INDEXER_URL: &str = "NAMADA_INDEXER_URL";
+pub const ENV_WALLET_DIR: &str = "NAMADA_WALLET_DIR";
+pub const ENV_MASP_DIR: &str = "NAMADA_MASP_DIR";
+pub const ENV_WALLET_HOME: &str = "NAMADA_WALLET_HOME";
+pub const ENV_WALLET: &str = "NAMADA_WALLET";
+pub const ENV_SIGNER: &str = "NAMADA_SIGNER";
+
+const DEFAULT_NETWORK: &str = "housefire-cotton";
+
+/// Wallet dir of the built-in profile before wallets moved to the wallet home.
+pub const LEGACY_WALLET_DIR: &str = "./sdk-wallet";
+
+/// A named network the wallet can talk to.
+///
+/// `wallet_dir` and `masp_dir` pin the wallet to fixed dirs; when they are left
+/// out the wallet is opened from the wallet home (see [`NetworkProfile::open_wallet`]).
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NetworkProfile {
+    pub rpc_url: String,
+    pub chain_id: String,
+    pub indexer_url: String,
+    #[serde(default, skip_serializing_if = "is_unset")]
+    pub wallet_dir: PathBuf,
+    #[serde(default, skip_serializing_if = "is_unset")]
+    pub masp_dir: PathBuf,
+    /// Name of the wallet opened from the home, `None` for pinned dirs
+    #[serde(skip)]
+    pub wallet: Option<String>,
+    /// Remote signer holding the keys, `unix:<path>` or a localhost `http://` URL;
+    /// the wallet signs when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<String>,
+}
+
+/// Contents of `namada-wallet.toml`.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Config {
+    pub default_network: String,
+    /// Dir of the named wallets, the platform data dir when unset
+    #[serde(default)]
+    pub wallet_home: Option<PathBuf>,
+    /// Wallet opened when none is named, `default` when unset
+    #[serde(default)]
+    pub default_wallet: Option<String>,
+    pub networks: BTreeMap<String, NetworkProfile>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        let mut networks = BTreeMap::new();
+        networks.insert(
+            DEFAULT_NETWORK.to_string(),
+            NetworkProfile {
+                rpc_url: "https://rpc.knowable.run:443".to_string(),
+                chain_id: "housefire-cotton.d3c912fee7462".to_string(),
+                indexer_url: "https://masp.knowable.run/api/v1".to_string(),
+                wallet_dir: PathBuf::new(),
+                masp_dir: PathBuf::new(),
+                wallet: None,
+                signer: None,
+            },
+        );
+        Config {
+            default_network: DEFAULT_NETWORK.to_string(),
+            wallet_home: None,
+            default_wallet: None,
+            networks,
+        }
+    }
+}
+
+impl Config {
+    // Load the config from NAMADA_WALLET_CONFIG or ./namada-wallet.toml,
+    // falling back to the built-in profile when neither exists
+    pub fn load() -> Result<Self> {
+        let path = env::var(ENV_CONFIG)
+            .map(PathBuf::from)
+            .unwrap_or_else(|_| PathBuf::from(CONFIG_FILE));
+
+        if !path.exists() {
+            if env::var(ENV_CONFIG).is_ok() {
+                return Err(Error::Config(format!("Config file {} does not exist", path.display())));
+            }
+            return Ok(Config::default());
+        }
+        Self::load_from(&path)
+    }
+
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::Config(format!("Unable to read config file {}: {}", path.display(), e)))?;
+        let config: Config = toml::from_str(&content)
+            .map_err(|e| Error::Config(format!("Unable to parse config file {}: {}", path.display(), e)))?;
+
+        if !config.networks.contains_key(&config.default_network) {
+            return Err(Error::Config(format!(
+                "Default network {} is not defined in {}",
+                config.default_network,
+                path.display()
+            )));
+        }
+        Ok(config)
+    }
+
+    // Pick the requested network (or NAMADA_NETWORK, or the default one), apply
+    // the per-field env overrides on top of it and open the wallet from the home
+    pub fn active_profile(&self, network: Option<&str>) -> Result<(String, NetworkProfile)> {
+        let home = self.wallet_home(None)?;
+        self.active_profile_in(network, &home, None)
+    }
+
+    // Same as `active_profile`, opening `wallet` (or NAMADA_WALLET, or the
+    // config's default_wallet) from `home` unless the profile pins its dirs
+    pub fn active_profile_in(
+        &self,
+        network: Option<&str>,
+        home: &WalletHome,
+        wallet: Option<&str>,
+    ) -> Result<(String, NetworkProfile)> {
+        let name = match network {
+            Some(network) => network.to_string(),
+            None => env::var(ENV_NETWORK).unwrap_or_else(|_| self.default_network.clone()),
+        };
+        let mut profile = self.profile(&name)?;
+        profile.apply_env_overrides();
+
+        let wallet = match wallet {
+            Some(wallet) => Some(wallet.to_string()),
+            None => env::var(ENV_WALLET).ok(),
+        };
+        let wallet = match wallet {
+            Some(wallet) => Some(wallet),
+            None if profile.is_pinned() => None,
+            None => self.default_wallet.clone(),
+        };
+        profile.open_wallet(home, wallet.as_deref(), &name)?;
+        Ok((name, profile))
+    }
+
+    // The wallet home: the given dir, else NAMADA_WALLET_HOME, else the
+    // config's wallet_home, else the platform data dir
+    pub fn wallet_home(&self, home: Option<&Path>) -> Result<WalletHome> {
+        if let Some(home) = home {
+            return Ok(WalletHome::new(home));
+        }
+        if let Ok(home) = env::var(ENV_WALLET_HOME) {
+            return Ok(WalletHome::new(home));
+        }
+        match &self.wallet_home {
+            Some(home) => Ok(WalletHome::new(home)),
+            None => WalletHome::platform_default(),
+        }
+    }
+
+    pub fn profile(&self, name: &str) -> Result<NetworkProfile> {
+        self.networks.get(name).cloned().ok_or_else(|| {
+            let known: Vec<&str> = self.networks.keys().map(String::as_str).collect();
+            Error::Config(format!("Unknown network {} (known networks: {})", name, known.join(", ")))
+        })
+    }
+}
+
+impl NetworkProfile {
+    pub fn apply_env_overrides(&mut self) {
+        if let Ok(rpc_url) = env::var(ENV_RPC_URL) {
+            self.rpc_url = rpc_url;
+        }
+        if let Ok(chain_id) = env::var(ENV_CHAIN_ID) {
+            self.chain_id = chain_id;
+        }
+        if let Ok(indexer_url) = env::var(ENV_INDEXER_URL) {
+            self.indexer_url = indexer_url;
+        }
+        if let Ok(wallet_dir) = env::var(ENV_WALLET_DIR) {
+            self.wallet_dir = wallet_dir.into();
+        }
+        if let Ok(masp_dir) = env::var(ENV_MASP_DIR) {
+            self.masp_dir = masp_dir.into();
+        }
+        if let Ok(signer) = env::var(ENV_SIGNER) {
+            self.signer = Some(signer);
+        }
+    }
+
+    /// Whether the config or env set the wallet dir instead of using the wallet home.
+    pub fn is_pinned(&self) -> bool {
+        !is_unset(&self.wallet_dir)
+    }
+
+    /// [`LEGACY_WALLET_DIR`] when it holds a wallet and this profile opened one from
+    /// the wallet home instead, so its keys would silently go unused.
+    pub fn unused_legacy_wallet(&self) -> Option<PathBuf> {
+        let dir = PathBuf::from(LEGACY_WALLET_DIR);
+        (self.wallet.is_some() && dir.join(WALLET_FILE).exists()).then_some(dir)
+    }
+
+    /// Point the profile at the wallet `name` of `home`, with its MASP dir for `network`.
+    ///
+    /// A named wallet must exist (see [`WalletHome::create`]). Without a name, pinned
+    /// dirs are kept and the default wallet of the home is used otherwise.
+    pub fn open_wallet(&mut self, home: &WalletHome, name: Option<&str>, network: &str) -> Result<()> {
+        match name {
+            Some(name) => {
+                check_wallet_name(name)?;
+                if !home.exists(name) {
+                    return Err(Error::Config(format!(
+                        "No wallet named {} in {}, create it first",
+                        name,
+                        home.root().display()
+                    )));
+                }
+                self.wallet_dir = home.wallet_dir(name);
+                self.masp_dir = home.masp_dir(name, network);
+                self.wallet = Some(name.to_string());
+            }
+            None if self.is_pinned() => {
+                if is_unset(&self.masp_dir) {
+                    self.masp_dir = self.wallet_dir.join("masp").join(network);
+                }
+                self.wallet = None;
+            }
+            None => {
+                self.wallet_dir = home.wallet_dir(DEFAULT_WALLET);
+                if is_unset(&self.masp_dir) {
+                    self.masp_dir = home.masp_dir(DEFAULT_WALLET, network);
+                }
+                self.wallet = Some(DEFAULT_WALLET.to_string());
+            }
+        }
+        Ok(())
+    }
+
+    // Build the SDK context (HTTP client, chain id, wallet and shielded dirs) for this profile
+    pub async fn build_sdk(
+        &self,
+    ) -> Result<NamadaImpl<HttpClient, CliWalletUtils, FsShieldedUtils, StdIo>> {
+        let url = Url::from_str(&self.rpc_url)
+            .map_err(|e| Error::Config(format!("Invalid RPC address {}: {}", self.rpc_url, e)))?;
+        let http_client = HttpClient::new(url)
+            .map_err(|e| Error::Rpc(format!("Failed to create HTTP client: {}", e)))?;
+        let chain_id = ChainId::from_str(&self.chain_id)
+            .map_err(|e| Error::Config(format!("Invalid chain ID {}: {}", self.chain_id, e)))?;
+
+        let wallet = CliWalletUtils::new(self.wallet_dir.clone());
+        let shielded_mut = FsShieldedUtils::new(self.masp_dir.clone());
+        let std_io = StdIo;
+
+        let sdk = NamadaImpl::new(http_client, wallet, shielded_mut, std_io)
+            .await
+            .map_err(|e| Error::WalletIo(format!("Unable to initialize Namada context: {}", e)))?
+            .chain_id(chain_id);
+        Ok(sdk)
+    }
+
+    /// The signer of this profile's txs: the remote one set in `signer`, or the
+    /// wallet of `sdk`.
+    pub fn signer<'a, C, U, V, I>(&self, sdk: &'a NamadaImpl<C, U, V, I>) -> Result<Box<dyn Signer + 'a>>
+    where
+        C: Client + MaybeSync + MaybeSend,
+        U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+        V: ShieldedUtils + MaybeSync + MaybeSend,
+        I: Io + MaybeSync + MaybeSend,
+    {
+        match &self.signer {
+            Some(endpoint) => Ok(Box::new(RemoteSigner::new(SignerEndpoint::from_str(endpoint)?))),
+            None => Ok(Box::new(WalletSigner::new(sdk))),
+        }
+    }
+}
+
+fn is_unset(dir: &Path) -> bool {
+    dir.as_os_str().is_empty()
+}
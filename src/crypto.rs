@@ -0,0 +1,58 @@
+//! Password based encryption for the secrets this crate keeps next to the wallet.
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use namada_sdk::zeroize::Zeroizing;
+use rand_core::{OsRng, RngCore};
+
+use crate::error::{Error, Result};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 24;
+
+/// Encrypt `plaintext` with a key derived from `password` (Argon2id, XChaCha20-Poly1305).
+///
+/// `aad` is authenticated but not encrypted, the same bytes must be given to
+/// [`decrypt`]. The output is `salt || nonce || ciphertext`.
+pub fn encrypt(password: &str, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut salt);
+    OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(password, &salt)?;
+    let ciphertext = XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+        .encrypt(XNonce::from_slice(&nonce), Payload { msg: plaintext, aad })
+        .map_err(|_| Error::Decryption("Unable to encrypt".to_string()))?;
+
+    let mut out = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    out.extend_from_slice(&salt);
+    out.extend_from_slice(&nonce);
+    out.extend_from_slice(&ciphertext);
+    Ok(out)
+}
+
+/// Decrypt the output of [`encrypt`]. Fails on a wrong password, tampered data or
+/// other `aad`.
+pub fn decrypt(password: &str, data: &[u8], aad: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+    if data.len() < SALT_LEN + NONCE_LEN {
+        return Err(Error::Decryption("Encrypted data is truncated".to_string()));
+    }
+    let (salt, rest) = data.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let key = derive_key(password, salt)?;
+    XChaCha20Poly1305::new(Key::from_slice(key.as_slice()))
+        .decrypt(XNonce::from_slice(nonce), Payload { msg: ciphertext, aad })
+        .map(Zeroizing::new)
+        .map_err(|_| Error::Decryption("Wrong password or corrupted data".to_string()))
+}
+
+fn derive_key(password: &str, salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    Argon2::default()
+        .hash_password_into(password.as_bytes(), salt, key.as_mut())
+        .map_err(|e| Error::Decryption(format!("Unable to derive key: {}", e)))?;
+    Ok(key)
+}
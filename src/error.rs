@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+
+use namada_core::address::Address;
+
+/// Errors returned by the wallet operations.
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("Config error: {0}")]
+    Config(String),
+    #[error("Wallet IO error: {0}")]
+    WalletIo(String),
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    #[error("Mnemonic backup check failed, nothing was saved")]
+    MnemonicNotVerified,
+    #[error("Decryption failed: {0}")]
+    Decryption(String),
+    #[error("Invalid {what}: {reason}")]
+    InvalidInput { what: &'static str, reason: String },
+    #[error("No address found for alias: {0}")]
+    UnknownAlias(String),
+    #[error("No key found for alias: {0}")]
+    UnknownKey(String),
+    #[error("Alias {0} is already in use")]
+    AliasTaken(String),
+    #[error(
+        "Wallet in use by {}: {} is locked, try again once it is done",
+        .pid.map_or_else(|| "another process".to_string(), |pid| format!("PID {}", pid)),
+        .path.display()
+    )]
+    Locked { path: PathBuf, pid: Option<u32> },
+    #[error("RPC request failed: {0}")]
+    Rpc(String),
+    #[error("Unable to build {tx}: {source}")]
+    TxBuild {
+        tx: &'static str,
+        source: namada_sdk::error::Error,
+    },
+    #[error("Signer error: {0}")]
+    Signer(String),
+    #[error("Failed to submit {tx}: {source}")]
+    Submission {
+        tx: &'static str,
+        source: namada_sdk::error::Error,
+    },
+    #[error("MASP indexer error: {0}")]
+    Indexer(String),
+    #[error("Shielded context error: {0}")]
+    Shielded(String),
+    #[error("{0} is watch-only, the wallet holds no key that can sign for it")]
+    WatchOnly(Address),
+    #[error("{address} needs {threshold} signature(s), only {held} of its keys can sign")]
+    ThresholdNotMet { address: Address, threshold: u8, held: usize },
+    #[error("Account {0} has not revealed its public key and no key to reveal was found")]
+    Unrevealed(Address),
+    #[error("{0} has not revealed its public key on chain, a dry run can not verify its txs")]
+    UnverifiableDryRun(Address),
+    #[error("Node is on chain {node}, expected {expected}")]
+    ChainIdMismatch { expected: String, node: String },
+    #[error("Node is not ready: {0}")]
+    NodeNotReady(String),
+}
+
+impl Error {
+    pub(crate) fn invalid(what: &'static str, reason: impl ToString) -> Self {
+        Error::InvalidInput {
+            what,
+            reason: reason.to_string(),
+        }
+    }
+}
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
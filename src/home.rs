@@ -0,0 +1,105 @@
+//! The wallet home: one directory holding several named wallets, each with its
+//! own `wallet.toml` and a MASP dir per network, so the wallet no longer depends
+//! on the directory the binary is started from.
+//!
+//! ```text
+//! <home>/wallets/<name>/wallet.toml
+//! <home>/wallets/<name>/masp/<network>/
+//! ```
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Wallet opened when none is named.
+pub const DEFAULT_WALLET: &str = "default";
+
+// Dir of the platform data dir used as the home when none is configured
+const HOME_DIR: &str = "namada-wallet";
+const WALLETS_DIR: &str = "wallets";
+const MASP_DIR: &str = "masp";
+
+/// A directory of named wallets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WalletHome {
+    root: PathBuf,
+}
+
+impl WalletHome {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        WalletHome { root: root.into() }
+    }
+
+    /// `namada-wallet` in the platform data dir, e.g. `~/.local/share/namada-wallet`.
+    pub fn platform_default() -> Result<Self> {
+        dirs::data_dir()
+            .map(|dir| WalletHome::new(dir.join(HOME_DIR)))
+            .ok_or_else(|| Error::Config("No data dir on this platform, set a wallet home".to_string()))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn wallet_dir(&self, name: &str) -> PathBuf {
+        self.root.join(WALLETS_DIR).join(name)
+    }
+
+    /// Shielded context of the wallet `name` on `network`.
+    pub fn masp_dir(&self, name: &str, network: &str) -> PathBuf {
+        self.wallet_dir(name).join(MASP_DIR).join(network)
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        self.wallet_dir(name).is_dir()
+    }
+
+    /// Names of the wallets in the home, sorted. Empty if the home does not exist yet.
+    pub fn wallets(&self) -> Result<Vec<String>> {
+        let dir = self.root.join(WALLETS_DIR);
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(Error::WalletIo(format!("Unable to read {}: {}", dir.display(), e))),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry.map_err(|e| Error::WalletIo(format!("Unable to read {}: {}", dir.display(), e)))?;
+            if !entry.path().is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str().filter(|name| check_wallet_name(name).is_ok()) {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Create the empty wallet `name`, its keys are added once it is opened.
+    ///
+    /// Fails if the name is not made of letters, digits, `-` and `_`, or already used.
+    pub fn create(&self, name: &str) -> Result<PathBuf> {
+        check_wallet_name(name)?;
+        let dir = self.wallet_dir(name);
+        if dir.exists() {
+            return Err(Error::invalid("wallet name", format!("{} already exists in {}", name, self.root.display())));
+        }
+        fs::create_dir_all(&dir)
+            .map_err(|e| Error::WalletIo(format!("Unable to create {}: {}", dir.display(), e)))?;
+        Ok(dir)
+    }
+}
+
+/// Wallet names become dir names, so only letters, digits, `-` and `_` are allowed.
+pub fn check_wallet_name(name: &str) -> Result<()> {
+    if name.is_empty() {
+        return Err(Error::invalid("wallet name", "it is empty"));
+    }
+    if !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(Error::invalid("wallet name", format!("{} may only contain letters, digits, - and _", name)));
+    }
+    Ok(())
+}
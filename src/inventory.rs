@@ -0,0 +1,201 @@
+use std::collections::BTreeMap;
+
+use namada_core::address::Address;
+use namada_sdk::key::PublicKeyHash;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::{
+    io::{Client, Io},
+    wallet::{Wallet, WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use serde::{Deserialize, Serialize};
+
+/// Kind of a wallet entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryKind {
+    Address,
+    PublicKey,
+    SpendingKey,
+    ViewingKey,
+    PaymentAddress,
+}
+
+/// Whether the wallet can spend or sign for an entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Custody {
+    /// The secret key is stored in clear
+    Secret,
+    /// The secret key is stored encrypted with a password
+    EncryptedSecret,
+    /// No secret key, the entry can only be watched
+    WatchOnly,
+    /// A payment address, only used to receive
+    ReceiveOnly,
+}
+
+/// One alias of the wallet and what is stored under it.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct WalletEntry {
+    pub kind: EntryKind,
+    pub alias: String,
+    pub value: String,
+    /// `implicit`, `established` or `internal`, for addresses
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address_type: Option<String>,
+    pub custody: Custody,
+    /// HD path the key was derived at, for keys derived from a mnemonic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub derivation_path: Option<String>,
+}
+
+/// Which entries to list. The default lists everything.
+#[derive(Clone, Debug, Default)]
+pub struct InventoryFilter {
+    /// Kinds to keep, all of them when empty
+    pub kinds: Vec<EntryKind>,
+    /// Case-insensitive substring the alias must contain
+    pub alias: Option<String>,
+}
+
+impl InventoryFilter {
+    fn matches(&self, kind: EntryKind, alias: &str) -> bool {
+        (self.kinds.is_empty() || self.kinds.contains(&kind))
+            && self
+                .alias
+                .as_ref()
+                .map_or(true, |needle| alias.to_lowercase().contains(&needle.to_lowercase()))
+    }
+}
+
+/// Every entry of the loaded wallet that passes `filter`, sorted by kind then alias.
+pub async fn list_wallet<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, filter: &InventoryFilter) -> Vec<WalletEntry>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let wallet = sdk.wallet().await;
+    let mut entries = BTreeMap::new();
+
+    // Secret keys and their public key hashes, by alias
+    let secret_keys: BTreeMap<String, (bool, Option<PublicKeyHash>)> = wallet
+        .get_secret_keys()
+        .into_iter()
+        .map(|(alias, (stored, pkh))| (alias, (stored.is_encrypted(), pkh.cloned())))
+        .collect();
+    let key_custody = |pkh: &PublicKeyHash| {
+        secret_keys
+            .values()
+            .find(|(_, key_pkh)| key_pkh.as_ref() == Some(pkh))
+            .map_or(Custody::WatchOnly, |(encrypted, _)| secret_custody(*encrypted))
+    };
+
+    for (alias, address) in wallet.get_addresses() {
+        let (address_type, custody, derivation_path) = match &address {
+            Address::Implicit(implicit) => {
+                let pkh = &implicit.0;
+                ("implicit", key_custody(pkh), derivation_path(&wallet, pkh))
+            }
+            Address::Established(_) => ("established", Custody::WatchOnly, None),
+            Address::Internal(_) => ("internal", Custody::WatchOnly, None),
+        };
+        entries.insert(
+            (EntryKind::Address, alias.clone()),
+            WalletEntry {
+                kind: EntryKind::Address,
+                alias,
+                value: address.to_string(),
+                address_type: Some(address_type.to_string()),
+                custody,
+                derivation_path,
+            },
+        );
+    }
+
+    for (alias, public_key) in wallet.get_public_keys() {
+        let pkh = PublicKeyHash::from(&public_key);
+        entries.insert(
+            (EntryKind::PublicKey, alias.clone()),
+            WalletEntry {
+                kind: EntryKind::PublicKey,
+                alias,
+                value: public_key.to_string(),
+                address_type: None,
+                custody: key_custody(&pkh),
+                derivation_path: derivation_path(&wallet, &pkh),
+            },
+        );
+    }
+
+    let spending_keys: BTreeMap<String, bool> = wallet
+        .get_spending_keys()
+        .into_iter()
+        .map(|(alias, stored)| (alias, stored.is_encrypted()))
+        .collect();
+
+    for (alias, encrypted) in &spending_keys {
+        // The key itself is never printed, only that it exists
+        entries.insert(
+            (EntryKind::SpendingKey, alias.clone()),
+            WalletEntry {
+                kind: EntryKind::SpendingKey,
+                alias: alias.clone(),
+                value: if *encrypted { "encrypted" } else { "unencrypted" }.to_string(),
+                address_type: None,
+                custody: secret_custody(*encrypted),
+                derivation_path: None,
+            },
+        );
+    }
+
+    for (alias, viewing_key) in wallet.get_viewing_keys() {
+        let custody = spending_keys
+            .get(&alias)
+            .map_or(Custody::WatchOnly, |encrypted| secret_custody(*encrypted));
+        entries.insert(
+            (EntryKind::ViewingKey, alias.clone()),
+            WalletEntry {
+                kind: EntryKind::ViewingKey,
+                alias,
+                value: viewing_key.to_string(),
+                address_type: None,
+                custody,
+                derivation_path: None,
+            },
+        );
+    }
+
+    for (alias, payment_address) in wallet.get_payment_addrs() {
+        entries.insert(
+            (EntryKind::PaymentAddress, alias.clone()),
+            WalletEntry {
+                kind: EntryKind::PaymentAddress,
+                alias,
+                value: payment_address.to_string(),
+                address_type: None,
+                custody: Custody::ReceiveOnly,
+                derivation_path: None,
+            },
+        );
+    }
+
+    entries
+        .into_values()
+        .filter(|entry| filter.matches(entry.kind, &entry.alias))
+        .collect()
+}
+
+fn secret_custody(encrypted: bool) -> Custody {
+    if encrypted {
+        Custody::EncryptedSecret
+    } else {
+        Custody::Secret
+    }
+}
+
+fn derivation_path<U: WalletIo>(wallet: &Wallet<U>, pkh: &PublicKeyHash) -> Option<String> {
+    wallet.find_path_by_pkh(pkh).ok().map(|path| path.to_string())
+}
@@ -0,0 +1,32 @@
+//! Namada wallet operations built on top of `namada_sdk`.
+//!
+//! Every operation takes a `NamadaImpl<C, U, V, I>` (see [`NetworkProfile::build_sdk`]
+//! for the usual HTTP/filesystem setup), so the same functions back the interactive
+//! binary, the examples and any service that depends on this crate.
+
+pub mod account;
+pub mod aliases;
+pub mod backup;
+pub mod config;
+pub mod crypto;
+pub mod error;
+pub mod home;
+pub mod inventory;
+pub mod lock;
+pub mod mnemonic;
+pub mod multisig;
+pub mod node;
+pub mod plan;
+pub mod query;
+pub mod recovery;
+pub mod shielded;
+pub mod signer;
+mod store;
+pub mod transfer;
+pub mod wallet;
+pub mod wallet_utils;
+pub mod watch;
+
+pub use config::{Config, NetworkProfile};
+pub use error::{Error, Result};
+pub use wallet_utils::CliWalletUtils;
@@ -0,0 +1,103 @@
+//! Advisory locks on the wallet and shielded context dirs, so two processes (the
+//! menu and a batch job, say) never both read, change and write the same files.
+//!
+//! The lock file holds the PID of its owner and the OS drops the lock when that
+//! process exits, so a crash never leaves the wallet locked. Within a process the
+//! lock is shared: locking a dir this process already holds succeeds.
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex, PoisonError, Weak};
+
+use fs2::FileExt;
+
+use crate::error::{Error, Result};
+
+/// Lock file created in every locked dir.
+pub const LOCK_FILE: &str = ".lock";
+
+// Locks held by this process, by canonical dir
+static HELD: Mutex<BTreeMap<PathBuf, Weak<LockFile>>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug)]
+struct LockFile {
+    file: File,
+}
+
+impl Drop for LockFile {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// An exclusive lock on a dir, released once every clone of it is dropped.
+#[derive(Clone, Debug)]
+pub struct DirLock {
+    _file: Arc<LockFile>,
+}
+
+impl DirLock {
+    /// Lock `dir`, creating it if needed.
+    ///
+    /// Fails with [`Error::Locked`] while another process holds the lock.
+    pub fn acquire(dir: &Path) -> Result<Self> {
+        let lock_error = |e: std::io::Error| Error::WalletIo(format!("Unable to lock {}: {}", dir.display(), e));
+        fs::create_dir_all(dir).map_err(lock_error)?;
+        let key = dir.canonicalize().map_err(lock_error)?;
+
+        let mut held = HELD.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(file) = held.get(&key).and_then(Weak::upgrade) {
+            return Ok(DirLock { _file: file });
+        }
+
+        // Not truncated on open, the owner's PID stays readable until it is locked
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(key.join(LOCK_FILE))
+            .map_err(lock_error)?;
+        if let Err(e) = file.try_lock_exclusive() {
+            if e.raw_os_error() == fs2::lock_contended_error().raw_os_error() {
+                return Err(Error::Locked { path: dir.to_path_buf(), pid: read_pid(&mut file) });
+            }
+            return Err(lock_error(e));
+        }
+        file.set_len(0)
+            .and_then(|()| file.write_all(process::id().to_string().as_bytes()))
+            .and_then(|()| file.sync_all())
+            .map_err(lock_error)?;
+
+        let file = Arc::new(LockFile { file });
+        held.insert(key, Arc::downgrade(&file));
+        Ok(DirLock { _file: file })
+    }
+}
+
+/// Locks on the wallet dir and the MASP dir of a profile, held for one
+/// read-modify-write cycle of `wallet.toml` and the shielded context.
+#[derive(Clone, Debug)]
+pub struct WalletLock {
+    _wallet: DirLock,
+    _masp: DirLock,
+}
+
+impl WalletLock {
+    pub fn acquire(wallet_dir: &Path, masp_dir: &Path) -> Result<Self> {
+        Ok(WalletLock {
+            _wallet: DirLock::acquire(wallet_dir)?,
+            _masp: DirLock::acquire(masp_dir)?,
+        })
+    }
+}
+
+fn read_pid(file: &mut File) -> Option<u32> {
+    let mut content = String::new();
+    file.read_to_string(&mut content).ok()?;
+    content.trim().parse().ok()
+}
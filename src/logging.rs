@@ -0,0 +1,55 @@
+use std::path::Path;
+use std::sync::Mutex;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_subscriber::fmt::format::FmtSpan;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer};
+
+// Filter used when RUST_LOG is not set
+const DEFAULT_CONSOLE_FILTER: &str = "warn";
+const DEFAULT_FILE_FILTER: &str = "info";
+
+const LOG_FILE_PREFIX: &str = "wallet-example.log";
+
+// Flushes the file writer when dropped
+static FILE_GUARD: Mutex<Option<WorkerGuard>> = Mutex::new(None);
+
+// Install the global subscriber: human readable logs on stderr and, with a log dir,
+// JSON lines in a daily rotated file. Both honour RUST_LOG. Span close events carry
+// the span fields and timings.
+pub fn init(log_dir: Option<&Path>) {
+    let console = fmt::layer()
+        .with_writer(std::io::stderr)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_filter(env_filter(DEFAULT_CONSOLE_FILTER));
+
+    let (file, guard) = match log_dir {
+        Some(dir) => {
+            let appender = tracing_appender::rolling::daily(dir, LOG_FILE_PREFIX);
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let layer = fmt::layer()
+                .json()
+                .with_current_span(true)
+                .with_span_list(true)
+                .with_span_events(FmtSpan::CLOSE)
+                .with_writer(writer)
+                .with_filter(env_filter(DEFAULT_FILE_FILTER));
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry().with(console).with(file).init();
+    *FILE_GUARD.lock().unwrap() = guard;
+}
+
+// Flush pending file logs, call before exiting
+pub fn shutdown() {
+    FILE_GUARD.lock().unwrap().take();
+}
+
+fn env_filter(default: &str) -> EnvFilter {
+    EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(default))
+}
@@ -1,902 +1,1079 @@
-use tokio;
-use std::str::FromStr;
 use std::io::{self, Write};
-use std::error::Error;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use clap::Parser;
+use colored::*;
+use namada_core::address::Address;
 use namada_sdk::{
-    MaybeSend, 
-    MaybeSync,
-    args::{InputAmount, TxTransparentTransferData, TxBuilder}, 
-    io::{StdIo, Io, Client}, 
-    masp::{ShieldedUtils, fs::FsShieldedUtils}, 
-    wallet::{WalletIo, DerivationPath, WalletStorage, fs::FsWalletUtils}, 
-    Namada, 
-    NamadaImpl, 
-    chain::ChainId,
-    zeroize::Zeroizing,
-    bip39::Mnemonic,
-    key::SchemeType,
-    rpc, // Importing RPC for account info
+    args::TxBuilder,
+    io::{Client, Io, StdIo},
+    masp::{fs::FsShieldedUtils, ShieldedUtils},
+    wallet::{WalletIo, WalletStorage},
+    rpc, token,
+    MaybeSend, MaybeSync, Namada, NamadaImpl, PaymentAddress,
 };
-use tokio::runtime::Runtime;
-use namada_core::address::Address;
-use namada_sdk::masp::IndexerMaspClient;
-use namada_sdk::args;
-use namada_sdk::signing::default_sign;
-use namada_sdk::ExtendedViewingKey;
-use namada_sdk::PaymentAddress;
-use rand_core::OsRng;
-use namada_sdk::masp::find_valid_diversifier;
-use namada_core::key::common::CommonPublicKey;
-use tendermint_rpc::{HttpClient, Url};
-use toml::Value;
-use tokio::fs;
-use std::time::Duration;
-use namada_sdk::ExtendedSpendingKey;
-use namada_sdk::control_flow::install_shutdown_signal;
-use namada_sdk::wallet::DatedSpendingKey;
-use namada_sdk::masp::{MaspLocalTaskEnv, ShieldedSyncConfig};
-use namada_ibc::core::host::types::identifiers::ChannelId;
-use namada_sdk::TransferSource;
-use anyhow::Result;
-use namada_sdk::token;
-use std::error::Error as StdError; 
-use namada_sdk::account::Account;
-use namada_sdk::key::common;
-use namada_sdk::masp::shielded_wallet::ShieldedApi;
-use namada_core::address::MASP;
-use namada_core::masp::MaspEpoch;
-use namada_sdk::tx::either::IntoEither;
-use eyre::Report;
-use either::Either;
-use namada_core::uint::I256;
-use masp_primitives::zip32::ExtendedFullViewingKey;
-use masp_primitives::sapling::ViewingKey;
-use std::io::Cursor;
-use namada_sdk::io::{display, display_line, edisplay_line};
-use colored::*; 
-
-const RPC_URL: &str = "https://rpc.knowable.run:443"; // RPC URL
-const CHAIN_ID: &str = "housefire-cotton.d3c912fee7462"; // Chain ID
-const OWNER_ADDRESS: &str = "tnam1qze5x6au3egfnq7qp963c793cev5z5jvkcufnfhj"; // Just a placeholder to check if address is reveal pk or not
+use tendermint_rpc::HttpClient;
+use wallet_example::account::{self, AccountUpdate};
+use wallet_example::backup::{self, Backup, RestoreMode};
+use wallet_example::config::{Config, NetworkProfile};
+use wallet_example::home::WalletHome;
+use wallet_example::inventory::{self, EntryKind, InventoryFilter};
+use wallet_example::lock::WalletLock;
+use wallet_example::mnemonic::{MnemonicVault, PendingMnemonic};
+use wallet_example::multisig::{self, Proposal, SignatureFile};
+use wallet_example::plan::{self, OnError, Plan, StepOutput};
+use wallet_example::recovery::{self, DEFAULT_GAP_LIMIT};
+use wallet_example::signer::Signer;
+use wallet_example::wallet::KeyOptions;
+use wallet_example::wallet_utils::{
+    prompt_backup_check, prompt_mnemonic_passphrase, prompt_new_password, prompt_password, prompt_secret, read_secret,
+};
+use wallet_example::{aliases, node, query, shielded, transfer, wallet, watch, CliWalletUtils, Error, Result};
+
+mod cli;
+mod logging;
+mod output;
+
+use cli::{
+    AddressCommand, BalanceArgs, BatchArgs, Cli, Command, DerivationArgs, IbcTransferArgs, KeyCommand, KeyScheme,
+    PaymentAddressCommand, RevealStatusArgs, ShieldArgs, ShieldedSyncArgs, SpendingKeyCommand, TransferArgs,
+    InitAccountArgs, MultisigCommand, UpdateAccountArgs, WalletCommand, WalletsCommand, WatchCommand,
+};
+use output::{AccountDiffPreview, BackupPreview, OutputFormat, ProposalPreview, Report, StepReport, StepStatus, TxReport};
+
+// Defaults offered by the interactive menu
+const DEFAULT_SOURCE_ALIAS: &str = "rilsso-public";
+const DEFAULT_SHIELDED_TARGET: &str = "znam1jk5dkka9gj8wqtkky5tgzy76heapcdg8r3aqn9syr9k3nmx6ms8wn3hdew79tptg9kfds960a2u";
+const DEFAULT_TRANSPARENT_TARGET: &str = "tnam1qpu63hxasnmsq25juqfcql287mvkgjx3vuvgzktz";
+const DEFAULT_IBC_RECEIVER: &str = "cosmos1qqzg5khvcfdgnjg4wghvxcnekxwu4kg5nuwjssjt";
+const DEFAULT_IBC_CHANNEL: &str = "channel-0";
+const DEFAULT_BALANCE_OWNER: &str = "tnam1qqteapc3ycthpehxtqadv6nx2grr5gptzs2ptyvy";
+const DEFAULT_BALANCE_TOKEN: &str = "tnam1qy440ynh9fwrx8aewjvvmu38zxqgukgc259fzp6h";
+const DEFAULT_PAYMENT_ALIAS: &str = "default";
+const DEFAULT_VIEWING_KEY: &str = "zvknam1qddsrtp4qqqqpqr6t24a76wu3gdszc0jw8r0643mhfs3sgx49cftd8qjtetl4a5aa24fmryf29uz7xkqket0exqm8vkky8w99uqjl80cl290uqfev3yegg3ym4z84x5gwruuw4t2ln26wkadckksfkfu8ku6jdqjryvdvtlq3x8atu9p3lk7a86wals57zp7dfnydr8088pmflt6c2zgwjnzdnrsfy4v3r85gf2my2ynzqtug4euewsj0ps6upqrw524jw5g5cyecjq4c8gjy";
+const DEFAULT_SHIELDED_KEY_ALIAS: &str = "default";
 
 #[tokio::main]
 async fn main() {
-    let url = Url::from_str(RPC_URL).expect("Invalid RPC address");
-    let http_client = HttpClient::new(url).expect("Failed to create HTTP client");
-
-    let wallet = FsWalletUtils::new("./sdk-wallet".into());
-    let shielded_mut = FsShieldedUtils::new("./masp".into());
-    let std_io = StdIo;
+    let args = Cli::parse();
+    let output = args.output;
+    logging::init(args.log_dir.as_deref());
+
+    // Network profile from namada-wallet.toml, with env overrides applied and
+    // the wallet opened from the wallet home
+    let config = match &args.config {
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    }
+    .unwrap_or_else(|e| exit_with_error(output, e));
+    let home = config
+        .wallet_home(args.wallet_home.as_deref())
+        .unwrap_or_else(|e| exit_with_error(output, e));
+    let (mut network, mut profile) = config
+        .active_profile_in(args.network.as_deref(), &home, args.wallet.as_deref())
+        .unwrap_or_else(|e| exit_with_error(output, e));
+    notice(output, &format!("Using network {} ({})", network, profile.chain_id));
+    notice(output, &format!("Using wallet {}", wallet_label(&profile)));
+    if let Some(dir) = profile.unused_legacy_wallet() {
+        let message = format!(
+            "Warning: the wallet in {} is not used, wallets now live in the wallet home. \
+             Set wallet_dir = \"{}\" in the network profile (or NAMADA_WALLET_DIR) to keep using it.",
+            dir.display(),
+            dir.display()
+        );
+        eprintln!("{}", message.yellow());
+    }
+    if let Some(signer) = &profile.signer {
+        notice(output, &format!("Signing through {}", signer));
+    }
 
-    let sdk = NamadaImpl::new(http_client, wallet, shielded_mut, std_io)
-        .await
-        .expect("Unable to initialize Namada context")
-        .chain_id(ChainId::from_str(CHAIN_ID).expect("Invalid chain ID"));
+    let mut sdk = profile.build_sdk().await.unwrap_or_else(|e| exit_with_error(output, e));
 
     // Load existing wallet
     if sdk.wallet_mut().await.load().is_ok() {
-        println!("Existing wallet found");
+        notice(output, "Existing wallet found");
     } else {
-        println!("No existing wallet found.");
+        notice(output, "No existing wallet found.");
+    }
+
+    // Offline operations still work against a bad node, transactions are refused later
+    warn_if_node_unhealthy(&sdk).await;
+
+    // Non-interactive mode
+    if let Some(command) = args.command {
+        // A dry-run batch only simulates its txs on the node
+        let sdk = match &command {
+            Command::Batch(batch) if batch.dry_run => sdk.dry_run(true),
+            _ => sdk,
+        };
+        let _lock = lock_wallet(&sdk, &profile).await.unwrap_or_else(|e| exit_with_error(output, e));
+        match run_command(&sdk, &profile, &home, command).await {
+            Ok(report) => {
+                output.emit(&report);
+                if report.is_failure() {
+                    logging::shutdown();
+                    std::process::exit(1);
+                }
+            }
+            Err(e) => exit_with_error(output, e),
+        }
+        logging::shutdown();
+        return;
     }
 
     loop {
-        display_menu();
-
-        let choice = get_user_choice();
-        match choice {
-            1 => create_wallet(&sdk).await,
-            2 => add_key(&sdk).await,
-            3 => print_address(&sdk).await,
-            4 => create_spending_key(&sdk).await,
-            5 => generate_payment_address(&sdk).await,
-            6 => send_token_shielded(&sdk).await, 
-            7 => check_if_revealed(&sdk).await, // New option to check if account is revealed
-            8 => shielded_sync(&sdk).await.expect("Failed to sync shielded context"),
-            9 => send_transparent_token(&sdk).await,
-            10 => send_ibc_token(&sdk).await,
-            11 => {
-                
-                match get_token_balance(&sdk).await {
-                    Ok(balance) => println!("Token Balance: {:?}", balance),
-                    Err(e) => eprintln!("Error fetching token balance: {}", e),
+        display_menu(&network, &profile);
+
+        let choice = get_user_choice().unwrap_or_else(|e| exit_with_error(output, e));
+        let command = match choice {
+            30 => {
+                if let Some((new_profile, new_sdk)) = switch_wallet(&home, &network, &profile).await {
+                    profile = new_profile;
+                    sdk = new_sdk;
                 }
+                continue;
             },
-            12 => {
-                let epoch = query_and_print_masp_epoch(&sdk).await; // Capture the returned epoch
-                println!("Fetched masp epoch: {:?}", epoch); // Do something with epoch
+            31 => {
+                let wallet = profile.wallet.clone();
+                if let Some((new_network, new_profile, new_sdk)) =
+                    switch_network(&config, &home, &network, wallet.as_deref()).await
+                {
+                    network = new_network;
+                    profile = new_profile;
+                    sdk = new_sdk;
+                }
+                continue;
             },
-            13 => {
-                println!("Exiting...");
+            32 => {
+                eprintln!("Exiting...");
                 break;
             },
-            _ => println!("Invalid choice, please enter a valid option."),
+            choice => match prompt_command(choice) {
+                Ok(Some(command)) => command,
+                Ok(None) => continue,
+                // Without input the menu can not go on
+                Err(e) => exit_with_error(output, e),
+            },
+        };
+
+        // Report the failure and keep the menu running
+        let result = match lock_wallet(&sdk, &profile).await {
+            Ok(_lock) => run_command(&sdk, &profile, &home, command).await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(report) => output.emit(&report),
+            Err(e) => output.emit_error(&e),
         }
     }
+    logging::shutdown();
 }
-// Display menu options
-fn display_menu() {
-    println!("\nNamada wallet example:");
-    println!("1. Create a new wallet");
-    println!("2. Add a new key from a mnemonic");
-    println!("3. Print an address from the wallet");
-    println!("4. Create a spending key");
-    println!("5. Generate a payment address");
-    println!("6. Send tokens");
-    println!("7. Check if account is revealed");
-    println!("8. Shielded Sync"); // New option for shielded sync
-    println!("9. Transparent Token Transfer"); // Added for transparent token transfer
-    println!("10. IBC Token Transfer"); // Added IBC transfer
-    println!("11. Fetch balance"); 
-    println!("12. Fetch epoch"); // fetch masp epoch
-    println!("13. Exit");
-}
-
-// User input here
-fn get_user_choice() -> usize {
-    print!("Enter your choice: ");
-    io::stdout().flush().expect("Failed to flush stdout");
 
-    let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-
-    input.trim().parse::<usize>().unwrap_or(0) // Default to 0 if parsing fails
+fn exit_with_error(output: OutputFormat, e: Error) -> ! {
+    output.emit_error(&e);
+    logging::shutdown();
+    std::process::exit(1);
 }
 
-// Create a new wallet
-async fn create_wallet<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let mnemonic = Mnemonic::new(namada_sdk::bip39::MnemonicType::Words24, namada_sdk::bip39::Language::English);
-    let phrase = mnemonic.phrase();
-
-    println!("Generated mnemonic: {}", phrase);
-    let alias = prompt_user("Enter an alias for the new wallet: ");
-
-    let derivation_path = DerivationPath::default_for_transparent_scheme(SchemeType::Ed25519);
-    let (_key_alias, _sk) = sdk.wallet_mut().await
-        .derive_store_key_from_mnemonic_code(
-            SchemeType::Ed25519,
-            Some(alias),
-            true,
-            derivation_path,
-            Some((mnemonic.clone(), Zeroizing::new("".to_owned()))),
-            true,
-            None,
-        ).expect("Unable to derive key from mnemonic code");
-
-    sdk.wallet().await.save().expect("Could not save wallet!");
-    println!("Wallet created and saved!");
+// Startup chatter goes to stderr in JSON mode so stdout only carries reports
+fn notice(output: OutputFormat, message: &str) {
+    match output {
+        OutputFormat::Text => println!("{}", message),
+        OutputFormat::Json => eprintln!("{}", message),
+    }
 }
 
-
-// shielded synnc
-async fn shielded_sync<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<(), Box<dyn Error>>
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let spend_key = ExtendedSpendingKey::from_str("zsknam1q0medj45qqqqpq9wh90qd9c7d9f7n5xxn89h6dl54k0jfmucwn4yk7nykxwcrjmk4ylkdnlnn3wkkd9f3ul3nyw8hv5wlsfgklzr5ghzk2spzzwm05csvl2s3rn0aq7f9w4z7guul682yrw4hsmren2k2lgdp003uuj00lsd8nlevc8n32sz6j350up209980c04qdqcu97vh5476xv423k6jp58qn7hqjf9nvsvk8p8g5yfmqguu039q34c2euzhcpnca7vpp4pelqu6y87k")
-        .expect("Invalid spending key");
-
-    let dated_key = DatedSpendingKey::try_from(spend_key).expect("Error reading spend key");
-
-    // create a thread pool for the shielded sync
-    let env = MaspLocalTaskEnv::new(500).expect("could not create masp env");
-
-    // progress bars for displaying sync progress
-    let fetched = kdam::tqdm!(
-        total = 0,
-        desc = "fetched ",
-        animation = "fillup",
-        position = 0,
-        force_refresh = true,
-        dynamic_ncols = true,
-        miniters = 0,
-        mininterval = 0.05
-    );
-
-    let scanned = kdam::tqdm!(
-        total = 0,
-        desc = "scanned ",
-        animation = "fillup",
-        position = 1,
-        force_refresh = true,
-        dynamic_ncols = true,
-        miniters = 0,
-        mininterval = 0.05
-    );
-
-    let applied = kdam::tqdm!(
-        total = 0,
-        desc = "applied ",
-        animation = "fillup",
-        position = 2,
-        force_refresh = true,
-        dynamic_ncols = true,
-        miniters = 0,
-        mininterval = 0.05
-    );
-
-    // create a masp client to sync from the masp-indexer
-    let client = reqwest::Client::builder()
-        .connect_timeout(Duration::from_secs(60))
-        .build()
-        .expect("Failed to build HTTP client"); // This will panic if an error occurs
-
-    let endpoint = "https://masp.knowable.run/api/v1".to_string();
- 
-    let url = endpoint.as_str().try_into().map_err(|err| {
-        format!("Failed to parse API endpoint {endpoint:?}: {err}")
-      
-    })?;
-
-    let shielded_client = IndexerMaspClient::new
-    (client,
-         url, true, 
-         100);
-
-
-
-    let config = ShieldedSyncConfig::builder()
-        .client(shielded_client)
-        .fetched_tracker(fetched)
-        .scanned_tracker(scanned)
-        .applied_tracker(applied)
-        .shutdown_signal(install_shutdown_signal(false))
-        .build();
-
-    // shielded sync and save the results
-    println!("Syncing shielded context");
-    sdk.shielded_mut().await.sync(env, config, None, &[dated_key], &[]).await.expect("Could not sync shielded context");
-     
-    println!("Shielded context synced");
-
-    Ok(())
+// Display menu options
+fn display_menu(network: &str, profile: &NetworkProfile) {
+    eprintln!("\nNamada wallet example:");
+    eprintln!("{}", format!("Network: {} | Chain: {} | RPC: {}", network, profile.chain_id, profile.rpc_url).cyan());
+    eprintln!("{}", format!("Wallet: {}", wallet_label(profile)).cyan());
+    eprintln!("1. Create a new wallet");
+    eprintln!("2. Add a new key from a mnemonic");
+    eprintln!("3. Print an address from the wallet");
+    eprintln!("4. Create a spending key");
+    eprintln!("5. Generate a payment address");
+    eprintln!("6. Send tokens");
+    eprintln!("7. Check if account is revealed");
+    eprintln!("8. Shielded Sync"); // New option for shielded sync
+    eprintln!("9. Transparent Token Transfer"); // Added for transparent token transfer
+    eprintln!("10. IBC Token Transfer"); // Added IBC transfer
+    eprintln!("11. Fetch balance"); 
+    eprintln!("12. Fetch epoch"); // fetch masp epoch
+    eprintln!("13. Node status");
+    eprintln!("14. Show a stored mnemonic");
+    eprintln!("15. Recover accounts from a mnemonic");
+    eprintln!("16. List wallet");
+    eprintln!("17. Rename a wallet entry");
+    eprintln!("18. Delete a wallet entry");
+    eprintln!("19. Back up the wallet");
+    eprintln!("20. Restore a wallet backup");
+    eprintln!("21. Watch an address");
+    eprintln!("22. Watch a viewing key");
+    eprintln!("23. Import a secret key");
+    eprintln!("24. Import a spending key");
+    eprintln!("25. Update an account's keys");
+    eprintln!("26. Create a multisig account");
+    eprintln!("27. Propose a multisig transfer");
+    eprintln!("28. Sign a multisig proposal");
+    eprintln!("29. Submit a multisig proposal");
+    eprintln!("30. Open or create a wallet");
+    eprintln!("31. Switch network");
+    eprintln!("32. Exit");
 }
 
+// Name and dir of the open wallet, only the dir when it is pinned by the config
+fn wallet_label(profile: &NetworkProfile) -> String {
+    match &profile.wallet {
+        Some(name) => format!("{} ({})", name, profile.wallet_dir.display()),
+        None => profile.wallet_dir.display().to_string(),
+    }
+}
 
-// Add a key from a mnemonic
-async fn add_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let phrase = prompt_user("Enter the mnemonic: ");
-    let alias = prompt_user("Enter an alias: ");
-    let mnemonic = Mnemonic::from_phrase(&phrase, namada_sdk::bip39::Language::English).expect("Invalid mnemonic");
-
-    let derivation_path = DerivationPath::default_for_transparent_scheme(SchemeType::Ed25519);
-    let (_key_alias, _sk) = sdk.wallet_mut().await
-        .derive_store_key_from_mnemonic_code(
-            SchemeType::Ed25519,
-            Some(alias),
-            true,
-            derivation_path,
-            Some((mnemonic.clone(), Zeroizing::new("".to_owned()))),
-            true,
-            None,
-        ).expect("Unable to derive key from mnemonic code");
-
-    sdk.wallet().await.save().expect("Could not save wallet!");
-    println!("Key added successfully!");
+// User input here
+fn get_user_choice() -> Result<usize> {
+    Ok(prompt_user("Enter your choice: ")?.parse::<usize>().unwrap_or(0)) // Default to 0 if parsing fails
 }
 
-// Print an address associated with an alias
-async fn print_address<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let alias = prompt_user("Which alias do you want to look up? ");
-    match sdk.wallet().await.find_address(&alias) {
-        Some(address) => println!("Address for {}: {:?}", alias, address),
-        None => println!("No address found for alias: {}", alias),
-    }
+// Ask for what a menu choice needs, `None` when an answer is invalid
+fn prompt_command(choice: usize) -> Result<Option<Command>> {
+    let command = match choice {
+        1 => {
+            let alias = prompt_user("Enter an alias for the new wallet: ")?;
+            let words = match prompt_default("Number of words (12, 15, 18, 21 or 24)", "24")?.parse() {
+                Ok(words) => words,
+                Err(_) => {
+                    eprintln!("Invalid number of words.");
+                    return Ok(None);
+                }
+            };
+            let bip39_passphrase = prompt_yes_no("Use a BIP39 passphrase?")?;
+            let Some(derivation) = prompt_derivation()? else { return Ok(None) };
+            Command::Wallet(WalletCommand::Create { alias, words, bip39_passphrase, derivation, force: false })
+        },
+        2 => {
+            let mnemonic = prompt_user("Enter the mnemonic: ")?;
+            let alias = prompt_user("Enter an alias: ")?;
+            let bip39_passphrase = prompt_yes_no("Was the key derived with a BIP39 passphrase?")?;
+            let Some(derivation) = prompt_derivation()? else { return Ok(None) };
+            Command::Key(KeyCommand::Add { alias, mnemonic: Some(mnemonic), bip39_passphrase, derivation, force: false })
+        },
+        3 => Command::Address(AddressCommand::Show {
+            alias: prompt_user("Which alias do you want to look up? ")?,
+        }),
+        4 => {
+            let mnemonic = prompt_user("Enter the mnemonic for the spending key: ")?;
+            let alias = prompt_user("Enter an alias for the spending key: ")?;
+            let bip39_passphrase = prompt_yes_no("Was the key derived with a BIP39 passphrase?")?;
+            Command::SpendingKey(SpendingKeyCommand::Create {
+                alias,
+                mnemonic: Some(mnemonic),
+                bip39_passphrase,
+                force: false,
+            })
+        },
+        5 => Command::PaymentAddress(PaymentAddressCommand::Gen {
+            alias: prompt_default("Alias for the payment address", DEFAULT_PAYMENT_ALIAS)?,
+            viewing_key: prompt_default("Viewing key or its alias", DEFAULT_VIEWING_KEY)?,
+            force: false,
+        }),
+        6 => Command::Shield(ShieldArgs {
+            source: prompt_default("Source alias", DEFAULT_SOURCE_ALIAS)?,
+            target: prompt_default("Shielded target", DEFAULT_SHIELDED_TARGET)?,
+            amount: prompt_default("Amount", "1")?,
+            token: None,
+        }),
+        7 => Command::RevealStatus(RevealStatusArgs {
+            owner: prompt_user("Enter the address or alias to check: ")?,
+        }),
+        8 => Command::ShieldedSync(ShieldedSyncArgs {
+            key: prompt_default("Spending key, viewing key or alias", DEFAULT_SHIELDED_KEY_ALIAS)?,
+        }),
+        9 => Command::Transfer(TransferArgs {
+            source: prompt_default("Source alias", DEFAULT_SOURCE_ALIAS)?,
+            target: prompt_default("Target address", DEFAULT_TRANSPARENT_TARGET)?,
+            amount: prompt_default("Amount", "10")?,
+            token: None,
+        }),
+        10 => Command::IbcTransfer(IbcTransferArgs {
+            source: prompt_default("Source alias", DEFAULT_SOURCE_ALIAS)?,
+            receiver: prompt_default("IBC receiver", DEFAULT_IBC_RECEIVER)?,
+            channel_id: prompt_default("Channel id", DEFAULT_IBC_CHANNEL)?,
+            amount: prompt_default("Amount", "10")?,
+            token: None,
+        }),
+        11 => Command::Balance(BalanceArgs {
+            owner: prompt_default("Owner address or alias", DEFAULT_BALANCE_OWNER)?,
+            token: Some(prompt_default("Token address or alias", DEFAULT_BALANCE_TOKEN)?),
+        }),
+        12 => Command::MaspEpoch,
+        13 => Command::NodeStatus,
+        14 => Command::Wallet(WalletCommand::ShowMnemonic {
+            alias: prompt_user("Which key's mnemonic do you want to see? ")?,
+        }),
+        15 => {
+            let mnemonic = prompt_user("Enter the mnemonic to scan: ")?;
+            let alias = prompt_user("Alias for the first account: ")?;
+            let bip39_passphrase = prompt_yes_no("Were the keys derived with a BIP39 passphrase?")?;
+            let scheme = prompt_scheme()?;
+            let gap_limit = match prompt_default("Gap limit", &DEFAULT_GAP_LIMIT.to_string())?.parse() {
+                Ok(gap_limit) => gap_limit,
+                Err(_) => {
+                    eprintln!("Invalid gap limit.");
+                    return Ok(None);
+                }
+            };
+            Command::Key(KeyCommand::Recover {
+                alias,
+                mnemonic: Some(mnemonic),
+                bip39_passphrase,
+                scheme,
+                gap_limit,
+                token: Vec::new(),
+            })
+        },
+        16 => Command::Wallet(WalletCommand::List {
+            kind: Vec::new(),
+            alias: Some(prompt_user("Filter by alias (empty for all): ")?).filter(|alias| !alias.is_empty()),
+        }),
+        17 => Command::Wallet(WalletCommand::Rename {
+            alias: prompt_user("Alias to rename: ")?,
+            new_alias: prompt_user("New alias: ")?,
+            kind: Vec::new(),
+            yes: false,
+        }),
+        18 => Command::Wallet(WalletCommand::Delete {
+            alias: prompt_user("Alias to delete: ")?,
+            kind: Vec::new(),
+            yes: false,
+        }),
+        19 => Command::Wallet(WalletCommand::Backup {
+            file: PathBuf::from(prompt_user("Backup file: ")?),
+            shielded: prompt_yes_no("Also back up the shielded context?")?,
+        }),
+        20 => Command::Wallet(WalletCommand::Restore {
+            file: PathBuf::from(prompt_user("Backup file: ")?),
+            overwrite: prompt_yes_no("Replace the wallet instead of merging the backup into it?")?,
+            yes: false,
+        }),
+        21 => Command::Watch(WatchCommand::Address {
+            alias: prompt_user("Alias for the watched address: ")?,
+            address: prompt_user("Address: ")?,
+            force: false,
+        }),
+        22 => {
+            let alias = prompt_user("Alias for the watched viewing key: ")?;
+            let viewing_key = prompt_user("Viewing key: ")?;
+            let Some(birthday) = prompt_birthday()? else { return Ok(None) };
+            Command::Watch(WatchCommand::ViewingKey { alias, viewing_key, birthday, force: false })
+        },
+        23 => Command::Key(KeyCommand::Import {
+            alias: prompt_user("Alias for the key: ")?,
+            secret_key_file: None,
+            scheme: prompt_scheme()?,
+            force: false,
+        }),
+        24 => {
+            let alias = prompt_user("Alias for the spending key: ")?;
+            let Some(birthday) = prompt_birthday()? else { return Ok(None) };
+            Command::SpendingKey(SpendingKeyCommand::Import { alias, spending_key_file: None, birthday, force: false })
+        },
+        25 => {
+            let account = prompt_user("Account alias or address: ")?;
+            let public_keys = prompt_list("New public keys or their aliases, comma separated: ")?;
+            let threshold = match prompt_user("Threshold (empty to keep the current one): ")?.as_str() {
+                "" => None,
+                threshold => match threshold.parse() {
+                    Ok(threshold) => Some(threshold),
+                    Err(_) => {
+                        eprintln!("Invalid threshold: {}", threshold);
+                        return Ok(None);
+                    }
+                },
+            };
+            let vp_code_path = Some(prompt_user("Validity predicate wasm (empty to keep the current one): ")?)
+                .filter(|path| !path.is_empty())
+                .map(PathBuf::from);
+            Command::UpdateAccount(UpdateAccountArgs { account, public_keys, threshold, vp_code_path, yes: false })
+        },
+        26 => {
+            let alias = prompt_user("Alias for the new account: ")?;
+            let public_keys = prompt_list("Public keys or their aliases, comma separated: ")?;
+            let threshold = match prompt_default("Threshold", "1")?.parse() {
+                Ok(threshold) => threshold,
+                Err(_) => {
+                    eprintln!("Invalid threshold.");
+                    return Ok(None);
+                }
+            };
+            let fee_payer = prompt_default("Fee payer alias", DEFAULT_SOURCE_ALIAS)?;
+            Command::InitAccount(InitAccountArgs { alias, public_keys, threshold, fee_payer, vp_code_path: None })
+        },
+        27 => Command::Multisig(MultisigCommand::Propose {
+            account: prompt_user("Multisig account alias or address: ")?,
+            target: prompt_default("Target address or alias", DEFAULT_TRANSPARENT_TARGET)?,
+            amount: prompt_user("Amount: ")?,
+            token: None,
+            fee_payer: prompt_default("Fee payer alias or public key", DEFAULT_SOURCE_ALIAS)?,
+            out: PathBuf::from(prompt_user("Proposal file: ")?),
+        }),
+        28 => Command::Multisig(MultisigCommand::Sign {
+            proposal: PathBuf::from(prompt_user("Proposal file: ")?),
+            keys: Vec::new(),
+            yes: false,
+        }),
+        29 => Command::Multisig(MultisigCommand::Submit {
+            proposal: PathBuf::from(prompt_user("Proposal file: ")?),
+            signatures: prompt_list("Signature files, comma separated: ")?.into_iter().map(PathBuf::from).collect(),
+        }),
+        _ => {
+            eprintln!("Invalid choice, please enter a valid option.");
+            return Ok(None);
+        },
+    };
+    Ok(Some(command))
 }
 
-// Create a new spending key
-async fn create_spending_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
+// Run a single operation, shared by the subcommands and the interactive menu
+async fn run_command<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    profile: &NetworkProfile,
+    home: &WalletHome,
+    command: Command,
+) -> Result<Report>
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    let phrase = prompt_user("Enter the mnemonic for the spending key: ");
-    let spending_alias = prompt_user("Enter an alias for the spending key: ");
-    let mnemonic = Mnemonic::from_phrase(&phrase, namada_sdk::bip39::Language::English).expect("Invalid mnemonic");
-
-    let spending_derivation_path = DerivationPath::default_for_shielded();
-    let (_spending_key_alias, sk_spending) = sdk.wallet_mut().await
-        .derive_store_spending_key_from_mnemonic_code(
-            spending_alias.clone(),
-            true,
-            None,
-            spending_derivation_path,
-            Some((mnemonic.clone(), Zeroizing::new("".to_owned()))),
-            true,
-            None,
-        ).expect("Unable to derive spending key from mnemonic");
-
-    println!("Derived spending key: {:?}", sk_spending);
-    sdk.wallet().await.save().expect("Could not save wallet!");
-    println!("Spending key created and saved!");
+    let signer = profile.signer(sdk)?;
+    let signer = signer.as_ref();
+    let report = match command {
+        Command::Wallet(WalletCommand::Create { alias, words, bip39_passphrase, derivation, force }) => {
+            // Checked first, so a taken alias does not cost the user a backup check
+            wallet::check_transparent_alias(sdk, &alias, force).await?;
+            // Nothing is stored unless the user proves the phrase was written down
+            let pending = PendingMnemonic::generate(words)?;
+            let mnemonic = prompt_backup_check(&pending)?;
+            let options = prompt_key_options(bip39_passphrase, derivation, force)?;
+            let alias = wallet::create_wallet(sdk, alias, &mnemonic, &options).await?;
+            remember_mnemonic(profile, &alias, mnemonic.phrase(), &options)?;
+            let address = wallet::find_address(sdk, &alias).await.map(|a| a.to_string());
+            Report::WalletCreated { alias, address }
+        }
+        Command::Wallet(WalletCommand::ShowMnemonic { alias }) => {
+            let password = prompt_password(false, Some(&alias))?;
+            let mnemonic = MnemonicVault::new(&profile.wallet_dir).reveal(&alias, &password)?;
+            Report::Mnemonic { alias, mnemonic: mnemonic.to_string() }
+        }
+        Command::Wallet(WalletCommand::List { kind, alias }) => {
+            let filter = InventoryFilter { kinds: kind.into_iter().map(Into::into).collect(), alias };
+            Report::Inventory { entries: inventory::list_wallet(sdk, &filter).await }
+        }
+        Command::Wallet(WalletCommand::Rename { alias, new_alias, kind, yes }) => {
+            let kinds: Vec<EntryKind> = kind.into_iter().map(Into::into).collect();
+            if !yes && !confirm_entries(sdk, &kinds, &alias, &format!("Rename them to {}?", new_alias)).await? {
+                return Ok(Report::Cancelled);
+            }
+            let kinds = aliases::rename_alias(sdk, &profile.wallet_dir, &kinds, &alias, &new_alias).await?;
+            Report::Renamed { alias, new_alias, kinds }
+        }
+        Command::Wallet(WalletCommand::Delete { alias, kind, yes }) => {
+            let kinds: Vec<EntryKind> = kind.into_iter().map(Into::into).collect();
+            if !yes && !confirm_entries(sdk, &kinds, &alias, "Delete them? This cannot be undone.").await? {
+                return Ok(Report::Cancelled);
+            }
+            let kinds = aliases::delete_alias(sdk, &profile.wallet_dir, &kinds, &alias).await?;
+            Report::Deleted { alias, kinds }
+        }
+        Command::Wallet(WalletCommand::Backup { file, shielded }) => {
+            let password = prompt_password(true, Some("the backup"))?;
+            let masp_dir = shielded.then_some(profile.masp_dir.as_path());
+            backup::export_backup(sdk, &profile.wallet_dir, masp_dir, &file, &password).await?.into()
+        }
+        Command::Wallet(WalletCommand::Restore { file, overwrite, yes }) => {
+            let password = prompt_password(false, Some("the backup"))?;
+            let backup = Backup::read(&file, &password)?;
+            let mode = if overwrite { RestoreMode::Overwrite } else { RestoreMode::Merge };
+            if !yes && !confirm_restore(profile, &backup, mode)? {
+                return Ok(Report::Cancelled);
+            }
+            backup::restore_backup(sdk, &profile.wallet_dir, Some(&profile.masp_dir), &backup, mode).await?.into()
+        }
+        Command::Wallets(WalletsCommand::List) => Report::Wallets {
+            home: home.root().display().to_string(),
+            active: profile.wallet.clone(),
+            wallets: home.wallets()?,
+        },
+        Command::Wallets(WalletsCommand::Create { name }) => {
+            let path = home.create(&name)?;
+            Report::WalletAdded { name, path: path.display().to_string() }
+        }
+        Command::Key(KeyCommand::Add { alias, mnemonic, bip39_passphrase, derivation, force }) => {
+            let phrase = match mnemonic {
+                Some(phrase) => phrase,
+                None => prompt_user("Enter the mnemonic: ")?,
+            };
+            let options = prompt_key_options(bip39_passphrase, derivation, force)?;
+            let alias = wallet::add_key(sdk, alias, &phrase, &options).await?;
+            remember_mnemonic(profile, &alias, &phrase, &options)?;
+            let address = wallet::find_address(sdk, &alias).await.map(|a| a.to_string());
+            Report::KeyAdded { alias, address }
+        }
+        Command::Key(KeyCommand::Recover { alias, mnemonic, bip39_passphrase, scheme, gap_limit, token }) => {
+            let phrase = match mnemonic {
+                Some(phrase) => phrase,
+                None => prompt_user("Enter the mnemonic: ")?,
+            };
+            let mut tokens = Vec::with_capacity(token.len());
+            for token in &token {
+                tokens.push(wallet::resolve_address(sdk, token).await?);
+            }
+            let derivation = DerivationArgs { scheme, ..Default::default() };
+            let options = prompt_key_options(bip39_passphrase, derivation, false)?;
+            let accounts = recovery::recover_accounts(sdk, &alias, &phrase, &options, &tokens, gap_limit).await?;
+            for account in &accounts {
+                remember_mnemonic(profile, &account.alias, &phrase, &options)?;
+            }
+            Report::AccountsRecovered { accounts: accounts.iter().map(Into::into).collect() }
+        }
+        Command::Key(KeyCommand::Import { alias, secret_key_file, scheme, force }) => {
+            let secret_key = match secret_key_file {
+                Some(path) => read_secret("secret key", &path)?,
+                None => prompt_secret("secret key")?,
+            };
+            let password = prompt_new_password()?;
+            let address = wallet::import_secret_key(sdk, &alias, &secret_key, scheme.into(), password, force).await?;
+            Report::KeyAdded { alias, address: Some(address.to_string()) }
+        }
+        Command::Address(AddressCommand::Show { alias }) => {
+            let address = wallet::find_address(sdk, &alias).await.map(|a| a.to_string());
+            Report::Address { alias, address }
+        }
+        Command::SpendingKey(SpendingKeyCommand::Create { alias, mnemonic, bip39_passphrase, force }) => {
+            let phrase = match mnemonic {
+                Some(phrase) => phrase,
+                None => prompt_user("Enter the mnemonic for the spending key: ")?,
+            };
+            let options = prompt_key_options(bip39_passphrase, DerivationArgs::default(), force)?;
+            let alias = wallet::create_spending_key(sdk, alias, &phrase, &options).await?;
+            Report::SpendingKeyCreated { alias }
+        }
+        Command::SpendingKey(SpendingKeyCommand::Import { alias, spending_key_file, birthday, force }) => {
+            let spending_key = match spending_key_file {
+                Some(path) => read_secret("spending key", &path)?,
+                None => prompt_secret("spending key")?,
+            };
+            let password = prompt_new_password()?;
+            let viewing_key = wallet::import_spending_key(sdk, &alias, &spending_key, birthday, password, force).await?;
+            Report::SpendingKeyImported { alias, viewing_key: viewing_key.to_string() }
+        }
+        Command::PaymentAddress(PaymentAddressCommand::Gen { alias, viewing_key, force }) => {
+            let payment_addr = wallet::generate_payment_address(sdk, &alias, &viewing_key, force).await?;
+            Report::PaymentAddress { alias, payment_address: payment_addr.to_string() }
+        }
+        Command::Watch(WatchCommand::Address { alias, address, force }) => {
+            let address = watch::import_address(sdk, &alias, &address, force).await?;
+            Report::Watching { alias, kind: EntryKind::Address, value: address.to_string() }
+        }
+        Command::Watch(WatchCommand::ViewingKey { alias, viewing_key, birthday, force }) => {
+            let viewing_key = watch::import_viewing_key(sdk, &alias, &viewing_key, birthday, force).await?;
+            Report::Watching { alias, kind: EntryKind::ViewingKey, value: viewing_key.to_string() }
+        }
+        Command::Shield(args) => {
+            let target = PaymentAddress::from_str(&args.target)
+                .map_err(|e| Error::InvalidInput { what: "target address", reason: format!("{}: {}", args.target, e) })?;
+            let token = wallet::resolve_token(sdk, args.token.as_deref()).await?;
+            let submission = transfer::send_token_shielded(sdk, signer, &args.source, &target, &args.amount, token).await?;
+            Report::transaction("Shielded transfer", &submission)
+        }
+        Command::RevealStatus(args) => {
+            let owner = wallet::resolve_address(sdk, &args.owner).await?;
+            let revealed = query::findifreveal(sdk, &owner).await?;
+            Report::RevealStatus { owner: owner.to_string(), revealed }
+        }
+        Command::ShieldedSync(args) => {
+            let synced_height = shielded::shielded_sync(sdk, &profile.indexer_url, &args.key).await?;
+            Report::ShieldedSync { synced_height }
+        }
+        Command::Transfer(args) => {
+            let target = wallet::resolve_address(sdk, &args.target).await?;
+            let token = wallet::resolve_token(sdk, args.token.as_deref()).await?;
+            let submission = transfer::send_transparent_token(sdk, signer, &args.source, target, &args.amount, token).await?;
+            Report::transaction("Transaction", &submission)
+        }
+        Command::IbcTransfer(args) => {
+            let token = wallet::resolve_token(sdk, args.token.as_deref()).await?;
+            let submission = transfer::send_ibc_token(
+                sdk,
+                signer,
+                &args.source,
+                &args.receiver,
+                &args.channel_id,
+                &args.amount,
+                token,
+            )
+            .await?;
+            Report::transaction("IBC transfer", &submission)
+        }
+        Command::InitAccount(args) => {
+            let created = account::create_account(
+                sdk,
+                signer,
+                &args.alias,
+                &args.fee_payer,
+                &args.public_keys,
+                args.threshold,
+                args.vp_code_path.as_deref(),
+            )
+            .await?;
+            Report::AccountCreated {
+                alias: args.alias,
+                address: created.address.map(|address| address.to_string()),
+                result: TxReport::from(&created.submission.response),
+            }
+        }
+        Command::Multisig(MultisigCommand::Propose { account, target, amount, token, fee_payer, out }) => {
+            let target = wallet::resolve_address(sdk, &target).await?;
+            let token = wallet::resolve_token(sdk, token.as_deref()).await?;
+            let proposal = multisig::propose_transfer(sdk, &account, target, &amount, token, &fee_payer).await?;
+            proposal.write(&out)?;
+            Report::TransferProposed {
+                file: out.display().to_string(),
+                summary: proposal.summary(),
+                account: proposal.account,
+                threshold: proposal.threshold,
+                tx_hash: proposal.tx_hash,
+            }
+        }
+        Command::Multisig(MultisigCommand::Sign { proposal: path, keys, yes }) => {
+            let proposal = Proposal::read(&path)?;
+            if !yes && !confirm_proposal(&proposal)? {
+                return Ok(Report::Cancelled);
+            }
+            let mut files = Vec::new();
+            for signature in multisig::sign_proposal(sdk, signer, &proposal, &keys).await? {
+                let file = multisig::signature_path(&path, signature.public_key());
+                signature.write(&file)?;
+                files.push(file.display().to_string());
+            }
+            Report::ProposalSigned { tx_hash: proposal.tx_hash, files }
+        }
+        Command::Multisig(MultisigCommand::Submit { proposal, signatures }) => {
+            let proposal = Proposal::read(&proposal)?;
+            let signatures = signatures
+                .iter()
+                .map(|path| SignatureFile::read(path))
+                .collect::<Result<Vec<_>>>()?;
+            let submission = multisig::submit_proposal(sdk, signer, &proposal, &signatures).await?;
+            Report::transaction("Multisig transfer", &submission)
+        }
+        Command::UpdateAccount(args) => {
+            let update = account::prepare_account_update(
+                sdk,
+                &args.account,
+                &args.public_keys,
+                args.threshold,
+                args.vp_code_path.as_deref(),
+            )
+            .await?;
+            if !args.yes && !confirm_account_update(&update)? {
+                return Ok(Report::Cancelled);
+            }
+            let submission = account::submit_account_update(sdk, signer, &update).await?;
+            Report::AccountUpdated { diff: update.diff, result: TxReport::from(&submission.response) }
+        }
+        Command::Balance(args) => {
+            let owner = wallet::resolve_address(sdk, &args.owner).await?;
+            let token = wallet::resolve_token(sdk, args.token.as_deref()).await?;
+            let balance = query::get_token_balance(sdk, &owner, &token).await?;
+            balance_report(sdk, &owner, &token, balance).await
+        }
+        Command::MaspEpoch => Report::MaspEpoch { epoch: query::query_masp_epoch(sdk).await? },
+        Command::NodeStatus => {
+            let status = node::node_status(sdk).await?;
+            Report::node_status(&status, &profile.chain_id)
+        }
+        Command::Batch(args) => run_batch(sdk, signer, args).await?,
+    };
+    Ok(report)
 }
 
-// Generate a shielded payment address
-async fn generate_payment_address<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
+async fn run_batch<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, signer: &dyn Signer, args: BatchArgs) -> Result<Report>
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    // Hardcoded viewing key
-    let viewing_key_str = "zvknam1qddsrtp4qqqqpqr6t24a76wu3gdszc0jw8r0643mhfs3sgx49cftd8qjtetl4a5aa24fmryf29uz7xkqket0exqm8vkky8w99uqjl80cl290uqfev3yegg3ym4z84x5gwruuw4t2ln26wkadckksfkfu8ku6jdqjryvdvtlq3x8atu9p3lk7a86wals57zp7dfnydr8088pmflt6c2zgwjnzdnrsfy4v3r85gf2my2ynzqtug4euewsj0ps6upqrw524jw5g5cyecjq4c8gjy";
-    let viewing_key = ExtendedViewingKey::from_str(viewing_key_str).expect("Invalid viewing key");
-
-    // Hardcoded alias
-    let alias = "default"; // No need for user input
-    let alias_force = true; // Set force to true
-
-    // Check if an address already exists for the alias
-    if let Some(address) = sdk.wallet().await.find_address(&alias) {
-        println!("Address already exists for {}: {:?}", alias, address);
-
-        if !alias_force {
-            println!("Skipping address generation for {}, as alias exists and force option is not enabled.", alias);
-            return; // Exit if not forcing alias generation
-        } else {
-            println!("Forcing address generation for {}...", alias);
-        }
-    } else {
-        println!("No address found for alias: {}, generating new payment address...", alias);
+    let mut plan = Plan::load(&args.plan)?;
+    if args.continue_on_error {
+        plan.on_error = OnError::Continue;
     }
 
-    // Use the provided viewing key
-    let viewing_key_ref = &viewing_key.as_viewing_key();
-
-    // Generate the shielded payment address
-    let (div, _g_d) = find_valid_diversifier(&mut OsRng);
-    let masp_payment_addr = viewing_key_ref
-        .to_payment_address(div)
-        .expect("Unable to generate a PaymentAddress");
-    let payment_addr = PaymentAddress::from(masp_payment_addr);
-
-    // Store the payment address in the wallet
-    sdk.wallet_mut().await
-        .insert_payment_addr(alias.to_string(), payment_addr.clone(), alias_force) // Convert alias to String
-        .expect("Payment address could not be inserted");
-
-    // Save the wallet with the new address
-    sdk.wallet().await.save().expect("Could not save wallet!");
+    let mut steps = Vec::new();
+    for step_result in plan::run_plan(sdk, signer, &plan).await {
+        let op = step_result.step.name();
+        let (status, result, error) = match step_result.outcome {
+            None => (StepStatus::Skipped, None, None),
+            Some(Err(e)) => (StepStatus::Failed, None, Some(e.to_string())),
+            Some(Ok(StepOutput::Revealed(responses))) => {
+                let reveal = responses.iter().map(Into::into).collect();
+                (StepStatus::Ok, Some(Report::RevealPk { reveal }), None)
+            }
+            Some(Ok(StepOutput::Submitted(submission))) => {
+                (StepStatus::Ok, Some(Report::transaction(op, &submission)), None)
+            }
+            Some(Ok(StepOutput::Balance { owner, token, amount })) => {
+                (StepStatus::Ok, Some(balance_report(sdk, &owner, &token, amount).await), None)
+            }
+        };
+        steps.push(StepReport { index: step_result.index, op, status, result, error });
+    }
 
-    println!("New payment address generated and saved for {}: {:?}", alias, payment_addr);
+    Ok(Report::Batch { dry_run: args.dry_run, steps })
 }
 
-// New function to send tokens
-async fn send_token_shielded<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
+async fn balance_report<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    owner: &Address,
+    token: &Address,
+    amount: token::Amount,
+) -> Report
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    let alias = "rilsso-public";
-    let tendermint_addr = "https://rpc.knowable.run:443";
-
-    // Retrieve the source address using the alias
-    let source_address = match sdk.wallet().await.find_address(&alias) {
-        Some(address) => address.into_owned(),
-        None => {
-            println!("No address found for alias: {}", alias);
-            return;
-        }
-    };
-
-    // Check if the account is already revealed
-    if !findifreveal(sdk, tendermint_addr, &source_address)
-        .await
-        .expect("Error checking reveal status")
-    {
-        println!("Account is not revealed, proceeding to reveal the public key.");
-
-        // Create a new reveal transaction builder for the public key
-        let _test = get_viewing_keys().await;
-
-        match _test {
-            Ok(keys) => {
-                for key in keys {
-                    println!("{}", key);
-                }
-            }
-            Err(e) => {
-                eprintln!("Error getting viewing keys: {}", e);
-            }
-        }
-
-        // Fetch public keys from the wallet.toml
-        let _testpk = get_public_keys().await;
-
-        match _testpk {
-            Ok(keys) => {
-                for key in keys {
-                    println!("{}", key);
-
-                    // Pass the derived key to the `CommonPublicKey::from_str`
-                    let public_key = CommonPublicKey::from_str(&key)
-                        .expect("Invalid public key format");
-
-                    let reveal_tx_builder = sdk
-                        .new_reveal_pk(public_key.clone())
-                        .signing_keys(vec![public_key.clone()]);
-
-                    // Build the reveal transaction
-                    let (mut reveal_tx, signing_data) = reveal_tx_builder
-                        .build(sdk)
-                        .await
-                        .expect("Unable to build reveal pk tx");
-
-                    // Sign the reveal transaction
-                    sdk.sign(&mut reveal_tx, &reveal_tx_builder.tx, signing_data, default_sign, ())
-                        .await
-                        .expect("Unable to sign reveal pk tx");
-
-                    // Submit the signed reveal transaction
-                    match sdk.submit(reveal_tx.clone(), &reveal_tx_builder.tx).await {
-                        Ok(res) => println!("Public key successfully revealed: {:?}", res),
-                        Err(e) => {
-                            println!("Failed to reveal public key: {:?}", e);
-                            return; // Exit if revealing the public key fails
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Error getting public keys: {}", e);
-            }
-        }
-    } else {
-        println!("Account is already revealed, skipping the reveal step.");
-    }
-
-    let target = PaymentAddress::from_str("znam1jk5dkka9gj8wqtkky5tgzy76heapcdg8r3aqn9syr9k3nmx6ms8wn3hdew79tptg9kfds960a2u")
-        .expect("Invalid target address");
-
-    let amount = InputAmount::from_str("1").expect("Invalid amount");
-
-    // Prepare the transaction data for shielding transfer
-    let shielding_data = vec![args::TxShieldingTransferData {
-        source: source_address,
- 
-        amount,
-        token: sdk.native_token(),
-    }];
-
-    // Create the shielding transfer
-    let mut shielding_transfer = sdk.new_shielding_transfer(target, shielding_data);
-
-    // Build the transaction from the shielding transfer
-    let (mut transfer_tx, signing_data, _masp_epoch) = shielding_transfer
-        .build(sdk)
-        .await
-        .expect("Unable to build shielding transfer");
-
-    // Sign the transaction
-    sdk.sign(&mut transfer_tx, &shielding_transfer.tx, signing_data, default_sign, ())
-        .await
-        .expect("Unable to sign shielding transfer tx");
-
-    // Submit the signed transaction to the ledger
-    match sdk.submit(transfer_tx, &shielding_transfer.tx).await {
-        Ok(res) => println!("Shielded transfer successfully submitted: {:?}", res),
-        Err(e) => println!("Failed to submit shielded transfer: {:?}", e),
+    let denom = rpc::query_denom(sdk.client(), token).await.map(|denom| denom.0);
+    Report::Balance {
+        owner: owner.to_string(),
+        token: token.to_string(),
+        amount: sdk.format_amount(token, amount).await,
+        raw_amount: amount.raw_amount().to_string(),
+        denom,
     }
 }
 
-// Check revealed or not
-async fn check_if_revealed<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
+async fn warn_if_node_unhealthy<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    let owner_address = Address::from_str(OWNER_ADDRESS).expect("Invalid owner address");
-
-    match findifreveal(sdk, RPC_URL, &owner_address).await {
-        Ok(is_revealed) => {
-            if is_revealed {
-                println!("The account is revealed.");
-            } else {
-                println!("The account is not revealed.");
-            }
-        }
-        Err(e) => eprintln!("Error checking reveal status: {}", e),
+    if let Err(e) = node::verify_node(sdk).await {
+        eprintln!("{}", format!("Warning: {}. Transactions will be refused.", e).yellow());
     }
 }
 
-// Function to check if an account is revealed by querying the Tendermint node
-async fn findifreveal<C, U, V, I>( // Custom function so _sdk 
-    _sdk: &NamadaImpl<C, U, V, I>,
-    tendermint_addr: &str,
-    owner: &Address,
-) -> Result<bool, Box<dyn Error>>
+// Lock the wallet and shielded dirs for one operation and reload the wallet, so
+// what another process saved since the last operation is neither missed nor lost
+async fn lock_wallet<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, profile: &NetworkProfile) -> Result<WalletLock>
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    let client = HttpClient::new(
-        Url::from_str(tendermint_addr)
-            .map_err(|e| Box::new(e) as Box<dyn Error>)?,
-    )?;
-
-    let account_info: Option<namada_sdk::account::Account> = rpc::get_account_info(&client, owner).await?;
-    if let Some(account) = account_info {
-        println!("Account information: {:?}", account);
-        Ok(!account.public_keys_map.idx_to_pk.is_empty()) 
-    } else {
-        println!("No account information found.");
-        Ok(false)
+    let lock = WalletLock::acquire(&profile.wallet_dir, &profile.masp_dir)?;
+    // A new wallet has no file until its first key is saved
+    if profile.wallet_dir.join("wallet.toml").exists() {
+        sdk.wallet_mut()
+            .await
+            .load()
+            .map_err(|e| Error::WalletIo(format!("Unable to reload the wallet: {}", e)))?;
     }
+    Ok(lock)
 }
 
-
-async fn get_viewing_keys() -> Result<Vec<String>, String> {
-    let file_path = "./sdk-wallet/wallet.toml"; 
-
-    // Await the future and use map_err on the result
-    let content = fs::read_to_string(file_path).await.map_err(|e| format!("Unable to read file: {}", e))?;
-
-    let parsed: Value = toml::de::from_str(&content).map_err(|e| format!("Unable to parse TOML: {}", e))?;
-
-    let mut keys = Vec::new();
-    if let Some(view_keys) = parsed.get("view_keys") {
-        for (_key, value) in view_keys.as_table().unwrap() {
-            if let Some(address) = value.get("key") {
-                keys.push(clean_address(address.as_str().unwrap()));
-            }
-        }
-    } else {
-        return Err("No view_keys found.".to_string());
+// Rebuild the SDK context (HTTP client, chain id, wallet and shielded dirs) for
+// another network, keeping the open wallet
+async fn switch_network(
+    config: &Config,
+    home: &WalletHome,
+    active: &str,
+    wallet: Option<&str>,
+) -> Option<(String, NetworkProfile, NamadaImpl<HttpClient, CliWalletUtils, FsShieldedUtils, StdIo>)> {
+    let names: Vec<&String> = config.networks.keys().collect();
+
+    eprintln!("\nKnown networks:");
+    for (i, name) in names.iter().enumerate() {
+        let profile = &config.networks[*name];
+        let marker = if name.as_str() == active { "*" } else { " " };
+        eprintln!("{} {}. {} ({}, {})", marker, i + 1, name, profile.chain_id, profile.rpc_url);
     }
 
-    Ok(keys)
-}
-
-fn clean_address(address: &str) -> String {
-    address.trim().to_string()
-}
-
-
-
-async fn get_public_keys() -> Result<Vec<String>, String> {
-    let file_path = "./sdk-wallet/wallet.toml"; 
-
-    let content = fs::read_to_string(file_path).await.map_err(|e| format!("Unable to read file: {}", e))?;
-
-    let parsed: Value = toml::de::from_str(&content).map_err(|e| format!("Unable to parse TOML: {}", e))?;
-
-    let mut keys = Vec::new();
-    if let Some(public_keys) = parsed.get("public_keys") {
-        for (_key, value) in public_keys.as_table().unwrap() {
-            if let Some(address) = value.as_str() {
-                let cleaned_address = address.replace("ED25519_PK_PREFIX", "");
-                keys.push(cleaned_address);
-            }
+    let choice = match prompt_user("Select a network (empty to cancel): ") {
+        Ok(choice) => choice,
+        Err(e) => {
+            eprintln!("{}", e);
+            return None;
         }
-    } else {
-        return Err("No public_keys found.".to_string());
+    };
+    if choice.is_empty() {
+        return None;
     }
-
-    Ok(keys)
-}
-
-// Send transparent tokens
-
-async fn send_transparent_token<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let alias = "rilsso-public";
-    let tendermint_addr = "https://rpc.knowable.run:443"; 
-
-    // Retrieve the source address using the alias
-    let source_address = match sdk.wallet().await.find_address(&alias) {
-        Some(address) => address.into_owned(),
+    let name = match choice.parse::<usize>().ok().and_then(|i| names.get(i.wrapping_sub(1))) {
+        Some(name) => name.to_string(),
         None => {
-            println!("No address found for alias: {}", alias);
-            return;
+            eprintln!("Invalid network selection: {}", choice);
+            return None;
         }
     };
 
-    // Check if the account is already revealed
-    if !findifreveal(sdk, tendermint_addr, &source_address)
-        .await
-        .expect("Error checking reveal status")
-    {
-        println!("Account is not revealed, proceeding to reveal the public key.");
+    // Env overrides only apply to the startup profile, an explicit switch wins
+    let mut profile = config.networks[&name].clone();
+    if let Err(e) = profile.open_wallet(home, wallet, &name) {
+        eprintln!("Unable to switch to {}: {}", name, e);
+        return None;
+    }
+    let sdk = open_sdk(&profile, &name).await?;
+    eprintln!("Switched to network {} ({})", name, profile.chain_id);
+    warn_if_node_unhealthy(&sdk).await;
 
-        // Create a new reveal transaction builder for the public key
-        let _test = get_viewing_keys().await; 
+    Some((name, profile, sdk))
+}
 
-        match _test {
-            Ok(keys) => {
-                for key in keys {
-                    println!("{}", key); 
-                }
-            }
-            Err(e) => {
-                eprintln!("Error getting viewing keys: {}", e);
-            }
+// List the wallets of the home, then open the chosen one (or a newly created one)
+// on the active network
+async fn switch_wallet(
+    home: &WalletHome,
+    network: &str,
+    active: &NetworkProfile,
+) -> Option<(NetworkProfile, NamadaImpl<HttpClient, CliWalletUtils, FsShieldedUtils, StdIo>)> {
+    let names = match home.wallets() {
+        Ok(names) => names,
+        Err(e) => {
+            eprintln!("{}", e);
+            return None;
         }
+    };
 
-        // Fetch public keys from the wallet.toml
-        let _testpk = get_public_keys().await; 
-        
-        match _testpk {
-            Ok(keys) => {
-                for key in keys {
-                    println!("{}", key); 
-                    
-                    // Pass the derived key to the `CommonPublicKey::from_str`
-                    let public_key = CommonPublicKey::from_str(&key)
-                        .expect("Invalid public key format");
-
-                    let reveal_tx_builder = sdk
-                        .new_reveal_pk(public_key.clone())
-                        .signing_keys(vec![public_key.clone()]);
-
-                    // Build the reveal transaction
-                    let (mut reveal_tx, signing_data) = reveal_tx_builder
-                        .build(sdk)
-                        .await
-                        .expect("Unable to build reveal pk tx");
-
-                    // Sign the reveal transaction
-                    sdk.sign(&mut reveal_tx, &reveal_tx_builder.tx, signing_data, default_sign, ())
-                        .await
-                        .expect("Unable to sign reveal pk tx");
-
-                    // Submit the signed reveal transaction
-                    match sdk.submit(reveal_tx.clone(), &reveal_tx_builder.tx).await {
-                        Ok(res) => println!("Public key successfully revealed: {:?}", res),
-                        Err(e) => {
-                            println!("Failed to reveal public key: {:?}", e);
-                            return;  // Exit if revealing the public key fails
-                        }
-                    }
-                }
-            }
-            Err(e) => {
-                eprintln!("Error getting public keys: {}", e);
-            }
-        }
-    } else {
-        println!("Account is already revealed, skipping the reveal step.");
+    eprintln!("\nWallets in {}:", home.root().display());
+    for (i, name) in names.iter().enumerate() {
+        let marker = if active.wallet.as_ref() == Some(name) { "*" } else { " " };
+        eprintln!("{} {}. {}", marker, i + 1, name);
     }
 
-
-    let target_address = Address::from_str("tnam1qpu63hxasnmsq25juqfcql287mvkgjx3vuvgzktz")
-        .expect("Invalid target address");
-    let amount = InputAmount::from_str("10").expect("Invalid amount");
-
-    let token = sdk.native_token();
-
-    // Prepare the transaction data
-    let data = TxTransparentTransferData {
-        source: source_address,
-        target: target_address,
-        token,
-        amount,
-    };
-
-    // Fetch public keys again for use in signing the transfer transaction
-    let _testpk = get_public_keys().await; 
-    let signing_keys: Vec<CommonPublicKey> = match _testpk {
-        Ok(keys) => keys.iter()
-            .map(|key| CommonPublicKey::from_str(key).expect("Invalid public key format"))
-            .collect(),
+    let choice = match prompt_user("Select a wallet, n to create one (empty to cancel): ") {
+        Ok(choice) => choice,
         Err(e) => {
-            eprintln!("Error getting public keys: {}", e);
-            return; 
+            eprintln!("{}", e);
+            return None;
         }
     };
+    let name = match choice.as_str() {
+        "" => return None,
+        "n" | "N" => {
+            let name = match prompt_user("Name of the new wallet: ") {
+                Ok(name) => name,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return None;
+                }
+            };
+            match home.create(&name) {
+                Ok(path) => eprintln!("Wallet {} created in {}", name, path.display()),
+                Err(e) => {
+                    eprintln!("Unable to create wallet {}: {}", name, e);
+                    return None;
+                }
+            }
+            name
+        }
+        _ => match choice.parse::<usize>().ok().and_then(|i| names.get(i.wrapping_sub(1))) {
+            Some(name) => name.clone(),
+            None => {
+                eprintln!("Invalid wallet selection: {}", choice);
+                return None;
+            }
+        },
+    };
 
-    let mut transfer_tx_builder = sdk
-        .new_transparent_transfer(vec![data])
-        .signing_keys(signing_keys);
-
-    // Build and sign the transaction
-    let (mut transfer_tx, signing_data) = transfer_tx_builder
-        .build(sdk)
-        .await
-        .expect("Unable to build transfer");
-
-    // Sign the transaction
-    sdk.sign(&mut transfer_tx, &transfer_tx_builder.tx, signing_data, default_sign, ())
-        .await
-        .expect("Unable to sign transparent-transfer tx");
-
-    // Submit the signed transaction to the ledger
-    match sdk.submit(transfer_tx, &transfer_tx_builder.tx).await {
-        Ok(res) => println!("Transaction successfully submitted: {:?}", res),
-        Err(e) => println!("Failed to submit transaction: {:?}", e),
+    let mut profile = active.clone();
+    if let Err(e) = profile.open_wallet(home, Some(&name), network) {
+        eprintln!("Unable to open wallet {}: {}", name, e);
+        return None;
     }
-}
+    let sdk = open_sdk(&profile, &name).await?;
+    eprintln!("Opened wallet {}", wallet_label(&profile));
 
-/// Generates the IBC memo for a transfer transaction.
-
-pub async fn generate_ibc_memo<C, U, V, I>(
-    sdk: &NamadaImpl<C, U, V, I>, 
-) -> String
-where
-    C: Client + MaybeSync + MaybeSend, 
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    let alias = "rilsso-public"; 
-    let wallet_guard = sdk.wallet().await; // Await to get the read guard
+    Some((profile, sdk))
+}
 
-    // Retrieve the source address using the alias
-    let source_address = match wallet_guard.find_address(&alias) { // Call find_address on the guard
-        Some(address) => address.into_owned(),
-        None => {
-            println!("No address found for alias: {}", alias);
-            return String::new(); 
+// Build the SDK context of a profile and load its wallet, `target` names the
+// network or wallet being switched to in errors
+async fn open_sdk(
+    profile: &NetworkProfile,
+    target: &str,
+) -> Option<NamadaImpl<HttpClient, CliWalletUtils, FsShieldedUtils, StdIo>> {
+    let sdk = match profile.build_sdk().await {
+        Ok(sdk) => sdk,
+        Err(e) => {
+            eprintln!("Unable to switch to {}: {}", target, e);
+            return None;
         }
     };
 
-    let amount = InputAmount::from_str("1").expect("Invalid amount"); 
-
-
-    let channel_id_str = "channel-0"; 
-    let port_id_str = "transfer/channel-0"; 
-    let receiver = "cosmos1tsf6mvwzgxakzfltdxjlxygkrm2ht8fk5tq4kp"; // Replace with your actual receiver address
-    let amount_str = format!("{:?}", amount); 
-
-    format!(
-        "Transfer of {} tokens from {} to {} via port {} and channel {}",
-        amount_str, 
-        source_address, 
-        receiver, 
-        port_id_str,
-        channel_id_str 
-    )
+    if sdk.wallet_mut().await.load().is_ok() {
+        eprintln!("Existing wallet found in {}", profile.wallet_dir.display());
+    } else {
+        eprintln!("No existing wallet found in {}", profile.wallet_dir.display());
+    }
+    Some(sdk)
 }
 
-
-async fn send_ibc_token<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>)
+// Show the entries stored under `alias` and ask whether to go ahead with them
+async fn confirm_entries<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    kinds: &[EntryKind],
+    alias: &str,
+    question: &str,
+) -> Result<bool>
 where
     C: Client + MaybeSync + MaybeSend,
     U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
     V: ShieldedUtils + MaybeSync + MaybeSend,
     I: Io + MaybeSync + MaybeSend,
 {
-    let alias = "rilsso-public";
-    let tendermint_addr = "https://rpc.knowable.run:443"; 
-
-    // Retrieve the source address using the alias
-    let source_address = match sdk.wallet().await.find_address(&alias) {
-        Some(address) => address.into_owned(),
-        None => {
-            println!("No address found for alias: {}", alias);
-            return;
-        }
-    };
-
-    // Check if the account is already revealed
-    if !findifreveal(sdk, tendermint_addr, &source_address)
+    let filter = InventoryFilter { kinds: kinds.to_vec(), alias: Some(alias.to_string()) };
+    let entries: Vec<_> = inventory::list_wallet(sdk, &filter)
         .await
-        .expect("Error checking reveal status")
-    {
-        println!("Account is not revealed, proceeding to reveal the public key.");
-        // Call your reveal function here if needed
-    } else {
-        println!("Account is already revealed, skipping the reveal step.");
+        .into_iter()
+        .filter(|entry| entry.alias.eq_ignore_ascii_case(alias))
+        .collect();
+    if entries.is_empty() {
+        // Nothing to confirm, the operation reports the unknown alias
+        return Ok(true);
     }
+    eprintln!("{}", Report::Inventory { entries });
+    prompt_yes_no(question)
+}
 
-    let target_address = "cosmos1qqzg5khvcfdgnjg4wghvxcnekxwu4kg5nuwjssjt"; // Example Cosmos IBC target address
-
-
-    let channel_id = ChannelId::new(0);
-
-
-    let amount = InputAmount::from_str("10").expect("Invalid amount");
-    let token = sdk.native_token();
-
-
-    let receiver = target_address.to_string(); // IBC receiver address
-    let source_transfer = TransferSource::Address(source_address.clone());
-
-    // Build the IBC transfer transaction
-    let  ibc_transfer_tx_builder = sdk.new_ibc_transfer(
-        source_transfer,
-        receiver.clone(),
-        token.clone(),
-        amount.clone(),
-        channel_id,  
-        false,
-    );
+// Show what a backup holds and ask whether to restore it
+fn confirm_restore(profile: &NetworkProfile, backup: &Backup, mode: RestoreMode) -> Result<bool> {
+    eprintln!("{}", BackupPreview(backup));
+    if backup.chain_id() != profile.chain_id {
+        let message = format!(
+            "The backup was made on chain {}, not {}. Its shielded context will not be restored.",
+            backup.chain_id(),
+            profile.chain_id
+        );
+        eprintln!("{}", message.yellow());
+    }
+    match mode {
+        RestoreMode::Merge => prompt_yes_no("Add the entries the wallet is missing?"),
+        RestoreMode::Overwrite => prompt_yes_no("Replace the wallet with the backup? Its current entries will be lost."),
+    }
+}
 
-    let (mut ibc_transfer_tx, signing_data, _epoch_option) = ibc_transfer_tx_builder
-        .build(sdk)
-        .await
-        .expect("Unable to build IBC transfer transaction");
+// Show what an account update changes and ask whether to submit it
+fn confirm_account_update(update: &AccountUpdate) -> Result<bool> {
+    eprintln!("{}", AccountDiffPreview(&update.diff));
+    prompt_yes_no("Sign and submit the update with the account's current keys?")
+}
 
-    // Sign the transaction
-    sdk.sign(&mut ibc_transfer_tx, &ibc_transfer_tx_builder.tx, signing_data, default_sign, ())
-        .await
-        .expect("Unable to sign IBC transfer transaction");
+// Show a co-signer what the proposal transfers before signing it
+fn confirm_proposal(proposal: &Proposal) -> Result<bool> {
+    eprintln!("{}", ProposalPreview(proposal));
+    prompt_yes_no("Sign it with the wallet's keys of the account?")
+}
 
-    match sdk.submit(ibc_transfer_tx, &ibc_transfer_tx_builder.tx).await {
-        Ok(res) => println!("IBC transfer successfully submitted: {:?}", res),
-        Err(e) => println!("Failed to submit IBC transfer: {:?}", e),
+// Keys stored with a password keep their mnemonic encrypted with it, for `show-mnemonic`
+fn remember_mnemonic(profile: &NetworkProfile, alias: &str, phrase: &str, options: &KeyOptions) -> Result<()> {
+    match &options.password {
+        Some(password) => MnemonicVault::new(&profile.wallet_dir).store(alias, phrase, password),
+        None => Ok(()),
     }
 }
 
+// BIP39 passphrase (only when asked for) and encryption password for a new key
+fn prompt_key_options(bip39_passphrase: bool, derivation: DerivationArgs, force: bool) -> Result<KeyOptions> {
+    Ok(KeyOptions {
+        passphrase: if bip39_passphrase { prompt_mnemonic_passphrase()? } else { Default::default() },
+        password: prompt_new_password()?,
+        scheme: derivation.scheme.into(),
+        account: derivation.account,
+        derivation_path: derivation.hd_path,
+        force,
+    })
+}
 
-pub async fn get_token_balance<C, U, V, I>(
-    _sdk: &NamadaImpl<C, U, V, I>,
-) -> Result<token::Amount, Box<dyn Error>>
-where
-    C: Client + MaybeSync + MaybeSend,
-    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
-    V: ShieldedUtils + MaybeSync + MaybeSend,
-    I: Io + MaybeSync + MaybeSend,
-{
-    // Hardcoded addresses
-    let token_address = Address::from_str("tnam1qy440ynh9fwrx8aewjvvmu38zxqgukgc259fzp6h")?;
-    let owner_address = Address::from_str("tnam1qqteapc3ycthpehxtqadv6nx2grr5gptzs2ptyvy")?;
-
-    // Hardcode the Tendermint address
-    let tendermint_addr = "https://rpc.knowable.run:443"; 
-
-    // Create a new HTTP client with the hardcoded Tendermint address
-    let client = HttpClient::new(
-        Url::from_str(tendermint_addr)
-            .map_err(|e| Box::new(e) as Box<dyn Error>)?,
-    )?;
+// Key scheme, then an account index or a full derivation path when the answer starts with "m/"
+fn prompt_derivation() -> Result<Option<DerivationArgs>> {
+    let scheme = prompt_scheme()?;
+    let answer = prompt_default("Account index or derivation path", "0")?;
+    if answer.starts_with("m/") {
+        return Ok(Some(DerivationArgs { scheme, account: 0, hd_path: Some(answer) }));
+    }
+    match answer.parse() {
+        Ok(account) => Ok(Some(DerivationArgs { scheme, account, hd_path: None })),
+        Err(_) => {
+            eprintln!("Invalid account index: {}", answer);
+            Ok(None)
+        }
+    }
+}
 
-    let balance = rpc::get_token_balance(&client, &token_address, &owner_address, None).await
-        .map_err(|e| Box::new(e) as Box<dyn Error>)?;
+// Optional birthday height, `None` when the answer is not a height
+fn prompt_birthday() -> Result<Option<Option<u64>>> {
+    match prompt_user("Birthday height (empty if unknown): ")?.as_str() {
+        "" => Ok(Some(None)),
+        height => match height.parse() {
+            Ok(height) => Ok(Some(Some(height))),
+            Err(_) => {
+                eprintln!("Invalid height: {}", height);
+                Ok(None)
+            }
+        },
+    }
+}
 
-    Ok(balance)
+fn prompt_scheme() -> Result<KeyScheme> {
+    if prompt_yes_no("Use a Secp256k1 (Ethereum-style) key?")? {
+        Ok(KeyScheme::Secp256k1)
+    } else {
+        Ok(KeyScheme::Ed25519)
+    }
 }
 
+fn prompt_yes_no(prompt: &str) -> Result<bool> {
+    Ok(matches!(prompt_user(&format!("{} [y/N]: ", prompt))?.to_lowercase().as_str(), "y" | "yes"))
+}
 
-pub async fn query_and_print_masp_epoch(context: &impl Namada) -> MaspEpoch {
-    let epoch = rpc::query_masp_epoch(context.client()).await.unwrap();
-    
-    // Print the epoch in green color
-    println!("{}", format!("Last committed masp epoch: {}", epoch).green());
-    
-    epoch
+fn prompt_default(prompt: &str, default: &str) -> Result<String> {
+    let input = prompt_user(&format!("{} [{}]: ", prompt, default))?;
+    if input.is_empty() {
+        Ok(default.to_string())
+    } else {
+        Ok(input)
+    }
 }
 
-// This query the epoch of the masp (dynamically set a function to integrate with the shielded balance fetch)
+// Comma separated answer, without empty items
+fn prompt_list(prompt: &str) -> Result<Vec<String>> {
+    Ok(prompt_user(prompt)?
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect())
+}
 
-fn prompt_user(prompt: &str) -> String {
-    print!("{}", prompt);
-    io::stdout().flush().expect("Failed to flush stdout");
+// One trimmed line, an error once stdin is closed rather than endless empty answers.
+// Prompts and the rest of the interactive text go to stderr, stdout only carries reports
+fn prompt_user(prompt: &str) -> Result<String> {
+    eprint!("{}", prompt);
+    io::stderr()
+        .flush()
+        .map_err(|e| Error::WalletIo(format!("Unable to write to stderr: {}", e)))?;
     let mut input = String::new();
-    io::stdin().read_line(&mut input).expect("Failed to read line");
-    input.trim().to_string()
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => Err(Error::InvalidInput { what: "input", reason: "stdin was closed".to_string() }),
+        Ok(_) => Ok(input.trim().to_string()),
+        Err(e) => Err(Error::WalletIo(format!("Unable to read stdin: {}", e))),
+    }
 }
\ No newline at end of file
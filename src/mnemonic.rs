@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use namada_sdk::bip39::{Language, Mnemonic, MnemonicType};
+use namada_sdk::zeroize::Zeroizing;
+use rand::seq::index;
+use rand_core::OsRng;
+
+use crate::crypto;
+use crate::error::{Error, Result};
+use crate::lock::DirLock;
+use crate::wallet_utils::write_atomic;
+
+/// Mnemonic lengths offered when creating a wallet.
+pub const WORD_COUNTS: [usize; 5] = [12, 15, 18, 21, 24];
+
+/// How many words the user has to re-enter to prove the phrase was written down.
+pub const CHECKED_WORDS: usize = 3;
+
+/// File next to `wallet.toml` holding the password-encrypted mnemonics.
+pub(crate) const VAULT_FILE: &str = "mnemonics.toml";
+
+/// A freshly generated mnemonic that the user has not confirmed yet.
+///
+/// It cannot be stored as is: [`PendingMnemonic::verify`] has to succeed first.
+pub struct PendingMnemonic {
+    mnemonic: Mnemonic,
+    challenge: Vec<usize>,
+}
+
+/// A mnemonic whose backup was checked, ready to be stored with
+/// [`create_wallet`](crate::wallet::create_wallet).
+pub struct VerifiedMnemonic {
+    mnemonic: Mnemonic,
+}
+
+impl PendingMnemonic {
+    /// Generate a mnemonic of `word_count` words (one of [`WORD_COUNTS`]) and pick
+    /// the words to check.
+    pub fn generate(word_count: usize) -> Result<Self> {
+        if !WORD_COUNTS.contains(&word_count) {
+            return Err(Error::invalid(
+                "word count",
+                format!("{}, expected one of {:?}", word_count, WORD_COUNTS),
+            ));
+        }
+        let mnemonic_type =
+            MnemonicType::for_word_count(word_count).map_err(|e| Error::invalid("word count", e))?;
+        let mnemonic = Mnemonic::new(mnemonic_type, Language::English);
+
+        let mut challenge = index::sample(&mut OsRng, word_count, CHECKED_WORDS).into_vec();
+        challenge.sort_unstable();
+
+        Ok(PendingMnemonic { mnemonic, challenge })
+    }
+
+    pub fn phrase(&self) -> &str {
+        self.mnemonic.phrase()
+    }
+
+    /// Zero-based positions of the words the user has to re-enter, in order.
+    pub fn challenge(&self) -> &[usize] {
+        &self.challenge
+    }
+
+    /// Check the re-entered words, given in the order of [`PendingMnemonic::challenge`].
+    ///
+    /// Can be retried after a failure.
+    pub fn verify(&self, answers: &[&str]) -> Result<VerifiedMnemonic> {
+        let words: Vec<&str> = self.mnemonic.phrase().split_whitespace().collect();
+        let matches = answers.len() == self.challenge.len()
+            && self
+                .challenge
+                .iter()
+                .zip(answers)
+                .all(|(&i, answer)| answer.trim().eq_ignore_ascii_case(words[i]));
+
+        if matches {
+            Ok(VerifiedMnemonic { mnemonic: self.mnemonic.clone() })
+        } else {
+            Err(Error::MnemonicNotVerified)
+        }
+    }
+}
+
+impl VerifiedMnemonic {
+    pub fn phrase(&self) -> &str {
+        self.mnemonic.phrase()
+    }
+
+    pub(crate) fn mnemonic(&self) -> &Mnemonic {
+        &self.mnemonic
+    }
+}
+
+/// Mnemonics of password protected keys, encrypted with that same password,
+/// so the phrase can be shown again later.
+#[derive(Clone, Debug)]
+pub struct MnemonicVault {
+    path: PathBuf,
+}
+
+impl MnemonicVault {
+    /// The vault kept in the wallet directory.
+    pub fn new(wallet_dir: &Path) -> Self {
+        MnemonicVault {
+            path: wallet_dir.join(VAULT_FILE),
+        }
+    }
+
+    /// Encrypt `phrase` with `password` and store it under `alias`, replacing any previous entry.
+    pub fn store(&self, alias: &str, phrase: &str, password: &str) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut entries = self.read()?;
+        let encrypted = crypto::encrypt(password, phrase.as_bytes(), &[])?;
+        entries.insert(alias.to_lowercase(), hex::encode(encrypted));
+
+        self.write(&entries)
+    }
+
+    /// Decrypt the mnemonic stored under `alias`.
+    pub fn reveal(&self, alias: &str, password: &str) -> Result<Zeroizing<String>> {
+        let entries = self.read()?;
+        let encrypted = entries
+            .get(&alias.to_lowercase())
+            .ok_or_else(|| Error::WalletIo(format!("No mnemonic stored for {}", alias)))?;
+        let encrypted = hex::decode(encrypted)
+            .map_err(|e| Error::WalletIo(format!("Corrupted entry for {}: {}", alias, e)))?;
+
+        let phrase = crypto::decrypt(password, &encrypted, &[])?;
+        String::from_utf8(phrase.to_vec())
+            .map(Zeroizing::new)
+            .map_err(|_| Error::Decryption(format!("Stored mnemonic for {} is not valid UTF-8", alias)))
+    }
+
+    /// Move the mnemonic stored under `alias` to `new_alias`, if there is one.
+    pub fn rename(&self, alias: &str, new_alias: &str) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut entries = self.read()?;
+        match entries.remove(&alias.to_lowercase()) {
+            Some(encrypted) => {
+                entries.insert(new_alias.to_lowercase(), encrypted);
+                self.write(&entries)
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// Forget the mnemonic stored under `alias`, if there is one.
+    pub fn remove(&self, alias: &str) -> Result<()> {
+        let _lock = self.lock()?;
+        let mut entries = self.read()?;
+        match entries.remove(&alias.to_lowercase()) {
+            Some(_) => self.write(&entries),
+            None => Ok(()),
+        }
+    }
+
+    /// Raw content of the vault file, `None` when no mnemonic was stored.
+    pub(crate) fn export(&self) -> Result<Option<String>> {
+        if !self.path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(&self.path)
+            .map(Some)
+            .map_err(|e| Error::WalletIo(format!("Unable to read {}: {}", self.path.display(), e)))
+    }
+
+    /// Add the entries of an exported vault, replacing the whole vault with `overwrite`.
+    ///
+    /// Without it existing entries are kept. Returns the aliases that were imported.
+    pub(crate) fn import(&self, content: &str, overwrite: bool) -> Result<Vec<String>> {
+        let imported: BTreeMap<String, String> = toml::from_str(content)
+            .map_err(|e| Error::WalletIo(format!("Unable to parse the mnemonics to import: {}", e)))?;
+        let _lock = self.lock()?;
+        let mut entries = if overwrite { BTreeMap::new() } else { self.read()? };
+        let mut added = Vec::new();
+        for (alias, encrypted) in imported {
+            if !entries.contains_key(&alias) {
+                entries.insert(alias.clone(), encrypted);
+                added.push(alias);
+            }
+        }
+        self.write(&entries)?;
+        Ok(added)
+    }
+
+    // The vault shares the lock of its wallet dir
+    fn lock(&self) -> Result<DirLock> {
+        DirLock::acquire(self.path.parent().unwrap_or_else(|| Path::new(".")))
+    }
+
+    fn write(&self, entries: &BTreeMap<String, String>) -> Result<()> {
+        let content = toml::to_string(entries)
+            .map_err(|e| Error::WalletIo(format!("Unable to encode {}: {}", self.path.display(), e)))?;
+        write_atomic(&self.path, content.as_bytes())
+    }
+
+    fn read(&self) -> Result<BTreeMap<String, String>> {
+        if !self.path.exists() {
+            return Ok(BTreeMap::new());
+        }
+        let content = fs::read_to_string(&self.path)
+            .map_err(|e| Error::WalletIo(format!("Unable to read {}: {}", self.path.display(), e)))?;
+        toml::from_str(&content)
+            .map_err(|e| Error::WalletIo(format!("Unable to parse {}: {}", self.path.display(), e)))
+    }
+}
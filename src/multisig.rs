@@ -0,0 +1,416 @@
+//! Transfers from multisig established accounts, signed by co-signers who each
+//! hold some of the account keys in their own wallet, with files as the only
+//! channel between them.
+//!
+//! The initiator builds the tx and writes it to a proposal file. Each co-signer
+//! checks what it does, signs it with their keys through their [`Signer`] and
+//! sends back one signature file per key. The initiator imports those, checks them against the account's
+//! key set and threshold on chain, adds the fee payer's wrapper signature and
+//! submits. Both files are JSON, a signature file names the tx it signs so it is
+//! never attached to another proposal.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use namada_core::address::Address;
+use namada_sdk::account::AccountPublicKeysMap;
+use namada_sdk::borsh::BorshDeserialize;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::signing::SigningTxData;
+use namada_sdk::tx::Tx;
+use namada_sdk::{
+    args::{InputAmount, TxBuilder, TxTransparentTransferData},
+    io::{Client, Io},
+    key::{common, PublicKeyHash},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use namada_tx::SignatureIndex;
+use serde::{Deserialize, Serialize};
+use tracing::{info, info_span, instrument, Instrument};
+
+use crate::error::{Error, Result};
+use crate::query;
+use crate::signer::{self, Signer};
+use crate::transfer::{sign_and_submit, TxSubmission};
+use crate::wallet;
+use crate::wallet_utils::write_atomic;
+
+/// Version of the proposal and signature files written by this crate.
+pub const PROPOSAL_VERSION: u8 = 1;
+
+/// An unsigned tx of a multisig account, as exchanged with its co-signers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Proposal {
+    pub version: u8,
+    pub chain_id: String,
+    /// The established account the tx spends from
+    pub account: String,
+    /// Key set of the account when the proposal was made, in index order
+    pub public_keys: Vec<String>,
+    pub threshold: u8,
+    /// Key that signs the wrapper, its implicit account pays the fees
+    pub fee_payer: String,
+    /// Hash of the inner tx, what the co-signers sign
+    pub tx_hash: String,
+    pub tx: Tx,
+}
+
+/// A co-signer's signature of a [`Proposal`], made with one of the account keys.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SignatureFile {
+    pub version: u8,
+    /// [`Proposal::tx_hash`] of the signed proposal
+    pub tx_hash: String,
+    pub signature: SignatureIndex,
+}
+
+impl Proposal {
+    pub fn read(path: &Path) -> Result<Self> {
+        let proposal: Proposal = read_json(path, "proposal")?;
+        check_version(proposal.version, "proposal")?;
+        Ok(proposal)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn address(&self) -> Result<Address> {
+        Address::from_str(&self.account).map_err(|e| Error::invalid("proposal", format!("account {}: {}", self.account, e)))
+    }
+
+    pub fn account_keys(&self) -> Result<Vec<common::PublicKey>> {
+        self.public_keys
+            .iter()
+            .map(|key| common::PublicKey::from_str(key).map_err(|e| Error::invalid("proposal", format!("key {}: {}", key, e))))
+            .collect()
+    }
+
+    pub fn fee_payer_key(&self) -> Result<common::PublicKey> {
+        common::PublicKey::from_str(&self.fee_payer)
+            .map_err(|e| Error::invalid("proposal", format!("fee payer {}: {}", self.fee_payer, e)))
+    }
+
+    /// What the tx transfers, read from the tx itself rather than from anything
+    /// the initiator wrote next to it. `None` if it is not a transparent transfer.
+    pub fn summary(&self) -> Option<String> {
+        let data = self.tx.commitments().first().and_then(|commitments| self.tx.data(commitments))?;
+        let transfer = namada_token::Transfer::try_from_slice(&data).ok()?;
+        let sources: Vec<String> = transfer
+            .sources
+            .iter()
+            .map(|(account, amount)| format!("{} {} from {}", amount, account.token, account.owner))
+            .collect();
+        let targets: Vec<String> = transfer
+            .targets
+            .iter()
+            .map(|(account, amount)| format!("{} {} to {}", amount, account.token, account.owner))
+            .collect();
+        Some(format!("Transfer {}, {}", sources.join(", "), targets.join(", ")))
+    }
+
+    // Refuse proposals whose tx is for another chain or was changed after it was hashed.
+    // The chain is read from the tx header, the one the signatures commit to.
+    fn check<C, U, V, I>(&self, sdk: &NamadaImpl<C, U, V, I>) -> Result<()>
+    where
+        C: Client + MaybeSync + MaybeSend,
+        U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+        V: ShieldedUtils + MaybeSync + MaybeSend,
+        I: Io + MaybeSync + MaybeSend,
+    {
+        let tx_chain_id = self.tx.header.chain_id.to_string();
+        if self.chain_id != tx_chain_id {
+            return Err(Error::invalid(
+                "proposal",
+                format!("it names chain {} but its tx is for chain {}", self.chain_id, tx_chain_id),
+            ));
+        }
+        let chain_id = sdk.tx_builder().chain_id.map(|id| id.to_string()).unwrap_or_default();
+        if tx_chain_id != chain_id {
+            return Err(Error::invalid(
+                "proposal",
+                format!("its tx is for chain {}, this wallet is on {}", tx_chain_id, chain_id),
+            ));
+        }
+        if self.tx.raw_header_hash().to_string() != self.tx_hash {
+            return Err(Error::invalid("proposal", "its tx does not match its hash"));
+        }
+        Ok(())
+    }
+}
+
+impl SignatureFile {
+    pub fn read(path: &Path) -> Result<Self> {
+        let file: SignatureFile = read_json(path, "signature")?;
+        check_version(file.version, "signature")?;
+        Ok(file)
+    }
+
+    pub fn write(&self, path: &Path) -> Result<()> {
+        write_json(path, self)
+    }
+
+    pub fn public_key(&self) -> &common::PublicKey {
+        &self.signature.pubkey
+    }
+}
+
+/// Where the signature of `proposal` by `key` is written: next to the proposal,
+/// as `<proposal name>.<key hash>.sig.json`.
+pub fn signature_path(proposal: &Path, key: &common::PublicKey) -> PathBuf {
+    let stem = proposal.file_stem().unwrap_or_default().to_string_lossy();
+    proposal.with_file_name(format!("{}.{}.sig.json", stem, PublicKeyHash::from(key)))
+}
+
+/// Build a transfer of `amount` of `token` from the multisig account `account`
+/// (an alias or address) to `target`, without signing it.
+///
+/// `fee_payer` (an alias or public key) signs the wrapper when the proposal is
+/// submitted, so whoever submits it must hold that key.
+#[instrument(skip_all, fields(account = account, target = %target, token = %token, amount = amount))]
+pub async fn propose_transfer<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    account: &str,
+    target: Address,
+    amount: &str,
+    token: Address,
+    fee_payer: &str,
+) -> Result<Proposal>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let address = wallet::resolve_address(sdk, account).await?;
+    if !matches!(address, Address::Established(_)) {
+        return Err(Error::invalid(
+            "account",
+            format!("{} is not an established account, sign for it directly", address),
+        ));
+    }
+    let current = query::get_account_info(sdk, &address)
+        .await?
+        .ok_or_else(|| Error::invalid("account", format!("{} does not exist on chain", address)))?;
+    let keys = account_key_set(&current.public_keys_map);
+    let fee_payer = wallet::resolve_public_key(sdk, fee_payer).await?;
+    let amount = InputAmount::from_str(amount).map_err(|e| Error::invalid("amount", format!("{}: {}", amount, e)))?;
+
+    let data = TxTransparentTransferData {
+        source: address.clone(),
+        target,
+        token,
+        amount,
+    };
+    let transfer_tx_builder = sdk
+        .new_transparent_transfer(vec![data])
+        .signing_keys(keys.clone())
+        .wrapper_fee_payer(fee_payer.clone());
+
+    let (transfer_tx, _signing_data) = transfer_tx_builder
+        .build(sdk)
+        .instrument(info_span!("build", tx = "multisig transfer"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "multisig transfer", source })?;
+
+    let proposal = Proposal {
+        version: PROPOSAL_VERSION,
+        chain_id: transfer_tx.header.chain_id.to_string(),
+        account: address.to_string(),
+        public_keys: keys.iter().map(|key| key.to_string()).collect(),
+        threshold: current.threshold,
+        fee_payer: fee_payer.to_string(),
+        tx_hash: transfer_tx.raw_header_hash().to_string(),
+        tx: transfer_tx,
+    };
+    info!(tx_hash = %proposal.tx_hash, threshold = proposal.threshold, "transfer proposed");
+    Ok(proposal)
+}
+
+/// Sign `proposal` through `signer` with the keys it holds among the account
+/// keys, or only with `keys` (aliases or public keys) when some are given. One
+/// signature per key.
+///
+/// Needs no node: the proposal carries the key set the signatures refer to.
+#[instrument(skip_all, fields(account = %proposal.account, tx_hash = %proposal.tx_hash))]
+pub async fn sign_proposal<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    proposal: &Proposal,
+    keys: &[String],
+) -> Result<Vec<SignatureFile>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    proposal.check(sdk)?;
+    let address = proposal.address()?;
+    let account_keys = proposal.account_keys()?;
+
+    let held = signer.held_keys(account_keys.clone()).await?;
+    let signers = if keys.is_empty() {
+        held
+    } else {
+        let mut signers = Vec::with_capacity(keys.len());
+        for value in keys {
+            let key = wallet::resolve_public_key(sdk, value).await?;
+            if !account_keys.contains(&key) {
+                return Err(Error::invalid("key", format!("{} is not a key of {}", value, address)));
+            }
+            if !held.contains(&key) {
+                return Err(Error::invalid("key", format!("the signer does not hold the secret key of {}", value)));
+            }
+            if !signers.contains(&key) {
+                signers.push(key);
+            }
+        }
+        signers
+    };
+    if signers.is_empty() {
+        return Err(Error::WatchOnly(address));
+    }
+
+    let signing_data = SigningTxData {
+        owner: Some(address),
+        public_keys: signers.clone(),
+        threshold: proposal.threshold,
+        account_public_keys_map: Some(AccountPublicKeysMap::from_iter(account_keys)),
+        fee_payer: proposal.fee_payer_key()?,
+    };
+    let signatures = signer::sign_raw_header(signer, &proposal.tx, &signing_data, &signers).await?;
+    info!(signatures = signatures.len(), "proposal signed");
+
+    Ok(signatures
+        .into_iter()
+        .map(|signature| SignatureFile {
+            version: PROPOSAL_VERSION,
+            tx_hash: proposal.tx_hash.clone(),
+            signature,
+        })
+        .collect())
+}
+
+/// Attach the co-signers' `signatures` and those of `signer` to `proposal`, sign
+/// the wrapper with the fee payer key and submit it.
+///
+/// The account's key set and threshold are read from chain. Nothing is signed if
+/// the key set changed since the proposal, a signature is for another tx, another
+/// account or another key, or does not verify, or fewer distinct keys signed
+/// than the threshold.
+#[instrument(skip_all, fields(account = %proposal.account, tx_hash = %proposal.tx_hash, signatures = signatures.len()))]
+pub async fn submit_proposal<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    proposal: &Proposal,
+    signatures: &[SignatureFile],
+) -> Result<TxSubmission>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    proposal.check(sdk)?;
+    let address = proposal.address()?;
+    let current = query::get_account_info(sdk, &address)
+        .await?
+        .ok_or_else(|| Error::invalid("account", format!("{} does not exist on chain", address)))?;
+    let account_keys = account_key_set(&current.public_keys_map);
+    if account_keys != proposal.account_keys()? {
+        return Err(Error::invalid(
+            "proposal",
+            format!("the key set of {} changed since it was made, propose the transfer again", address),
+        ));
+    }
+
+    let mut signed: Vec<&SignatureFile> = Vec::with_capacity(signatures.len());
+    for file in signatures {
+        if file.tx_hash != proposal.tx_hash {
+            return Err(Error::invalid("signature", format!("it signs tx {}, not {}", file.tx_hash, proposal.tx_hash)));
+        }
+        let Some(index) = current.public_keys_map.get_index_from_public_key(file.public_key()) else {
+            return Err(Error::invalid("signature", format!("{} is not a key of {}", file.public_key(), address)));
+        };
+        if file.signature.index != Some((address.clone(), index)) {
+            return Err(Error::invalid(
+                "signature",
+                format!("the signature by {} is not for key {} of {}", file.public_key(), index, address),
+            ));
+        }
+        signer::verify_presigned(&proposal.tx, &address, &file.signature)?;
+        if signed.iter().all(|other| other.public_key() != file.public_key()) {
+            signed.push(file);
+        }
+    }
+    let own: Vec<common::PublicKey> = signer
+        .held_keys(account_keys)
+        .await?
+        .into_iter()
+        .filter(|key| signed.iter().all(|file| file.public_key() != key))
+        .collect();
+
+    let collected = signed.len() + own.len();
+    if collected < usize::from(current.threshold) {
+        return Err(Error::ThresholdNotMet {
+            address,
+            threshold: current.threshold,
+            held: collected,
+        });
+    }
+
+    let fee_payer = proposal.fee_payer_key()?;
+    if signer.held_keys(vec![fee_payer.clone()]).await?.is_empty() {
+        return Err(Error::invalid(
+            "fee payer",
+            format!("the signer does not hold the secret key of {}, it signs the wrapper", fee_payer),
+        ));
+    }
+
+    let mut args = sdk.tx_builder();
+    args.signatures = signed
+        .iter()
+        .map(|file| serde_json::to_vec(&file.signature))
+        .collect::<Result<_, _>>()
+        .map_err(|e| Error::invalid("signature", e))?;
+    let signing_data = SigningTxData {
+        owner: Some(address),
+        public_keys: own,
+        threshold: current.threshold,
+        account_public_keys_map: Some(current.public_keys_map),
+        fee_payer,
+    };
+
+    let response = sign_and_submit(sdk, signer, proposal.tx.clone(), &args, signing_data, "multisig transfer").await?;
+    Ok(TxSubmission { reveal: Vec::new(), response })
+}
+
+// The account keys by index
+fn account_key_set(public_keys_map: &AccountPublicKeysMap) -> Vec<common::PublicKey> {
+    let mut keys: Vec<(&u8, &common::PublicKey)> = public_keys_map.idx_to_pk.iter().collect();
+    keys.sort_by_key(|(index, _)| **index);
+    keys.into_iter().map(|(_, key)| key.clone()).collect()
+}
+
+fn check_version(version: u8, what: &'static str) -> Result<()> {
+    if version != PROPOSAL_VERSION {
+        return Err(Error::invalid(
+            what,
+            format!("format version {} is not supported, expected {}", version, PROPOSAL_VERSION),
+        ));
+    }
+    Ok(())
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path, what: &'static str) -> Result<T> {
+    let data = fs::read(path).map_err(|e| Error::WalletIo(format!("Unable to read {}: {}", path.display(), e)))?;
+    serde_json::from_slice(&data).map_err(|e| Error::invalid(what, format!("{}: {}", path.display(), e)))
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) -> Result<()> {
+    let data = serde_json::to_vec_pretty(value).map_err(|e| Error::WalletIo(format!("Unable to encode {}: {}", path.display(), e)))?;
+    write_atomic(path, &data)
+}
@@ -0,0 +1,102 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use namada_sdk::io::{Client, Io};
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::wallet::{WalletIo, WalletStorage};
+use namada_sdk::{MaybeSend, MaybeSync, Namada, NamadaImpl};
+use tendermint::Time;
+
+use crate::error::{Error, Result};
+
+/// Oldest latest-block time we accept before considering the node stalled.
+pub const MAX_BLOCK_AGE: Duration = Duration::from_secs(5 * 60);
+
+/// What the node reports about itself on `/status`.
+#[derive(Clone, Debug)]
+pub struct NodeStatus {
+    pub moniker: String,
+    pub network: String,
+    pub version: String,
+    pub latest_block_height: u64,
+    pub latest_block_time: Time,
+    pub catching_up: bool,
+}
+
+impl NodeStatus {
+    /// Time elapsed since the latest block, zero if the block is from the future.
+    pub fn block_age(&self) -> Duration {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let block = u64::try_from(self.latest_block_time.unix_timestamp()).unwrap_or(0);
+        Duration::from_secs(now.saturating_sub(block))
+    }
+
+    /// Check that the node serves `chain_id`, is caught up and has produced a block recently.
+    pub fn check(&self, chain_id: &str) -> Result<()> {
+        if self.network != chain_id {
+            return Err(Error::ChainIdMismatch {
+                expected: chain_id.to_string(),
+                node: self.network.clone(),
+            });
+        }
+        if self.catching_up {
+            return Err(Error::NodeNotReady(format!(
+                "node is still catching up (height {})",
+                self.latest_block_height
+            )));
+        }
+        let age = self.block_age();
+        if age > MAX_BLOCK_AGE {
+            return Err(Error::NodeNotReady(format!(
+                "latest block {} is {}s old",
+                self.latest_block_height,
+                age.as_secs()
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Query the node's `/status` endpoint.
+pub async fn node_status<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<NodeStatus>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let status = sdk
+        .client()
+        .status()
+        .await
+        .map_err(|e| Error::Rpc(format!("Unable to query node status: {}", e)))?;
+
+    Ok(NodeStatus {
+        moniker: status.node_info.moniker.to_string(),
+        network: status.node_info.network.to_string(),
+        version: status.node_info.version.to_string(),
+        latest_block_height: status.sync_info.latest_block_height.value(),
+        latest_block_time: status.sync_info.latest_block_time,
+        catching_up: status.sync_info.catching_up,
+    })
+}
+
+/// Query the node status and check it against the chain id the SDK was built with.
+pub async fn verify_node<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<NodeStatus>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let chain_id = sdk
+        .tx_builder()
+        .chain_id
+        .ok_or_else(|| Error::Config("No chain id configured".to_string()))?;
+
+    let status = node_status(sdk).await?;
+    status.check(chain_id.as_str())?;
+    Ok(status)
+}
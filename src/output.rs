@@ -0,0 +1,621 @@
+use std::fmt;
+
+use clap::ValueEnum;
+use colored::*;
+use namada_core::masp::MaspEpoch;
+use namada_sdk::tx::ProcessTxResponse;
+use serde::Serialize;
+use wallet_example::account::AccountDiff;
+use wallet_example::backup::{Backup, BackupEntry, BackupSummary, RestoreMode, RestoreSummary};
+use wallet_example::inventory::{Custody, EntryKind, WalletEntry};
+use wallet_example::multisig::Proposal;
+use wallet_example::node::NodeStatus;
+use wallet_example::recovery::RecoveredAccount;
+use wallet_example::transfer::{self, TxSubmission};
+
+/// How operation results are written to stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    /// Human readable lines
+    #[default]
+    Text,
+    /// One JSON document per operation
+    Json,
+}
+
+/// Result of a single operation, printed as text or serialized as JSON.
+#[derive(Debug, Serialize)]
+#[serde(tag = "operation", rename_all = "kebab-case")]
+pub enum Report {
+    WalletCreated {
+        alias: String,
+        address: Option<String>,
+    },
+    Mnemonic {
+        alias: String,
+        mnemonic: String,
+    },
+    Inventory {
+        entries: Vec<WalletEntry>,
+    },
+    Renamed {
+        alias: String,
+        new_alias: String,
+        kinds: Vec<EntryKind>,
+    },
+    Deleted {
+        alias: String,
+        kinds: Vec<EntryKind>,
+    },
+    Cancelled,
+    Wallets {
+        home: String,
+        active: Option<String>,
+        wallets: Vec<String>,
+    },
+    WalletAdded {
+        name: String,
+        path: String,
+    },
+    BackupCreated {
+        file: String,
+        entries: Vec<BackupEntry>,
+        mnemonics: bool,
+        shielded_files: Vec<String>,
+    },
+    BackupRestored {
+        mode: RestoreMode,
+        imported: Vec<BackupEntry>,
+        conflicts: Vec<BackupEntry>,
+        shielded_files: Vec<String>,
+    },
+    KeyAdded {
+        alias: String,
+        address: Option<String>,
+    },
+    AccountsRecovered {
+        accounts: Vec<AccountReport>,
+    },
+    Address {
+        alias: String,
+        address: Option<String>,
+    },
+    SpendingKeyCreated {
+        alias: String,
+    },
+    SpendingKeyImported {
+        alias: String,
+        viewing_key: String,
+    },
+    PaymentAddress {
+        alias: String,
+        payment_address: String,
+    },
+    Watching {
+        alias: String,
+        kind: EntryKind,
+        value: String,
+    },
+    AccountCreated {
+        alias: String,
+        address: Option<String>,
+        result: TxReport,
+    },
+    AccountUpdated {
+        diff: AccountDiff,
+        result: TxReport,
+    },
+    TransferProposed {
+        file: String,
+        account: String,
+        threshold: u8,
+        tx_hash: String,
+        summary: Option<String>,
+    },
+    ProposalSigned {
+        tx_hash: String,
+        files: Vec<String>,
+    },
+    Transaction {
+        kind: &'static str,
+        reveal: Vec<TxReport>,
+        result: TxReport,
+    },
+    RevealPk {
+        reveal: Vec<TxReport>,
+    },
+    RevealStatus {
+        owner: String,
+        revealed: bool,
+    },
+    ShieldedSync {
+        synced_height: Option<u64>,
+    },
+    Balance {
+        owner: String,
+        token: String,
+        amount: String,
+        raw_amount: String,
+        denom: Option<u8>,
+    },
+    MaspEpoch {
+        epoch: MaspEpoch,
+    },
+    NodeStatus {
+        moniker: String,
+        network: String,
+        version: String,
+        latest_block_height: u64,
+        latest_block_time: String,
+        block_age_secs: u64,
+        catching_up: bool,
+        problem: Option<String>,
+    },
+    Batch {
+        dry_run: bool,
+        steps: Vec<StepReport>,
+    },
+}
+
+/// An account imported by a recovery scan.
+#[derive(Debug, Serialize)]
+pub struct AccountReport {
+    pub index: u32,
+    pub alias: String,
+    pub address: String,
+    pub revealed: bool,
+}
+
+/// Result of one step of a batch.
+#[derive(Debug, Serialize)]
+pub struct StepReport {
+    pub index: usize,
+    pub op: &'static str,
+    pub status: StepStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Report>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StepStatus {
+    Ok,
+    Failed,
+    Skipped,
+}
+
+/// What the node returned for one submitted tx.
+#[derive(Debug, Serialize)]
+pub struct TxReport {
+    pub hash: String,
+    pub code: u32,
+    pub height: Option<u64>,
+    pub gas_used: Option<u64>,
+    pub info: String,
+    pub log: String,
+    /// Inner tx results of the batch, as reported by the node
+    pub inner_results: Option<serde_json::Value>,
+}
+
+impl Report {
+    pub fn transaction(kind: &'static str, submission: &TxSubmission) -> Self {
+        Report::Transaction {
+            kind,
+            reveal: submission.reveal.iter().map(TxReport::from).collect(),
+            result: TxReport::from(&submission.response),
+        }
+    }
+
+    /// Whether the operation as a whole should make the process exit with an error.
+    pub fn is_failure(&self) -> bool {
+        match self {
+            Report::Batch { steps, .. } => steps.iter().any(|step| step.status != StepStatus::Ok),
+            _ => false,
+        }
+    }
+
+    pub fn node_status(status: &NodeStatus, chain_id: &str) -> Self {
+        Report::NodeStatus {
+            moniker: status.moniker.clone(),
+            network: status.network.clone(),
+            version: status.version.clone(),
+            latest_block_height: status.latest_block_height,
+            latest_block_time: status.latest_block_time.to_rfc3339(),
+            block_age_secs: status.block_age().as_secs(),
+            catching_up: status.catching_up,
+            problem: status.check(chain_id).err().map(|e| e.to_string()),
+        }
+    }
+}
+
+impl From<&RecoveredAccount> for AccountReport {
+    fn from(account: &RecoveredAccount) -> Self {
+        AccountReport {
+            index: account.index,
+            alias: account.alias.clone(),
+            address: account.address.to_string(),
+            revealed: account.revealed,
+        }
+    }
+}
+
+impl From<BackupSummary> for Report {
+    fn from(summary: BackupSummary) -> Self {
+        Report::BackupCreated {
+            file: summary.path.display().to_string(),
+            entries: summary.entries,
+            mnemonics: summary.mnemonics,
+            shielded_files: summary.shielded_files,
+        }
+    }
+}
+
+impl From<RestoreSummary> for Report {
+    fn from(summary: RestoreSummary) -> Self {
+        Report::BackupRestored {
+            mode: summary.mode,
+            imported: summary.imported,
+            conflicts: summary.conflicts,
+            shielded_files: summary.shielded_files,
+        }
+    }
+}
+
+/// What a backup holds, shown before it is restored.
+pub struct BackupPreview<'a>(pub &'a Backup);
+
+impl fmt::Display for BackupPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let backup = self.0;
+        write!(
+            f,
+            "Backup format {} made at unix time {} on chain {}",
+            backup.version(),
+            backup.created_at(),
+            backup.chain_id()
+        )?;
+        let entries = backup.entries();
+        write!(f, "\n{} wallet entries:", entries.len())?;
+        write_backup_entries(f, &entries)?;
+        if backup.has_mnemonics() {
+            write!(f, "\nStored mnemonics included")?;
+        }
+        let shielded_files = backup.shielded_files();
+        if !shielded_files.is_empty() {
+            write!(f, "\nShielded context: {}", shielded_files.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// The key set and threshold of an account before and after an update.
+pub struct AccountDiffPreview<'a>(pub &'a AccountDiff);
+
+impl fmt::Display for AccountDiffPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let diff = self.0;
+        write!(f, "Account {}", diff.address)?;
+        write!(f, "\n  {:<8} {:<7} {:<7} public key", "", "before", "after")?;
+        for key in &diff.keys {
+            let change = match (key.index_before, key.index_after) {
+                (None, _) => "added",
+                (_, None) => "removed",
+                (before, after) if before != after => "moved",
+                _ => "kept",
+            };
+            let index = |index: Option<u8>| index.map_or_else(|| "-".to_string(), |index| index.to_string());
+            let (before, after) = (index(key.index_before), index(key.index_after));
+            write!(f, "\n  {:<8} {:<7} {:<7} {}", change, before, after, key.public_key)?;
+            if let Some(alias) = &key.alias {
+                write!(f, " ({})", alias)?;
+            }
+        }
+        write!(f, "\nThreshold: {} -> {}", diff.threshold_before, diff.threshold_after)?;
+        if let Some(vp_code) = &diff.vp_code {
+            write!(f, "\nNew validity predicate: {}", vp_code)?;
+        }
+        Ok(())
+    }
+}
+
+/// What a co-signer is asked to sign.
+pub struct ProposalPreview<'a>(pub &'a Proposal);
+
+impl fmt::Display for ProposalPreview<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let proposal = self.0;
+        write!(f, "Proposal {} on chain {}", proposal.tx_hash, proposal.chain_id)?;
+        write!(f, "\n  {}", proposal.summary().as_deref().unwrap_or("Not a transparent transfer"))?;
+        write!(
+            f,
+            "\nAccount {} needs {} of {} signature(s)",
+            proposal.account,
+            proposal.threshold,
+            proposal.public_keys.len()
+        )?;
+        write!(f, "\nFees paid by {}", proposal.fee_payer)
+    }
+}
+
+impl From<&ProcessTxResponse> for TxReport {
+    fn from(response: &ProcessTxResponse) -> Self {
+        match response {
+            ProcessTxResponse::Applied(applied) => TxReport {
+                hash: transfer::tx_hash(response).unwrap_or_default(),
+                code: applied.code.to_usize() as u32,
+                height: Some(applied.height.0),
+                gas_used: Some(u64::from(applied.gas_used)),
+                info: applied.info.clone(),
+                log: applied.log.clone(),
+                inner_results: applied
+                    .batch
+                    .as_ref()
+                    .and_then(|batch| serde_json::to_value(batch).ok()),
+            },
+            ProcessTxResponse::Broadcast(broadcast) => TxReport {
+                hash: transfer::tx_hash(response).unwrap_or_default(),
+                code: broadcast.code.value(),
+                height: None,
+                gas_used: None,
+                info: String::new(),
+                log: broadcast.log.clone(),
+                inner_results: None,
+            },
+            ProcessTxResponse::DryRun(result) => TxReport {
+                hash: String::new(),
+                code: 0,
+                height: None,
+                gas_used: None,
+                info: "dry run".to_string(),
+                log: String::new(),
+                inner_results: serde_json::to_value(result).ok(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Report::WalletCreated { .. } => write!(f, "Wallet created and saved!"),
+            Report::Mnemonic { alias, mnemonic } => write!(f, "Mnemonic for {}: {}", alias, mnemonic),
+            Report::Renamed { alias, new_alias, kinds } => {
+                write!(f, "Renamed {} to {}: {}", alias, new_alias, kind_list(kinds))
+            }
+            Report::Deleted { alias, kinds } => write!(f, "Deleted {}: {}", alias, kind_list(kinds)),
+            Report::Cancelled => write!(f, "Cancelled, nothing was changed."),
+            Report::Wallets { home, wallets, .. } if wallets.is_empty() => write!(f, "No wallets in {} yet.", home),
+            Report::Wallets { home, active, wallets } => {
+                write!(f, "Wallets in {}:", home)?;
+                for name in wallets {
+                    let marker = if active.as_ref() == Some(name) { "*" } else { " " };
+                    write!(f, "\n{} {}", marker, name)?;
+                }
+                Ok(())
+            }
+            Report::WalletAdded { name, path } => write!(f, "Wallet {} created in {}", name, path),
+            Report::BackupCreated { file, entries, mnemonics, shielded_files } => {
+                write!(f, "Backup written to {} with {} wallet entries", file, entries.len())?;
+                if *mnemonics {
+                    write!(f, ", the stored mnemonics")?;
+                }
+                if !shielded_files.is_empty() {
+                    write!(f, ", the shielded context ({})", shielded_files.join(", "))?;
+                }
+                write_backup_entries(f, entries)
+            }
+            Report::BackupRestored { mode, imported, conflicts, shielded_files } => {
+                let mode = match mode {
+                    RestoreMode::Merge => "merged into",
+                    RestoreMode::Overwrite => "replaced",
+                };
+                write!(f, "Backup {} the wallet, {} entries imported", mode, imported.len())?;
+                write_backup_entries(f, imported)?;
+                if !conflicts.is_empty() {
+                    write!(f, "\nAlias already in use, kept the wallet's entry:")?;
+                    write_backup_entries(f, conflicts)?;
+                }
+                if !shielded_files.is_empty() {
+                    write!(f, "\nShielded context restored: {}", shielded_files.join(", "))?;
+                }
+                Ok(())
+            }
+            Report::Inventory { entries } if entries.is_empty() => write!(f, "No wallet entries found."),
+            Report::Inventory { entries } => {
+                for (i, entry) in entries.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write_entry(f, entry)?;
+                }
+                Ok(())
+            }
+            Report::KeyAdded { alias, .. } => write!(f, "Key added successfully with alias: {}", alias),
+            Report::AccountsRecovered { accounts } if accounts.is_empty() => {
+                write!(f, "No account with on-chain activity found.")
+            }
+            Report::AccountsRecovered { accounts } => {
+                write!(f, "Recovered {} account(s):", accounts.len())?;
+                for account in accounts {
+                    write!(f, "\n  #{} {}: {}", account.index, account.alias, account.address)?;
+                    if !account.revealed {
+                        write!(f, " (not revealed)")?;
+                    }
+                }
+                Ok(())
+            }
+            Report::Address { alias, address: Some(address) } => write!(f, "Address for {}: {}", alias, address),
+            Report::Address { alias, address: None } => write!(f, "No address found for alias: {}", alias),
+            Report::SpendingKeyCreated { alias } => write!(f, "Spending key {} created and saved!", alias),
+            Report::SpendingKeyImported { alias, viewing_key } => {
+                write!(f, "Spending key {} imported, viewing key: {}", alias, viewing_key)
+            }
+            Report::Watching { alias, kind, value } => {
+                write!(f, "Watching {} {} (no secret stored): {}", kind_name(*kind), alias, value)
+            }
+            Report::PaymentAddress { alias, payment_address } => {
+                write!(f, "New payment address generated and saved for {}: {}", alias, payment_address)
+            }
+            Report::AccountCreated { alias, address: Some(address), result } => {
+                write!(f, "Account {} created as {}: {}", address, alias, result)
+            }
+            Report::AccountCreated { result, .. } => {
+                write!(f, "Init account tx submitted, the new address was not reported so it was not stored: {}", result)
+            }
+            Report::TransferProposed { file, account, threshold, tx_hash, summary } => {
+                write!(f, "Proposal {} written to {}", tx_hash, file)?;
+                if let Some(summary) = summary {
+                    write!(f, "\n  {}", summary)?;
+                }
+                write!(f, "\nSend it to the co-signers, {} signature(s) of {} are needed", threshold, account)
+            }
+            Report::ProposalSigned { tx_hash, files } => {
+                write!(f, "Signed proposal {}, send back:", tx_hash)?;
+                for file in files {
+                    write!(f, "\n  {}", file)?;
+                }
+                Ok(())
+            }
+            Report::AccountUpdated { diff, result } => {
+                writeln!(f, "{}", AccountDiffPreview(diff))?;
+                write!(f, "Account update successfully submitted: {}", result)
+            }
+            Report::Transaction { kind, reveal, result } => {
+                for response in reveal {
+                    writeln!(f, "Public key successfully revealed: {}", response)?;
+                }
+                write!(f, "{} successfully submitted: {}", kind, result)
+            }
+            Report::RevealPk { reveal } if reveal.is_empty() => write!(f, "The account is already revealed."),
+            Report::RevealPk { reveal } => {
+                for (i, response) in reveal.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "Public key successfully revealed: {}", response)?;
+                }
+                Ok(())
+            }
+            Report::RevealStatus { revealed: true, .. } => write!(f, "The account is revealed."),
+            Report::RevealStatus { revealed: false, .. } => write!(f, "The account is not revealed."),
+            Report::ShieldedSync { synced_height: Some(height) } => {
+                write!(f, "Shielded context synced to height {}", height)
+            }
+            Report::ShieldedSync { synced_height: None } => write!(f, "Shielded context synced"),
+            Report::Balance { amount, .. } => write!(f, "Token Balance: {}", amount),
+            Report::MaspEpoch { epoch } => write!(f, "Last committed masp epoch: {}", epoch),
+            Report::NodeStatus {
+                moniker,
+                network,
+                version,
+                latest_block_height,
+                latest_block_time,
+                block_age_secs,
+                catching_up,
+                problem,
+            } => {
+                writeln!(f, "Node: {} ({})", moniker, version)?;
+                writeln!(f, "Network: {}", network)?;
+                writeln!(
+                    f,
+                    "Latest block: {} at {} ({}s ago)",
+                    latest_block_height, latest_block_time, block_age_secs
+                )?;
+                writeln!(f, "Catching up: {}", catching_up)?;
+                match problem {
+                    None => write!(f, "Node is healthy"),
+                    Some(problem) => write!(f, "{}", problem),
+                }
+            }
+            Report::Batch { dry_run, steps } => {
+                if *dry_run {
+                    writeln!(f, "Dry run, nothing was broadcast")?;
+                }
+                for (i, step) in steps.iter().enumerate() {
+                    if i > 0 {
+                        writeln!(f)?;
+                    }
+                    write!(f, "[{}] {}: ", step.index + 1, step.op)?;
+                    match (&step.result, &step.error) {
+                        (Some(result), _) => write!(f, "ok\n{}", result)?,
+                        (None, Some(error)) => write!(f, "failed: {}", error)?,
+                        (None, None) => write!(f, "skipped")?,
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn kind_name(kind: EntryKind) -> &'static str {
+    match kind {
+        EntryKind::Address => "address",
+        EntryKind::PublicKey => "public key",
+        EntryKind::SpendingKey => "spending key",
+        EntryKind::ViewingKey => "viewing key",
+        EntryKind::PaymentAddress => "payment address",
+    }
+}
+
+fn kind_list(kinds: &[EntryKind]) -> String {
+    kinds.iter().map(|&kind| kind_name(kind)).collect::<Vec<_>>().join(", ")
+}
+
+// One indented line per entry, after the line already written
+fn write_backup_entries(f: &mut fmt::Formatter<'_>, entries: &[BackupEntry]) -> fmt::Result {
+    for entry in entries {
+        write!(f, "\n  {:<16} {}", kind_name(entry.kind), entry.alias)?;
+    }
+    Ok(())
+}
+
+// kind, alias, custody and value of a wallet entry, then its address type and HD path
+fn write_entry(f: &mut fmt::Formatter<'_>, entry: &WalletEntry) -> fmt::Result {
+    let kind = kind_name(entry.kind);
+    let custody = match entry.custody {
+        Custody::Secret => "secret",
+        Custody::EncryptedSecret => "secret (encrypted)",
+        Custody::WatchOnly => "watch-only",
+        Custody::ReceiveOnly => "receive-only",
+    };
+    write!(f, "{:<16} {:<24} {:<19} {}", kind, entry.alias, custody, entry.value)?;
+    if let Some(address_type) = &entry.address_type {
+        write!(f, " ({})", address_type)?;
+    }
+    if let Some(path) = &entry.derivation_path {
+        write!(f, " [{}]", path)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for TxReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "hash {} (code {}", self.hash, self.code)?;
+        if let Some(height) = self.height {
+            write!(f, ", height {}", height)?;
+        }
+        if let Some(gas_used) = self.gas_used {
+            write!(f, ", gas used {}", gas_used)?;
+        }
+        write!(f, ")")
+    }
+}
+
+impl OutputFormat {
+    pub fn emit(self, report: &Report) {
+        match self {
+            OutputFormat::Text => println!("{}", report),
+            OutputFormat::Json => println!("{}", serde_json::to_string(report).expect("reports serialize to JSON")),
+        }
+    }
+
+    pub fn emit_error(self, error: &wallet_example::Error) {
+        match self {
+            OutputFormat::Text => eprintln!("{}", format!("Error: {}", error).red()),
+            OutputFormat::Json => println!("{}", serde_json::json!({ "error": error.to_string() })),
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use namada_core::address::Address;
+use namada_sdk::io::{Client, Io};
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::tx::ProcessTxResponse;
+use namada_sdk::wallet::{WalletIo, WalletStorage};
+use namada_sdk::{token, MaybeSend, MaybeSync, Namada, NamadaImpl, PaymentAddress};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::signer::Signer;
+use crate::transfer::{self, TxSubmission};
+use crate::{query, wallet};
+
+/// What to do with the remaining steps once one fails.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnError {
+    #[default]
+    Stop,
+    Continue,
+}
+
+/// A list of wallet operations run in order, loaded from a TOML or JSON file.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Plan {
+    #[serde(default)]
+    pub on_error: OnError,
+    pub steps: Vec<Step>,
+}
+
+/// One operation of a plan.
+///
+/// Transfer steps use the fields of `TxTransparentTransferData` and
+/// `TxShieldingTransferData`, with aliases accepted wherever an address is.
+/// `token` defaults to the native token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "op", rename_all = "kebab-case")]
+pub enum Step {
+    RevealPk {
+        owner: String,
+    },
+    Shield {
+        source: String,
+        target: String,
+        token: Option<String>,
+        amount: String,
+    },
+    Transfer {
+        source: String,
+        target: String,
+        token: Option<String>,
+        amount: String,
+    },
+    IbcTransfer {
+        source: String,
+        receiver: String,
+        channel_id: String,
+        token: Option<String>,
+        amount: String,
+    },
+    Balance {
+        owner: String,
+        token: Option<String>,
+    },
+}
+
+/// What a successful step produced.
+#[derive(Debug)]
+pub enum StepOutput {
+    Revealed(Vec<ProcessTxResponse>),
+    Submitted(TxSubmission),
+    Balance {
+        owner: Address,
+        token: Address,
+        amount: token::Amount,
+    },
+}
+
+/// Outcome of one step. Steps skipped after a failure have no result.
+#[derive(Debug)]
+pub struct StepResult {
+    pub index: usize,
+    pub step: Step,
+    pub outcome: Option<Result<StepOutput>>,
+}
+
+impl Step {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Step::RevealPk { .. } => "reveal-pk",
+            Step::Shield { .. } => "shield",
+            Step::Transfer { .. } => "transfer",
+            Step::IbcTransfer { .. } => "ibc-transfer",
+            Step::Balance { .. } => "balance",
+        }
+    }
+}
+
+impl Plan {
+    /// Load a plan, as JSON for `.json` files and TOML otherwise.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| Error::invalid("plan file", format!("{}: {}", path.display(), e)))?;
+        let plan = if path.extension().is_some_and(|ext| ext == "json") {
+            serde_json::from_str(&content).map_err(|e| e.to_string())
+        } else {
+            toml::from_str(&content).map_err(|e| e.to_string())
+        };
+        plan.map_err(|e| Error::invalid("plan file", format!("{}: {}", path.display(), e)))
+    }
+}
+
+/// Run every step of `plan` in order, signing its txs through `signer`.
+///
+/// With `OnError::Stop` the steps after the first failure are reported as skipped.
+/// To build and sign the txs without broadcasting them, pass an SDK built with
+/// `.dry_run(true)`; the node then only simulates them. A dry run signs like a
+/// real one and encrypted keys are unlocked as usual. As nothing is applied, a
+/// `reveal-pk` step does not reveal anything for the steps after it: their txs
+/// could not be checked against the key, so a transfer whose source is unrevealed
+/// on chain fails with [`Error::UnverifiableDryRun`] without being signed.
+pub async fn run_plan<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, signer: &dyn Signer, plan: &Plan) -> Vec<StepResult>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let mut results = Vec::with_capacity(plan.steps.len());
+    let mut failed = false;
+
+    for (index, step) in plan.steps.iter().enumerate() {
+        let outcome = if failed && plan.on_error == OnError::Stop {
+            None
+        } else {
+            let outcome = run_step(sdk, signer, step).await;
+            failed |= outcome.is_err();
+            Some(outcome)
+        };
+        results.push(StepResult {
+            index,
+            step: step.clone(),
+            outcome,
+        });
+    }
+
+    results
+}
+
+async fn run_step<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, signer: &dyn Signer, step: &Step) -> Result<StepOutput>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    match step {
+        Step::RevealPk { owner } => {
+            let owner = wallet::resolve_address(sdk, owner).await?;
+            let responses = transfer::reveal_pk_if_needed(sdk, signer, &owner).await?;
+            Ok(StepOutput::Revealed(responses))
+        }
+        Step::Shield { source, target, token, amount } => {
+            check_verifiable(sdk, source).await?;
+            let target = PaymentAddress::from_str(target)
+                .map_err(|e| Error::invalid("target address", format!("{}: {}", target, e)))?;
+            let token = wallet::resolve_token(sdk, token.as_deref()).await?;
+            let submission = transfer::send_token_shielded(sdk, signer, source, &target, amount, token).await?;
+            Ok(StepOutput::Submitted(submission))
+        }
+        Step::Transfer { source, target, token, amount } => {
+            check_verifiable(sdk, source).await?;
+            let target = wallet::resolve_address(sdk, target).await?;
+            let token = wallet::resolve_token(sdk, token.as_deref()).await?;
+            let submission = transfer::send_transparent_token(sdk, signer, source, target, amount, token).await?;
+            Ok(StepOutput::Submitted(submission))
+        }
+        Step::IbcTransfer { source, receiver, channel_id, token, amount } => {
+            check_verifiable(sdk, source).await?;
+            let token = wallet::resolve_token(sdk, token.as_deref()).await?;
+            let submission = transfer::send_ibc_token(sdk, signer, source, receiver, channel_id, amount, token).await?;
+            Ok(StepOutput::Submitted(submission))
+        }
+        Step::Balance { owner, token } => {
+            let owner = wallet::resolve_address(sdk, owner).await?;
+            let token = wallet::resolve_token(sdk, token.as_deref()).await?;
+            let amount = query::get_token_balance(sdk, &owner, &token).await?;
+            Ok(StepOutput::Balance { owner, token, amount })
+        }
+    }
+}
+
+// In a dry run the node checks a tx against the keys revealed on chain, and a
+// simulated reveal is never applied: refuse to sign a tx it can not verify
+async fn check_verifiable<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, source: &str) -> Result<()>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if !sdk.tx_builder().dry_run {
+        return Ok(());
+    }
+    let source = wallet::resolve_address(sdk, source).await?;
+    if matches!(source, Address::Implicit(_)) && !query::findifreveal(sdk, &source).await? {
+        return Err(Error::UnverifiableDryRun(source));
+    }
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use colored::*;
+use namada_core::address::Address;
+use namada_core::masp::MaspEpoch;
+use namada_sdk::account::Account;
+use namada_sdk::masp::shielded_wallet::ShieldedApi;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::{
+    io::{Client, Io},
+    rpc, token,
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+
+use crate::error::{Error, Result};
+use crate::wallet;
+
+/// Fetch the on-chain account of `owner`, if it has one.
+pub async fn get_account_info<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, owner: &Address) -> Result<Option<Account>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    rpc::get_account_info(sdk.client(), owner)
+        .await
+        .map_err(|e| Error::Rpc(format!("Unable to query account info: {}", e)))
+}
+
+/// Whether `owner` has revealed at least one public key on chain.
+pub async fn findifreveal<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, owner: &Address) -> Result<bool>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let account_info = get_account_info(sdk, owner).await?;
+    Ok(account_info.map_or(false, |account| !account.public_keys_map.idx_to_pk.is_empty()))
+}
+
+/// Transparent balance of `token` held by `owner`.
+pub async fn get_token_balance<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    owner: &Address,
+    token: &Address,
+) -> Result<token::Amount>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    rpc::get_token_balance(sdk.client(), token, owner, None)
+        .await
+        .map_err(|e| Error::Rpc(format!("Unable to query token balance: {}", e)))
+}
+
+/// Shielded balance of `token` visible to `viewing_key` (a key or its alias), from the last synced shielded context.
+///
+/// With `no_conversions` unset the balance is exchanged to the current MASP epoch.
+pub async fn shielded_balance<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    viewing_key: &str,
+    token: &Address,
+    no_conversions: bool,
+) -> Result<token::Amount>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let viewing_key = wallet::resolve_viewing_key(sdk, viewing_key).await?.as_viewing_key();
+
+    // Load and precompute asset types
+    {
+        let mut shielded = sdk.shielded_mut().await;
+        shielded
+            .load()
+            .await
+            .map_err(|e| Error::Shielded(format!("Unable to load shielded context: {}", e)))?;
+        shielded
+            .precompute_asset_types(sdk.client(), vec![token])
+            .await
+            .map_err(|e| Error::Rpc(format!("Unable to precompute asset types: {}", e)))?;
+        shielded
+            .save()
+            .await
+            .map_err(|e| Error::Shielded(format!("Unable to save shielded context: {}", e)))?;
+    }
+
+    let masp_epoch = query_masp_epoch(sdk).await?;
+    let mut shielded = sdk.shielded_mut().await;
+
+    let balance = if no_conversions {
+        shielded.compute_shielded_balance(&viewing_key).await
+    } else {
+        shielded
+            .compute_exchanged_balance(sdk.client(), sdk.io(), &viewing_key, masp_epoch)
+            .await
+    }
+    .map_err(|e| Error::Shielded(format!("Unable to compute shielded balance: {}", e)))?;
+
+    let Some(balance) = balance else {
+        return Ok(token::Amount::zero());
+    };
+    let total_balance = shielded
+        .decode_combine_sum_to_epoch(sdk.client(), balance, masp_epoch)
+        .await
+        .0
+        .get(token);
+
+    Ok(total_balance.into())
+}
+
+/// Last committed MASP epoch.
+pub async fn query_masp_epoch(context: &impl Namada) -> Result<MaspEpoch> {
+    rpc::query_masp_epoch(context.client())
+        .await
+        .map_err(|e| Error::Rpc(format!("Unable to query masp epoch: {}", e)))
+}
+
+/// Query the last committed MASP epoch and print it.
+pub async fn query_and_print_masp_epoch(context: &impl Namada) -> Result<MaspEpoch> {
+    let epoch = query_masp_epoch(context).await?;
+
+    // Print the epoch in green color
+    println!("{}", format!("Last committed masp epoch: {}", epoch).green());
+
+    Ok(epoch)
+}
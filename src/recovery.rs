@@ -0,0 +1,113 @@
+use namada_core::address::Address;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::{
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use tracing::{debug, info, instrument};
+
+use crate::error::{Error, Result};
+use crate::query;
+use crate::wallet::{self, KeyOptions};
+
+/// Unused accounts in a row after which a recovery scan stops, as in BIP44.
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
+/// An account found with on-chain activity and imported into the wallet.
+#[derive(Clone, Debug)]
+pub struct RecoveredAccount {
+    pub index: u32,
+    pub alias: String,
+    pub address: Address,
+    /// Whether the account exists on chain, i.e. has revealed its public key
+    pub revealed: bool,
+}
+
+/// Derive the accounts of `phrase` at indices 0, 1, 2, ... and import every one
+/// that exists on chain or holds a balance of the native token or of `tokens`.
+///
+/// The scan stops after `gap_limit` unused accounts in a row. Account 0 is stored
+/// under `alias`, account N under `alias-N`; nothing is stored if one of those
+/// aliases is taken by another entry, and accounts already stored under theirs
+/// are kept as they are. `options.account` and `options.derivation_path` are
+/// ignored.
+#[instrument(skip_all, fields(alias = alias, gap_limit = gap_limit))]
+pub async fn recover_accounts<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    phrase: &str,
+    options: &KeyOptions,
+    tokens: &[Address],
+    gap_limit: u32,
+) -> Result<Vec<RecoveredAccount>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if gap_limit == 0 {
+        return Err(Error::invalid("gap limit", "must be at least 1"));
+    }
+
+    let mut tokens = tokens.to_vec();
+    let native_token = sdk.native_token();
+    if !tokens.contains(&native_token) {
+        tokens.insert(0, native_token);
+    }
+
+    let mut found = Vec::new();
+    let mut unused = 0;
+    let mut index = 0;
+    while unused < gap_limit {
+        let account_options = KeyOptions {
+            account: index,
+            derivation_path: None,
+            ..options.clone()
+        };
+        let address = wallet::derive_address(phrase, &account_options)?;
+
+        let revealed = query::get_account_info(sdk, &address).await?.is_some();
+        let mut funded = false;
+        for token in &tokens {
+            if !query::get_token_balance(sdk, &address, token).await?.is_zero() {
+                funded = true;
+                break;
+            }
+        }
+        debug!(index, %address, revealed, funded, "scanned account");
+
+        if revealed || funded {
+            let account_alias = if index == 0 { alias.to_string() } else { format!("{}-{}", alias, index) };
+            found.push((RecoveredAccount { index, alias: account_alias, address, revealed }, account_options));
+            unused = 0;
+        } else {
+            unused += 1;
+        }
+        index += 1;
+    }
+
+    // Check every alias before storing any, so a taken one leaves the wallet as it was
+    let mut stored = Vec::with_capacity(found.len());
+    {
+        let wallet = sdk.wallet().await;
+        for (account, _) in &found {
+            match wallet.find_address(&account.alias) {
+                Some(existing) if *existing == account.address => stored.push(true),
+                None if wallet.find_public_key(&account.alias).is_err() => stored.push(false),
+                _ => return Err(Error::AliasTaken(account.alias.clone())),
+            }
+        }
+    }
+
+    let mut recovered = Vec::with_capacity(found.len());
+    for ((mut account, account_options), stored) in found.into_iter().zip(stored) {
+        if !stored {
+            account.alias = wallet::add_key(sdk, account.alias, phrase, &account_options).await?;
+        }
+        info!(index = account.index, address = %account.address, alias = account.alias, "recovered account");
+        recovered.push(account);
+    }
+    Ok(recovered)
+}
@@ -0,0 +1,146 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use masp_primitives::sapling::ViewingKey;
+use namada_sdk::control_flow::install_shutdown_signal;
+use namada_sdk::masp::shielded_wallet::ShieldedApi;
+use namada_sdk::masp::{IndexerMaspClient, MaspLocalTaskEnv, ShieldedSyncConfig, ShieldedUtils};
+use namada_sdk::wallet::{DatedKeypair, DatedSpendingKey};
+use namada_sdk::{
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    ExtendedSpendingKey, MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use tracing::field::Empty;
+use tracing::{info, instrument, Span};
+
+use crate::error::{Error, Result};
+use crate::wallet;
+
+/// Sync the shielded context for `key` from the MASP indexer at `indexer_url`,
+/// and save it to the shielded dir.
+///
+/// `key` is an extended spending key, an extended viewing key, or the alias of a
+/// viewing key in the wallet, such as a watch-only one. An alias syncs from its birthday.
+///
+/// Returns the height the context is synced to, `None` if no tx has been scanned yet.
+#[instrument(skip_all, fields(indexer_url = indexer_url, synced_height = Empty))]
+pub async fn shielded_sync<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    indexer_url: &str,
+    key: &str,
+) -> Result<Option<u64>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let (spending_keys, viewing_keys) = sync_keys(sdk, key).await?;
+
+    // create a thread pool for the shielded sync
+    let env = MaspLocalTaskEnv::new(500)
+        .map_err(|e| Error::Shielded(format!("could not create masp env: {}", e)))?;
+
+    // progress bars for displaying sync progress
+    let fetched = kdam::tqdm!(
+        total = 0,
+        desc = "fetched ",
+        animation = "fillup",
+        position = 0,
+        force_refresh = true,
+        dynamic_ncols = true,
+        miniters = 0,
+        mininterval = 0.05
+    );
+
+    let scanned = kdam::tqdm!(
+        total = 0,
+        desc = "scanned ",
+        animation = "fillup",
+        position = 1,
+        force_refresh = true,
+        dynamic_ncols = true,
+        miniters = 0,
+        mininterval = 0.05
+    );
+
+    let applied = kdam::tqdm!(
+        total = 0,
+        desc = "applied ",
+        animation = "fillup",
+        position = 2,
+        force_refresh = true,
+        dynamic_ncols = true,
+        miniters = 0,
+        mininterval = 0.05
+    );
+
+    // create a masp client to sync from the masp-indexer
+    let client = reqwest::Client::builder()
+        .connect_timeout(Duration::from_secs(60))
+        .build()
+        .map_err(|e| Error::Indexer(format!("Failed to build HTTP client: {}", e)))?;
+
+    let url = indexer_url
+        .try_into()
+        .map_err(|err| Error::Indexer(format!("Failed to parse API endpoint {indexer_url:?}: {err}")))?;
+
+    let shielded_client = IndexerMaspClient::new(client, url, true, 100);
+
+    let config = ShieldedSyncConfig::builder()
+        .client(shielded_client)
+        .fetched_tracker(fetched)
+        .scanned_tracker(scanned)
+        .applied_tracker(applied)
+        .shutdown_signal(install_shutdown_signal(false))
+        .build();
+
+    // shielded sync and save the results, starting from what was saved last
+    // (nothing before the first sync) rather than a possibly stale context
+    let mut shielded = sdk.shielded_mut().await;
+    match shielded.load().await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(Error::Shielded(format!("Unable to load shielded context: {}", e))),
+    }
+    shielded
+        .sync(env, config, None, &spending_keys, &viewing_keys)
+        .await
+        .map_err(|e| Error::Indexer(format!("Could not sync shielded context: {}", e)))?;
+
+    let synced_height = shielded
+        .vk_heights
+        .values()
+        .flatten()
+        .map(|indexed_tx| indexed_tx.height.0)
+        .max();
+    if let Some(height) = synced_height {
+        Span::current().record("synced_height", height);
+    }
+    info!(?synced_height, "shielded context synced");
+    Ok(synced_height)
+}
+
+// A spending key is synced as is, anything else as a viewing key
+async fn sync_keys<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    key: &str,
+) -> Result<(Vec<DatedSpendingKey>, Vec<DatedKeypair<ViewingKey>>)>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if let Ok(spend_key) = ExtendedSpendingKey::from_str(key) {
+        let dated_key = DatedSpendingKey::try_from(spend_key).map_err(|e| Error::invalid("spending key", e))?;
+        return Ok((vec![dated_key], Vec::new()));
+    }
+
+    let viewing_key = wallet::resolve_viewing_key(sdk, key)
+        .await
+        .map_err(|_| Error::invalid("spending key, viewing key or alias", key))?;
+    let birthday = sdk.wallet().await.find_birthday(key).copied();
+    Ok((Vec::new(), vec![DatedKeypair::new(viewing_key.as_viewing_key(), birthday)]))
+}
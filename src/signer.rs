@@ -0,0 +1,611 @@
+//! Signers: whatever holds the secret keys and turns sig-hashes into signatures.
+//!
+//! Txs are not signed by handing the wallet to the SDK. [`sign_tx`] computes the
+//! hash each key must sign, asks a [`Signer`] for the signatures, verifies them
+//! and attaches them. [`WalletSigner`] signs with the wallet's own keys,
+//! [`RemoteSigner`] asks another process over a Unix socket or localhost HTTP so
+//! key custody can live apart from the wallet, and [`LocalSigner`] holds keys in
+//! memory, to stand in for such a process offline (see [`serve_unix`]).
+//!
+//! The remote protocol is one JSON [`SignerRequest`] per connection, answered by
+//! one [`SignerResponse`]. Over a Unix socket both end with a newline, over HTTP
+//! the request is POSTed to the endpoint URL.
+
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use async_trait::async_trait;
+use namada_core::address::Address;
+use namada_core::hash::Hash;
+use namada_sdk::account::AccountPublicKeysMap;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::signing::SigningTxData;
+use namada_sdk::tx::Tx;
+use namada_sdk::{
+    io::{Client, Io},
+    key::{common, PublicKeyHash, RefTo, SigScheme},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use namada_tx::{Authorization, Section, SignatureIndex, Signer as AuthorizationSigner};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use tracing::{debug, instrument};
+
+use crate::error::{Error, Result};
+
+/// What a sig-hash covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SigTarget {
+    /// The inner tx, signed by the keys of the account it acts for
+    RawHeader,
+    /// The wrapper and every section, signed by the fee payer
+    Wrapper,
+}
+
+/// A hash to sign with one key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SigHash {
+    #[serde(with = "as_string")]
+    pub public_key: common::PublicKey,
+    #[serde(with = "as_string")]
+    pub hash: Hash,
+    pub target: SigTarget,
+}
+
+/// A signature made by a [`Signer`].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeySignature {
+    #[serde(with = "as_string")]
+    pub public_key: common::PublicKey,
+    #[serde(with = "as_string")]
+    pub signature: common::Signature,
+}
+
+/// Holds secret keys and signs sig-hashes with them.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// The keys this signer can sign with.
+    async fn public_keys(&self) -> Result<Vec<common::PublicKey>>;
+
+    /// Sign each of `hashes` with its key, skipping the keys this signer does not
+    /// hold. `signing_data` tells for which account and fee payer, for signers
+    /// that apply their own policy.
+    async fn sign(&self, hashes: &[SigHash], signing_data: &SigningTxData) -> Result<Vec<KeySignature>>;
+
+    /// The keys among `keys` this signer holds, in the same order.
+    async fn held_keys(&self, keys: Vec<common::PublicKey>) -> Result<Vec<common::PublicKey>> {
+        let held = self.public_keys().await?;
+        Ok(keys.into_iter().filter(|key| held.contains(key)).collect())
+    }
+}
+
+/// Signs with the secret keys of the wallet, prompting for the password of
+/// encrypted ones.
+pub struct WalletSigner<'a, C, U, V, I>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    sdk: &'a NamadaImpl<C, U, V, I>,
+}
+
+impl<'a, C, U, V, I> WalletSigner<'a, C, U, V, I>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    pub fn new(sdk: &'a NamadaImpl<C, U, V, I>) -> Self {
+        WalletSigner { sdk }
+    }
+}
+
+#[async_trait]
+impl<C, U, V, I> Signer for WalletSigner<'_, C, U, V, I>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    async fn public_keys(&self) -> Result<Vec<common::PublicKey>> {
+        let wallet = self.sdk.wallet().await;
+        let public_keys = wallet.get_public_keys();
+        Ok(wallet
+            .get_secret_keys()
+            .into_values()
+            .filter_map(|(_, pkh)| pkh.cloned())
+            .filter_map(|pkh| public_keys.values().find(|key| PublicKeyHash::from(*key) == pkh).cloned())
+            .collect())
+    }
+
+    async fn sign(&self, hashes: &[SigHash], _signing_data: &SigningTxData) -> Result<Vec<KeySignature>> {
+        let held = self.public_keys().await?;
+        let mut signatures = Vec::with_capacity(hashes.len());
+        for hash in hashes.iter().filter(|hash| held.contains(&hash.public_key)) {
+            let secret_key = self
+                .sdk
+                .wallet_mut()
+                .await
+                .find_key_by_pk(&hash.public_key, None)
+                .map_err(|e| Error::Decryption(format!("Secret key of {}: {}", hash.public_key, e)))?;
+            signatures.push(KeySignature {
+                public_key: hash.public_key.clone(),
+                signature: common::SigScheme::sign(&secret_key, hash.hash),
+            });
+        }
+        Ok(signatures)
+    }
+}
+
+/// Signs with keys held in memory, e.g. to stand in for a remote signer in tests.
+pub struct LocalSigner {
+    keys: Vec<common::SecretKey>,
+}
+
+impl LocalSigner {
+    pub fn new(keys: Vec<common::SecretKey>) -> Self {
+        LocalSigner { keys }
+    }
+}
+
+#[async_trait]
+impl Signer for LocalSigner {
+    async fn public_keys(&self) -> Result<Vec<common::PublicKey>> {
+        Ok(self.keys.iter().map(RefTo::ref_to).collect())
+    }
+
+    async fn sign(&self, hashes: &[SigHash], _signing_data: &SigningTxData) -> Result<Vec<KeySignature>> {
+        Ok(hashes
+            .iter()
+            .filter_map(|hash| {
+                let secret_key = self.keys.iter().find(|key| key.ref_to() == hash.public_key)?;
+                Some(KeySignature {
+                    public_key: hash.public_key.clone(),
+                    signature: common::SigScheme::sign(secret_key, hash.hash),
+                })
+            })
+            .collect())
+    }
+}
+
+/// Where a [`RemoteSigner`] listens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignerEndpoint {
+    /// `unix:<path>`
+    Unix(PathBuf),
+    /// An `http://` URL on a loopback address
+    Http(String),
+}
+
+impl FromStr for SignerEndpoint {
+    type Err = Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        if let Some(path) = value.strip_prefix("unix:") {
+            return Ok(SignerEndpoint::Unix(PathBuf::from(path)));
+        }
+        let url = reqwest::Url::parse(value).map_err(|e| Error::Config(format!("Invalid signer {}: {}", value, e)))?;
+        if url.scheme() != "http" {
+            return Err(Error::Config(format!("Signer {} must be unix:<path> or an http:// URL", value)));
+        }
+        // Signatures are not sent over the network, the signer must be on this machine
+        let host = url.host_str().unwrap_or_default();
+        let loopback = host == "localhost"
+            || host
+                .trim_start_matches('[')
+                .trim_end_matches(']')
+                .parse::<IpAddr>()
+                .is_ok_and(|ip| ip.is_loopback());
+        if !loopback {
+            return Err(Error::Config(format!("Signer {} is not on localhost", value)));
+        }
+        Ok(SignerEndpoint::Http(value.to_string()))
+    }
+}
+
+impl Display for SignerEndpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SignerEndpoint::Unix(path) => write!(f, "unix:{}", path.display()),
+            SignerEndpoint::Http(url) => write!(f, "{}", url),
+        }
+    }
+}
+
+/// A signer in another process, keeping the keys out of the wallet.
+pub struct RemoteSigner {
+    endpoint: SignerEndpoint,
+}
+
+impl RemoteSigner {
+    pub fn new(endpoint: SignerEndpoint) -> Self {
+        RemoteSigner { endpoint }
+    }
+
+    async fn call(&self, request: &SignerRequest) -> Result<SignerResponse> {
+        let body = serde_json::to_vec(request).map_err(|e| Error::Signer(e.to_string()))?;
+        let reply = match &self.endpoint {
+            SignerEndpoint::Unix(path) => call_unix(path, body).await,
+            SignerEndpoint::Http(url) => call_http(url, body).await,
+        }
+        .map_err(|e| Error::Signer(format!("{}: {}", self.endpoint, e)))?;
+        match serde_json::from_slice(&reply) {
+            Ok(SignerResponse::Error { error }) => Err(Error::Signer(format!("{}: {}", self.endpoint, error))),
+            Ok(response) => Ok(response),
+            Err(e) => Err(Error::Signer(format!("{}: unreadable reply: {}", self.endpoint, e))),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn public_keys(&self) -> Result<Vec<common::PublicKey>> {
+        match self.call(&SignerRequest::PublicKeys).await? {
+            SignerResponse::PublicKeys { public_keys } => public_keys
+                .iter()
+                .map(|key| common::PublicKey::from_str(key).map_err(|e| Error::Signer(format!("Public key {}: {}", key, e))))
+                .collect(),
+            _ => Err(Error::Signer(format!("{}: unexpected reply to public-keys", self.endpoint))),
+        }
+    }
+
+    async fn sign(&self, hashes: &[SigHash], signing_data: &SigningTxData) -> Result<Vec<KeySignature>> {
+        let request = SignerRequest::Sign {
+            signing_data: SigningSummary::from(signing_data),
+            hashes: hashes.to_vec(),
+        };
+        match self.call(&request).await? {
+            SignerResponse::Signatures { signatures } => Ok(signatures),
+            _ => Err(Error::Signer(format!("{}: unexpected reply to sign", self.endpoint))),
+        }
+    }
+}
+
+/// A request to a remote signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "kebab-case")]
+pub enum SignerRequest {
+    PublicKeys,
+    Sign {
+        signing_data: SigningSummary,
+        hashes: Vec<SigHash>,
+    },
+}
+
+/// The reply of a remote signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "kebab-case")]
+pub enum SignerResponse {
+    PublicKeys { public_keys: Vec<String> },
+    Signatures { signatures: Vec<KeySignature> },
+    Error { error: String },
+}
+
+/// The [`SigningTxData`] sent to a remote signer.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SigningSummary {
+    /// Account the tx acts for
+    pub owner: Option<String>,
+    /// Keys asked to sign the inner tx
+    pub public_keys: Vec<String>,
+    pub threshold: u8,
+    pub fee_payer: String,
+}
+
+impl From<&SigningTxData> for SigningSummary {
+    fn from(signing_data: &SigningTxData) -> Self {
+        SigningSummary {
+            owner: signing_data.owner.as_ref().map(Address::to_string),
+            public_keys: signing_data.public_keys.iter().map(|key| key.to_string()).collect(),
+            threshold: signing_data.threshold,
+            fee_payer: signing_data.fee_payer.to_string(),
+        }
+    }
+}
+
+impl SigningSummary {
+    fn to_signing_data(&self) -> Result<SigningTxData> {
+        let public_key = |key: &str| {
+            common::PublicKey::from_str(key).map_err(|e| Error::invalid("signing data", format!("public key {}: {}", key, e)))
+        };
+        let owner = self
+            .owner
+            .as_deref()
+            .map(|owner| Address::from_str(owner).map_err(|e| Error::invalid("signing data", format!("owner {}: {}", owner, e))))
+            .transpose()?;
+        let public_keys = self.public_keys.iter().map(|key| public_key(key)).collect::<Result<Vec<_>>>()?;
+        Ok(SigningTxData {
+            owner,
+            account_public_keys_map: Some(AccountPublicKeysMap::from_iter(public_keys.clone())),
+            public_keys,
+            threshold: self.threshold,
+            fee_payer: public_key(&self.fee_payer)?,
+        })
+    }
+}
+
+/// Answer one request with `signer`, its errors included in the response.
+pub async fn handle_request(signer: &dyn Signer, request: SignerRequest) -> SignerResponse {
+    let response = match request {
+        SignerRequest::PublicKeys => signer.public_keys().await.map(|keys| SignerResponse::PublicKeys {
+            public_keys: keys.iter().map(|key| key.to_string()).collect(),
+        }),
+        SignerRequest::Sign { signing_data, hashes } => match signing_data.to_signing_data() {
+            Ok(signing_data) => signer
+                .sign(&hashes, &signing_data)
+                .await
+                .map(|signatures| SignerResponse::Signatures { signatures }),
+            Err(e) => Err(e),
+        },
+    };
+    response.unwrap_or_else(|e| SignerResponse::Error { error: e.to_string() })
+}
+
+/// Serve `signer` on a Unix socket until accepting a connection fails.
+#[cfg(unix)]
+pub async fn serve_unix(listener: tokio::net::UnixListener, signer: std::sync::Arc<dyn Signer>) -> Result<()> {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    loop {
+        let (stream, _) = listener.accept().await.map_err(|e| Error::Signer(format!("Accept failed: {}", e)))?;
+        let (read, mut write) = stream.into_split();
+        let mut line = String::new();
+        if BufReader::new(read).read_line(&mut line).await.is_err() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => handle_request(signer.as_ref(), request).await,
+            Err(e) => SignerResponse::Error { error: format!("Invalid request: {}", e) },
+        };
+        let mut reply = serde_json::to_vec(&response).unwrap_or_default();
+        reply.push(b'\n');
+        let _ = write.write_all(&reply).await;
+    }
+}
+
+#[cfg(unix)]
+async fn call_unix(path: &std::path::Path, mut body: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let mut stream = tokio::net::UnixStream::connect(path).await?;
+    body.push(b'\n');
+    stream.write_all(&body).await?;
+    let mut reply = Vec::new();
+    stream.read_to_end(&mut reply).await?;
+    Ok(reply)
+}
+
+#[cfg(not(unix))]
+async fn call_unix(_path: &std::path::Path, _body: Vec<u8>) -> std::io::Result<Vec<u8>> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "Unix sockets are not available on this platform"))
+}
+
+async fn call_http(url: &str, body: Vec<u8>) -> reqwest::Result<Vec<u8>> {
+    let response = reqwest::Client::new()
+        .post(url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .body(body)
+        .send()
+        .await?
+        .error_for_status()?;
+    Ok(response.bytes().await?.to_vec())
+}
+
+/// Sign `tx` as `signing_data` asks, through `signer`, in place of the SDK's `sign`.
+///
+/// `signatures` made beforehand by co-signers, for keys of `signing_data.owner`,
+/// are attached first and their keys are not asked for again. The remaining keys of `signing_data` then sign the
+/// inner tx, and the fee payer signs the wrapper over every section, those
+/// signatures included. Signatures are verified before they are attached.
+#[instrument(skip_all, fields(owner = ?signing_data.owner, keys = signing_data.public_keys.len(), presigned = signatures.len()))]
+pub async fn sign_tx(
+    signer: &dyn Signer,
+    tx: &mut Tx,
+    signing_data: &SigningTxData,
+    signatures: Vec<SignatureIndex>,
+) -> Result<()> {
+    for signature in &signatures {
+        let owner = match (&signature.index, &signing_data.owner) {
+            (Some((owner, index)), Some(account))
+                if owner == account && key_index(signing_data, &signature.pubkey) == Some(*index) =>
+            {
+                owner
+            }
+            _ => {
+                return Err(Error::invalid(
+                    "signature",
+                    format!("the signature by {} is not for one of the keys to sign with", signature.pubkey),
+                ))
+            }
+        };
+        verify_presigned(tx, owner, signature)?;
+    }
+
+    tx.protocol_filter();
+    let presigned: Vec<common::PublicKey> = signatures.iter().map(|signature| signature.pubkey.clone()).collect();
+    if !signatures.is_empty() {
+        tx.add_signatures(signatures);
+    }
+
+    let keys: Vec<common::PublicKey> = signing_data
+        .public_keys
+        .iter()
+        .filter(|key| !presigned.contains(key))
+        .cloned()
+        .collect();
+    if !keys.is_empty() {
+        let authorization = authorize_raw_header(signer, tx, signing_data, &keys).await?;
+        debug!(signatures = authorization.signatures.len(), "inner tx signed");
+        tx.add_section(Section::Authorization(authorization));
+    }
+
+    if tx.header.wrapper().is_some() {
+        let fee_payer = &signing_data.fee_payer;
+        let mut authorization = Authorization {
+            targets: tx.sechashes(),
+            signer: AuthorizationSigner::PubKeys(vec![fee_payer.clone()]),
+            signatures: BTreeMap::new(),
+        };
+        let hash = SigHash {
+            public_key: fee_payer.clone(),
+            hash: authorization.get_raw_hash(),
+            target: SigTarget::Wrapper,
+        };
+        let signature = request_signatures(signer, &[hash], signing_data)
+            .await?
+            .pop()
+            .ok_or_else(|| Error::Signer(format!("The signer holds no key of the fee payer {}", fee_payer)))?;
+        authorization.signatures.insert(0, signature.signature);
+        tx.add_section(Section::Authorization(authorization));
+    }
+    Ok(())
+}
+
+/// Signatures of the inner tx of `tx` by `keys` for the established account
+/// `signing_data.owner`, in the form co-signers exchange them.
+pub async fn sign_raw_header(
+    signer: &dyn Signer,
+    tx: &Tx,
+    signing_data: &SigningTxData,
+    keys: &[common::PublicKey],
+) -> Result<Vec<SignatureIndex>> {
+    let (Some(owner), Some(_)) = (&signing_data.owner, &signing_data.account_public_keys_map) else {
+        return Err(Error::invalid("signing data", "co-signed txs need the account and its key set"));
+    };
+    let authorization = authorize_raw_header(signer, tx, signing_data, keys).await?;
+    Ok(authorization
+        .signatures
+        .into_iter()
+        .filter_map(|(index, signature)| {
+            let pubkey = keys.iter().find(|key| key_index(signing_data, key) == Some(index))?.clone();
+            Some(SignatureIndex {
+                pubkey,
+                index: Some((owner.clone(), index)),
+                signature,
+            })
+        })
+        .collect())
+}
+
+// The section signing the inner tx for `signing_data.owner`, or for the keys
+// themselves without an owner, the same way `Authorization::new` builds it
+async fn authorize_raw_header(
+    signer: &dyn Signer,
+    tx: &Tx,
+    signing_data: &SigningTxData,
+    keys: &[common::PublicKey],
+) -> Result<Authorization> {
+    let mut indexed: Vec<(u8, &common::PublicKey)> = Vec::with_capacity(keys.len());
+    for (position, key) in keys.iter().enumerate() {
+        let index = match &signing_data.account_public_keys_map {
+            Some(_) => key_index(signing_data, key),
+            None => u8::try_from(position).ok(),
+        }
+        .ok_or_else(|| Error::Signer(format!("{} is not one of the keys to sign with", key)))?;
+        indexed.push((index, key));
+    }
+    indexed.sort_by_key(|(index, _)| *index);
+
+    let owner_signs = signing_data.owner.is_some();
+    let mut authorization = Authorization {
+        targets: vec![tx.raw_header_hash()],
+        signer: match &signing_data.owner {
+            Some(owner) => AuthorizationSigner::Address(owner.clone()),
+            None => AuthorizationSigner::PubKeys(indexed.iter().map(|(_, key)| (*key).clone()).collect()),
+        },
+        signatures: BTreeMap::new(),
+    };
+    let hash = authorization.get_raw_hash();
+    let hashes: Vec<SigHash> = indexed
+        .iter()
+        .map(|(_, key)| SigHash {
+            public_key: (*key).clone(),
+            hash,
+            target: SigTarget::RawHeader,
+        })
+        .collect();
+
+    let signatures = request_signatures(signer, &hashes, signing_data).await?;
+    // Keys listed in the section must all sign, an account needs only its threshold
+    if signatures.is_empty() || (!owner_signs && signatures.len() < hashes.len()) {
+        return Err(Error::Signer(format!(
+            "The signer holds {} of the {} key(s) asked to sign",
+            signatures.len(),
+            hashes.len()
+        )));
+    }
+    for signature in signatures {
+        if let Some((index, _)) = indexed.iter().find(|(_, key)| **key == signature.public_key) {
+            authorization.signatures.insert(*index, signature.signature);
+        }
+    }
+    Ok(authorization)
+}
+
+/// Check a co-signer's signature of the inner tx of `tx` for the account `owner`,
+/// over the section the SDK builds for it out of such signatures.
+pub(crate) fn verify_presigned(tx: &Tx, owner: &Address, signature: &SignatureIndex) -> Result<()> {
+    let authorization = Authorization {
+        targets: vec![tx.raw_header_hash()],
+        signer: AuthorizationSigner::Address(owner.clone()),
+        signatures: BTreeMap::new(),
+    };
+    common::SigScheme::verify_signature(&signature.pubkey, &authorization.get_raw_hash(), &signature.signature)
+        .map_err(|e| Error::invalid("signature", format!("the signature by {} does not verify: {}", signature.pubkey, e)))
+}
+
+fn key_index(signing_data: &SigningTxData, key: &common::PublicKey) -> Option<u8> {
+    signing_data
+        .account_public_keys_map
+        .as_ref()
+        .and_then(|map| map.get_index_from_public_key(key))
+}
+
+// Ask `signer` for `hashes` and keep one verified signature per requested key
+async fn request_signatures(
+    signer: &dyn Signer,
+    hashes: &[SigHash],
+    signing_data: &SigningTxData,
+) -> Result<Vec<KeySignature>> {
+    let mut verified: Vec<KeySignature> = Vec::with_capacity(hashes.len());
+    for signature in signer.sign(hashes, signing_data).await? {
+        let hash = hashes
+            .iter()
+            .find(|hash| hash.public_key == signature.public_key)
+            .ok_or_else(|| Error::Signer(format!("Signature by {}, which was not asked for", signature.public_key)))?;
+        common::SigScheme::verify_signature(&hash.public_key, &hash.hash, &signature.signature)
+            .map_err(|e| Error::Signer(format!("Signature by {} does not verify: {}", signature.public_key, e)))?;
+        if verified.iter().all(|other| other.public_key != signature.public_key) {
+            verified.push(signature);
+        }
+    }
+    Ok(verified)
+}
+
+// Keys, hashes and signatures travel in their usual string forms
+mod as_string {
+    use super::*;
+
+    pub fn serialize<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
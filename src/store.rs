@@ -0,0 +1,160 @@
+//! Wallet entries taken out of namada's `Store` and put back under an alias, for
+//! the operations `Wallet` has no single call for, such as renaming an entry or
+//! deleting one kind of entry of an alias.
+//!
+//! Keys are moved as they are stored, encrypted ones without their password.
+
+use namada_core::address::Address;
+use namada_sdk::{
+    chain::BlockHeight,
+    key::{common, PublicKeyHash},
+    wallet::{alias::Alias, DerivationPath, Store, StoredKeypair, WalletIo},
+    ExtendedSpendingKey, ExtendedViewingKey, PaymentAddress,
+};
+
+use crate::error::{Error, Result};
+use crate::inventory::EntryKind;
+
+pub(crate) const WALLET_FILE: &str = "wallet.toml";
+
+/// What is stored under an alias for one kind of entry, with its metadata.
+#[derive(Clone)]
+pub(crate) enum StoredEntry {
+    Address(Address),
+    /// A public key, with its secret key unless the wallet only watches it
+    PublicKey {
+        public_key: common::PublicKey,
+        secret_key: Option<StoredKeypair<common::SecretKey>>,
+        path: Option<DerivationPath>,
+    },
+    /// A spending key, which `Store` keeps together with its viewing key. Its HD
+    /// path is not kept, `Store` has no lookup for it
+    SpendingKey {
+        spending_key: StoredKeypair<ExtendedSpendingKey>,
+        viewing_key: ExtendedViewingKey,
+        birthday: Option<BlockHeight>,
+    },
+    /// A viewing key stored without its spending key
+    ViewingKey {
+        viewing_key: ExtendedViewingKey,
+        birthday: Option<BlockHeight>,
+    },
+    PaymentAddress(PaymentAddress),
+}
+
+impl StoredEntry {
+    /// The kinds of entry this one stands for.
+    pub(crate) fn kinds(&self) -> &'static [EntryKind] {
+        match self {
+            StoredEntry::Address(_) => &[EntryKind::Address],
+            StoredEntry::PublicKey { .. } => &[EntryKind::PublicKey],
+            StoredEntry::SpendingKey { .. } => &[EntryKind::SpendingKey, EntryKind::ViewingKey],
+            StoredEntry::ViewingKey { .. } => &[EntryKind::ViewingKey],
+            StoredEntry::PaymentAddress(_) => &[EntryKind::PaymentAddress],
+        }
+    }
+
+    /// Whether the entry stands for one of `kinds`, or for any kind when empty.
+    pub(crate) fn is_any_of(&self, kinds: &[EntryKind]) -> bool {
+        kinds.is_empty() || self.kinds().iter().any(|kind| kinds.contains(kind))
+    }
+
+    /// Whether `other` stores the same value, metadata aside.
+    pub(crate) fn same_as(&self, other: &StoredEntry) -> bool {
+        match (self, other) {
+            (StoredEntry::Address(a), StoredEntry::Address(b)) => a == b,
+            (
+                StoredEntry::PublicKey { public_key: a, secret_key: a_secret, .. },
+                StoredEntry::PublicKey { public_key: b, secret_key: b_secret, .. },
+            ) => a == b && a_secret.is_some() == b_secret.is_some(),
+            (
+                StoredEntry::SpendingKey { spending_key: a, .. },
+                StoredEntry::SpendingKey { spending_key: b, .. },
+            ) => a.to_string() == b.to_string(),
+            (StoredEntry::ViewingKey { viewing_key: a, .. }, StoredEntry::ViewingKey { viewing_key: b, .. }) => a == b,
+            (StoredEntry::PaymentAddress(a), StoredEntry::PaymentAddress(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+/// The entries stored under `alias`, keys first.
+pub(crate) fn entries_of(store: &Store, alias: &str) -> Vec<StoredEntry> {
+    let mut entries = Vec::new();
+    if let Some(public_key) = store.find_public_key(alias) {
+        entries.push(StoredEntry::PublicKey {
+            public_key: public_key.clone(),
+            secret_key: store.find_secret_key(alias).cloned(),
+            path: store.find_path_by_pkh(&PublicKeyHash::from(public_key)),
+        });
+    }
+    let birthday = store.get_birthday(alias).copied();
+    match (store.find_spending_key(alias), store.find_viewing_key(alias)) {
+        (Some(spending_key), Some(viewing_key)) => entries.push(StoredEntry::SpendingKey {
+            spending_key: spending_key.clone(),
+            viewing_key: *viewing_key,
+            birthday,
+        }),
+        (None, Some(viewing_key)) => entries.push(StoredEntry::ViewingKey { viewing_key: *viewing_key, birthday }),
+        _ => {}
+    }
+    if let Some(payment_address) = store.find_payment_addr(alias) {
+        entries.push(StoredEntry::PaymentAddress(*payment_address));
+    }
+    if let Some(address) = store.find_address(alias) {
+        entries.push(StoredEntry::Address(address.clone()));
+    }
+    entries
+}
+
+/// Every alias of `store` with the kind of entry stored under it, sorted by kind
+/// then alias.
+pub(crate) fn aliased_entries(store: &Store) -> Vec<(EntryKind, String)> {
+    let mut entries: Vec<(EntryKind, String)> = Vec::new();
+    let mut add = |kind, aliases: Vec<String>| entries.extend(aliases.into_iter().map(|alias| (kind, alias)));
+    add(EntryKind::Address, store.get_addresses().iter().map(|(alias, _)| alias.to_string()).collect());
+    add(EntryKind::PublicKey, store.get_public_keys().iter().map(|(alias, _)| alias.to_string()).collect());
+    add(EntryKind::SpendingKey, store.get_spending_keys().iter().map(|(alias, _)| alias.to_string()).collect());
+    add(EntryKind::ViewingKey, store.get_viewing_keys().iter().map(|(alias, _)| alias.to_string()).collect());
+    add(EntryKind::PaymentAddress, store.get_payment_addrs().iter().map(|(alias, _)| alias.to_string()).collect());
+    entries.sort();
+    entries.dedup();
+    entries
+}
+
+/// Replace everything stored under `alias` with `entries`.
+pub(crate) fn replace_entries<U: WalletIo>(store: &mut Store, alias: &str, mut entries: Vec<StoredEntry>) -> Result<()> {
+    store.remove_alias(&Alias::from(alias));
+    // Keys go first, the other entries of the alias are stored next to them
+    entries.sort_by_key(|entry| !matches!(entry, StoredEntry::PublicKey { .. } | StoredEntry::SpendingKey { .. }));
+    for entry in entries {
+        insert_entry::<U>(store, alias, entry)?;
+    }
+    Ok(())
+}
+
+fn insert_entry<U: WalletIo>(store: &mut Store, alias: &str, entry: StoredEntry) -> Result<()> {
+    let kind = entry.kinds()[0];
+    let alias = Alias::from(alias);
+    let inserted = match entry {
+        StoredEntry::Address(address) => store.insert_address::<U>(alias.clone(), address, true),
+        StoredEntry::PublicKey { public_key, secret_key: Some(secret_key), path } => store
+            .insert_keypair::<U>(alias.clone(), secret_key, PublicKeyHash::from(&public_key), path, true)
+            .and_then(|alias| store.insert_public_key::<U>(alias, public_key, None, None, true)),
+        StoredEntry::PublicKey { public_key, secret_key: None, path } => {
+            store.insert_public_key::<U>(alias.clone(), public_key, None, path, true)
+        }
+        StoredEntry::SpendingKey { spending_key, viewing_key, birthday } => {
+            store.insert_spending_key::<U>(alias.clone(), spending_key, viewing_key, birthday, None, true)
+        }
+        StoredEntry::ViewingKey { viewing_key, birthday } => {
+            store.insert_viewing_key::<U>(alias.clone(), viewing_key, birthday, true)
+        }
+        StoredEntry::PaymentAddress(payment_address) => {
+            store.insert_payment_addr::<U>(alias.clone(), payment_address, true)
+        }
+    };
+    inserted
+        .map(drop)
+        .ok_or_else(|| Error::WalletIo(format!("The {:?} entry could not be stored under {}", kind, alias)))
+}
@@ -0,0 +1,312 @@
+use std::str::FromStr;
+
+use namada_core::address::Address;
+use namada_ibc::core::host::types::identifiers::ChannelId;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::signing::SigningTxData;
+use namada_sdk::tx::{ProcessTxResponse, Tx};
+use namada_tx::SignatureIndex;
+use namada_sdk::{
+    args::{self, InputAmount, TxBuilder, TxTransparentTransferData},
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    MaybeSend, MaybeSync, Namada, NamadaImpl, PaymentAddress, TransferSource,
+};
+use tracing::field::{display, Empty};
+use tracing::{info, info_span, instrument, Instrument, Span};
+
+use crate::error::{Error, Result};
+use crate::node::verify_node;
+use crate::query::findifreveal;
+use crate::signer::{sign_tx, Signer};
+use crate::wallet::{find_address, signing_keys_of};
+
+/// Responses for a submitted transaction, including any reveal-pk txs sent before it.
+#[derive(Debug)]
+pub struct TxSubmission {
+    pub reveal: Vec<ProcessTxResponse>,
+    pub response: ProcessTxResponse,
+}
+
+/// Reveal the public keys of `owner` that `signer` holds on chain, Ed25519 or
+/// Secp256k1, if it has not revealed one yet.
+///
+/// Returns the responses of the reveal txs, empty if the account was already revealed.
+#[instrument(skip_all, fields(owner = %owner))]
+pub async fn reveal_pk_if_needed<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    owner: &Address,
+) -> Result<Vec<ProcessTxResponse>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let mut responses = Vec::new();
+    if findifreveal(sdk, owner).await? {
+        return Ok(responses);
+    }
+
+    let keys = signing_keys_of(sdk, signer, owner).await.map_err(|e| match e {
+        Error::WatchOnly(owner) => Error::Unrevealed(owner),
+        e => e,
+    })?;
+
+    for public_key in keys {
+        let reveal_tx_builder = sdk
+            .new_reveal_pk(public_key.clone())
+            .signing_keys(vec![public_key.clone()]);
+
+        let (reveal_tx, signing_data) = reveal_tx_builder
+            .build(sdk)
+            .instrument(info_span!("build", tx = "reveal pk tx"))
+            .await
+            .map_err(|source| Error::TxBuild { tx: "reveal pk tx", source })?;
+
+        let response =
+            sign_and_submit(sdk, signer, reveal_tx, &reveal_tx_builder.tx, signing_data, "reveal pk tx").await?;
+        responses.push(response);
+    }
+
+    Ok(responses)
+}
+
+/// Check the node, then sign `tx` through `signer` and submit it.
+///
+/// Signatures made beforehand, in `args.signatures`, are attached as they are.
+/// Nothing is signed if the node is on another chain or not caught up.
+#[instrument(skip_all, fields(tx = what, tx_hash = Empty))]
+pub(crate) async fn sign_and_submit<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    mut tx: Tx,
+    args: &args::Tx,
+    signing_data: SigningTxData,
+    what: &'static str,
+) -> Result<ProcessTxResponse>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    verify_node(sdk).instrument(info_span!("verify_node")).await?;
+
+    let signatures = args
+        .signatures
+        .iter()
+        .map(|signature| serde_json::from_slice(signature))
+        .collect::<Result<Vec<SignatureIndex>, _>>()
+        .map_err(|e| Error::invalid("signature", e))?;
+    sign_tx(signer, &mut tx, &signing_data, signatures)
+        .instrument(info_span!("sign"))
+        .await?;
+
+    let response = sdk
+        .submit(tx, args)
+        .instrument(info_span!("submit"))
+        .await
+        .map_err(|source| Error::Submission { tx: what, source })?;
+
+    if let Some(hash) = tx_hash(&response) {
+        Span::current().record("tx_hash", hash.as_str());
+        info!(tx = what, tx_hash = %hash, "tx submitted");
+    }
+    Ok(response)
+}
+
+/// Hash of a submitted tx, `None` for a dry run.
+pub fn tx_hash(response: &ProcessTxResponse) -> Option<String> {
+    match response {
+        ProcessTxResponse::Applied(applied) => Some(applied.hash.clone()),
+        ProcessTxResponse::Broadcast(broadcast) => Some(broadcast.hash.to_string()),
+        ProcessTxResponse::DryRun(_) => None,
+    }
+}
+
+/// Shield `amount` of `token` from the transparent account `alias` to a payment address.
+///
+/// Watch-only sources are refused before anything is built.
+#[instrument(skip_all, fields(alias = alias, source = Empty, target = %target, token = %token, amount = amount, masp_epoch = Empty))]
+pub async fn send_token_shielded<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    alias: &str,
+    target: &PaymentAddress,
+    amount: &str,
+    token: Address,
+) -> Result<TxSubmission>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let source_address = find_address(sdk, alias)
+        .await
+        .ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+    Span::current().record("source", display(&source_address));
+    let signing_keys = signing_keys_of(sdk, signer, &source_address).await?;
+    let amount = InputAmount::from_str(amount).map_err(|e| Error::invalid("amount", format!("{}: {}", amount, e)))?;
+
+    let reveal = reveal_pk_if_needed(sdk, signer, &source_address).await?;
+
+    let shielding_data = vec![args::TxShieldingTransferData {
+        source: source_address,
+        amount,
+        token,
+    }];
+
+    let mut shielding_transfer = sdk
+        .new_shielding_transfer(target.clone(), shielding_data)
+        .signing_keys(signing_keys);
+
+    let (transfer_tx, signing_data, masp_epoch) = shielding_transfer
+        .build(sdk)
+        .instrument(info_span!("build", tx = "shielding transfer"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "shielding transfer", source })?;
+    if let Some(masp_epoch) = masp_epoch {
+        Span::current().record("masp_epoch", display(masp_epoch));
+    }
+
+    let response =
+        sign_and_submit(sdk, signer, transfer_tx, &shielding_transfer.tx, signing_data, "shielding transfer").await?;
+
+    Ok(TxSubmission { reveal, response })
+}
+
+/// Transfer `amount` of `token` from the transparent account `alias` to `target`.
+///
+/// Signed only with the keys that control the source, watch-only sources are refused.
+#[instrument(skip_all, fields(alias = alias, source = Empty, target = %target, token = %token, amount = amount))]
+pub async fn send_transparent_token<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    alias: &str,
+    target: Address,
+    amount: &str,
+    token: Address,
+) -> Result<TxSubmission>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let source_address = find_address(sdk, alias)
+        .await
+        .ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+    Span::current().record("source", display(&source_address));
+    let signing_keys = signing_keys_of(sdk, signer, &source_address).await?;
+    let amount = InputAmount::from_str(amount).map_err(|e| Error::invalid("amount", format!("{}: {}", amount, e)))?;
+
+    let reveal = reveal_pk_if_needed(sdk, signer, &source_address).await?;
+
+    let data = TxTransparentTransferData {
+        source: source_address,
+        target,
+        token,
+        amount,
+    };
+
+    let mut transfer_tx_builder = sdk
+        .new_transparent_transfer(vec![data])
+        .signing_keys(signing_keys);
+
+    let (transfer_tx, signing_data) = transfer_tx_builder
+        .build(sdk)
+        .instrument(info_span!("build", tx = "transparent transfer"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "transparent transfer", source })?;
+
+    let response =
+        sign_and_submit(sdk, signer, transfer_tx, &transfer_tx_builder.tx, signing_data, "transparent transfer").await?;
+
+    Ok(TxSubmission { reveal, response })
+}
+
+/// Send `amount` of `token` from the transparent account `alias` over IBC.
+///
+/// Watch-only sources are refused before anything is built.
+#[instrument(skip_all, fields(alias = alias, source = Empty, target = receiver, channel_id = channel_id, token = %token, amount = amount))]
+pub async fn send_ibc_token<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    alias: &str,
+    receiver: &str,
+    channel_id: &str,
+    amount: &str,
+    token: Address,
+) -> Result<TxSubmission>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let source_address = find_address(sdk, alias)
+        .await
+        .ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+    Span::current().record("source", display(&source_address));
+    let signing_keys = signing_keys_of(sdk, signer, &source_address).await?;
+    let channel_id = ChannelId::from_str(channel_id)
+        .map_err(|e| Error::invalid("channel id", format!("{}: {}", channel_id, e)))?;
+    let amount = InputAmount::from_str(amount).map_err(|e| Error::invalid("amount", format!("{}: {}", amount, e)))?;
+
+    let reveal = reveal_pk_if_needed(sdk, signer, &source_address).await?;
+
+    let source_transfer = TransferSource::Address(source_address);
+
+    let ibc_transfer_tx_builder = sdk.new_ibc_transfer(
+        source_transfer,
+        receiver.to_string(),
+        token,
+        amount,
+        channel_id,
+        false,
+    )
+    .signing_keys(signing_keys);
+
+    let (ibc_transfer_tx, signing_data, _epoch_option) = ibc_transfer_tx_builder
+        .build(sdk)
+        .instrument(info_span!("build", tx = "IBC transfer"))
+        .await
+        .map_err(|source| Error::TxBuild { tx: "IBC transfer", source })?;
+
+    let response =
+        sign_and_submit(sdk, signer, ibc_transfer_tx, &ibc_transfer_tx_builder.tx, signing_data, "IBC transfer").await?;
+
+    Ok(TxSubmission { reveal, response })
+}
+
+/// Human readable summary of an IBC transfer, used as the transfer memo.
+pub async fn generate_ibc_memo<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    receiver: &str,
+    channel_id: &str,
+    amount: &str,
+) -> Result<String>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let source_address = find_address(sdk, alias)
+        .await
+        .ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+    let amount = InputAmount::from_str(amount).map_err(|e| Error::invalid("amount", format!("{}: {}", amount, e)))?;
+
+    Ok(format!(
+        "Transfer of {:?} {} from {} to {} via port transfer and channel {}",
+        amount,
+        sdk.native_token(),
+        source_address,
+        receiver,
+        channel_id
+    ))
+}
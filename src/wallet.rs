@@ -0,0 +1,515 @@
+use std::str::FromStr;
+
+use masp_primitives::zip32;
+use namada_core::address::Address;
+use namada_sdk::masp::{find_valid_diversifier, ShieldedUtils};
+use namada_sdk::{
+    bip39::{Language, Mnemonic, Seed},
+    chain::BlockHeight,
+    io::{Client, Io},
+    key::{common, PublicKeyHash, RefTo, SchemeType},
+    wallet::{derive_hd_secret_key, DerivationPath, WalletIo, WalletStorage},
+    zeroize::Zeroizing,
+    ExtendedSpendingKey, ExtendedViewingKey, MaybeSend, MaybeSync, Namada, NamadaImpl, PaymentAddress,
+};
+use rand_core::OsRng;
+use tracing::{info, instrument};
+
+use crate::error::{Error, Result};
+use crate::mnemonic::VerifiedMnemonic;
+use crate::query;
+use crate::signer::Signer;
+
+/// SLIP-44 coin type of Namada, used for Ed25519 keys.
+const NAMADA_COIN_TYPE: u32 = 877;
+/// SLIP-44 coin type of Ethereum, used for Secp256k1 keys.
+const ETH_COIN_TYPE: u32 = 60;
+
+/// How a key is derived from its mnemonic and stored.
+#[derive(Clone, Debug)]
+pub struct KeyOptions {
+    /// Key scheme, Ed25519 by default or Secp256k1 for Ethereum-style keys
+    pub scheme: SchemeType,
+    /// BIP39 passphrase mixed into the seed, empty for none
+    pub passphrase: Zeroizing<String>,
+    /// Password the key is encrypted with in the wallet file, `None` to store it in clear
+    pub password: Option<Zeroizing<String>>,
+    /// Index of the account to derive, the last component of `m/44'/877'/0'/0'/N'`
+    /// (`m/44'/60'/0'/0/N` for Secp256k1)
+    pub account: u32,
+    /// Full derivation path, overriding `account`
+    pub derivation_path: Option<String>,
+    /// Overwrite the entries already stored under the alias
+    pub force: bool,
+}
+
+impl Default for KeyOptions {
+    fn default() -> Self {
+        KeyOptions {
+            scheme: SchemeType::Ed25519,
+            passphrase: Zeroizing::default(),
+            password: None,
+            account: 0,
+            derivation_path: None,
+            force: false,
+        }
+    }
+}
+
+/// Derivation path of the transparent key selected by `options`.
+pub fn transparent_derivation_path(options: &KeyOptions) -> Result<DerivationPath> {
+    let path = match (&options.derivation_path, options.scheme) {
+        (Some(path), _) => path.clone(),
+        (None, SchemeType::Secp256k1) => format!("m/44'/{}'/0'/0/{}", ETH_COIN_TYPE, options.account),
+        (None, _) => format!("m/44'/{}'/0'/0'/{}'", NAMADA_COIN_TYPE, options.account),
+    };
+    let derivation_path = DerivationPath::from_path_string_for_transparent_scheme(options.scheme, &path)
+        .map_err(|e| Error::invalid("derivation path", format!("{}: {}", path, e)))?;
+    if !derivation_path.is_compatible(options.scheme) {
+        return Err(Error::invalid(
+            "derivation path",
+            format!("{}: not usable for {:?} keys (Ed25519 needs every component hardened)", path, options.scheme),
+        ));
+    }
+    Ok(derivation_path)
+}
+
+/// Address of the transparent key `options` selects from `phrase`, without storing anything.
+pub fn derive_address(phrase: &str, options: &KeyOptions) -> Result<Address> {
+    let mnemonic = parse_mnemonic(phrase)?;
+    let seed = Seed::new(&mnemonic, &options.passphrase);
+    let secret_key = derive_hd_secret_key(options.scheme, seed.as_bytes(), transparent_derivation_path(options)?);
+    Ok(Address::from(&secret_key.ref_to()))
+}
+
+/// Store the key `options` selects from a newly generated mnemonic under `alias`.
+///
+/// Fails if the alias is already taken by an address or a key, unless `options.force` is set.
+/// Only mnemonics whose backup was checked with [`PendingMnemonic::verify`] are accepted.
+/// The phrase itself is not kept in the wallet, see [`MnemonicVault`] to keep it encrypted.
+///
+/// [`PendingMnemonic::verify`]: crate::mnemonic::PendingMnemonic::verify
+/// [`MnemonicVault`]: crate::mnemonic::MnemonicVault
+pub async fn create_wallet<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: String,
+    mnemonic: &VerifiedMnemonic,
+    options: &KeyOptions,
+) -> Result<String>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    store_transparent_key(sdk, alias, mnemonic.mnemonic().clone(), options).await
+}
+
+/// Derive the key `options` selects from an existing mnemonic and store it under `alias`.
+///
+/// Fails if the alias is already taken by an address or a key, unless `options.force` is set.
+pub async fn add_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: String,
+    phrase: &str,
+    options: &KeyOptions,
+) -> Result<String>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let mnemonic = parse_mnemonic(phrase)?;
+    store_transparent_key(sdk, alias, mnemonic, options).await
+}
+
+async fn store_transparent_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: String,
+    mnemonic: Mnemonic,
+    options: &KeyOptions,
+) -> Result<String>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_transparent_alias(sdk, &alias, options.force).await?;
+    let derivation_path = transparent_derivation_path(options)?;
+    let (key_alias, _sk) = sdk.wallet_mut().await
+        .derive_store_key_from_mnemonic_code(
+            options.scheme,
+            Some(alias),
+            true,
+            derivation_path,
+            Some((mnemonic, options.passphrase.clone())),
+            true,
+            options.password.clone(),
+        )
+        .ok_or_else(|| Error::WalletIo("Unable to derive key from mnemonic code".to_string()))?;
+
+    save_wallet(sdk).await?;
+    Ok(key_alias)
+}
+
+/// Fail with [`Error::AliasTaken`] if an address or a key is stored under `alias`,
+/// unless `force` is set.
+pub async fn check_transparent_alias<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, alias: &str, force: bool) -> Result<()>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let wallet = sdk.wallet().await;
+    if !force && (wallet.find_address(alias).is_some() || wallet.find_public_key(alias).is_ok()) {
+        return Err(Error::AliasTaken(alias.to_string()));
+    }
+    Ok(())
+}
+
+pub(crate) fn check_alias(alias: &str) -> Result<()> {
+    if alias.trim().is_empty() {
+        return Err(Error::invalid("alias", "the alias is empty"));
+    }
+    Ok(())
+}
+
+fn parse_mnemonic(phrase: &str) -> Result<Mnemonic> {
+    Mnemonic::from_phrase(phrase, Language::English).map_err(|e| Error::InvalidMnemonic(e.to_string()))
+}
+
+/// Save the in-memory wallet to its storage.
+pub async fn save_wallet<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>) -> Result<()>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    sdk.wallet()
+        .await
+        .save()
+        .map_err(|e| Error::WalletIo(format!("Could not save wallet: {}", e)))
+}
+
+/// Look up the address stored under `alias`.
+pub async fn find_address<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, alias: &str) -> Option<Address>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    sdk.wallet().await.find_address(alias).map(|address| address.into_owned())
+}
+
+/// Derive the default shielded spending key from a mnemonic and store it under `alias`.
+///
+/// Fails if the alias is already taken by a shielded key, unless `options.force` is set.
+pub async fn create_spending_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: String,
+    phrase: &str,
+    options: &KeyOptions,
+) -> Result<String>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let mnemonic = parse_mnemonic(phrase)?;
+    if !options.force && sdk.wallet().await.find_viewing_key(&alias).is_ok() {
+        return Err(Error::AliasTaken(alias));
+    }
+
+    let spending_derivation_path = DerivationPath::default_for_shielded();
+    let (spending_key_alias, _sk_spending) = sdk.wallet_mut().await
+        .derive_store_spending_key_from_mnemonic_code(
+            alias,
+            true,
+            None,
+            spending_derivation_path,
+            Some((mnemonic, options.passphrase.clone())),
+            true,
+            options.password.clone(),
+        )
+        .ok_or_else(|| Error::WalletIo("Unable to derive spending key from mnemonic".to_string()))?;
+
+    save_wallet(sdk).await?;
+    Ok(spending_key_alias)
+}
+
+/// Parse a transparent secret key, as exported by `namadaw`: hex of the scheme byte
+/// (`00` Ed25519, `01` Secp256k1) followed by the key. The bare 32 bytes of a key
+/// are read as a `scheme` key.
+///
+/// Namada has no bech32 form for transparent secret keys, only hex is accepted.
+pub fn parse_secret_key(value: &str, scheme: SchemeType) -> Result<common::SecretKey> {
+    let value = value.trim().trim_start_matches("0x").to_lowercase();
+    let prefixed = match (value.len(), scheme) {
+        (64, SchemeType::Secp256k1) => format!("01{}", value),
+        (64, _) => format!("00{}", value),
+        _ => value,
+    };
+    common::SecretKey::from_str(&prefixed).map_err(|e| Error::invalid("secret key", e))
+}
+
+/// Store a raw transparent secret key (see [`parse_secret_key`]) under `alias`, with
+/// its public key and implicit address, encrypted with `password` if given.
+///
+/// Fails if the alias is already taken by an address or a key, unless `force` is set.
+#[instrument(skip_all, fields(alias = alias))]
+pub async fn import_secret_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    secret_key: &str,
+    scheme: SchemeType,
+    password: Option<Zeroizing<String>>,
+    force: bool,
+) -> Result<Address>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_alias(alias)?;
+    let secret_key = parse_secret_key(secret_key, scheme)?;
+    let address = Address::from(&secret_key.ref_to());
+
+    {
+        let mut wallet = sdk.wallet_mut().await;
+        if !force && (wallet.find_address(alias).is_some() || wallet.find_public_key(alias).is_ok()) {
+            return Err(Error::AliasTaken(alias.to_string()));
+        }
+        wallet
+            .insert_keypair(alias.to_string(), true, secret_key, password, Some(address.clone()), None)
+            .ok_or_else(|| Error::WalletIo(format!("Key {} could not be inserted", alias)))?;
+    }
+    save_wallet(sdk).await?;
+
+    info!(%address, "imported secret key");
+    Ok(address)
+}
+
+/// Store a bech32 extended spending key (`zsknam1...`) under `alias`, with its
+/// viewing key and the height shielded syncs of it start at (`birthday`), if known.
+///
+/// Fails if the alias is already taken by a shielded key, unless `force` is set.
+/// Returns the viewing key.
+#[instrument(skip_all, fields(alias = alias, birthday = birthday))]
+pub async fn import_spending_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    spending_key: &str,
+    birthday: Option<u64>,
+    password: Option<Zeroizing<String>>,
+    force: bool,
+) -> Result<ExtendedViewingKey>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_alias(alias)?;
+    let spend_key = ExtendedSpendingKey::from_str(spending_key.trim())
+        .map_err(|e| Error::invalid("spending key", e))?;
+    let masp_key: zip32::ExtendedSpendingKey = spend_key.into();
+    let viewing_key = ExtendedViewingKey::from(zip32::ExtendedFullViewingKey::from(&masp_key));
+
+    {
+        let mut wallet = sdk.wallet_mut().await;
+        if !force && wallet.find_viewing_key(alias).is_ok() {
+            return Err(Error::AliasTaken(alias.to_string()));
+        }
+        wallet
+            .insert_spending_key(alias.to_string(), true, spend_key, password, birthday.map(BlockHeight), None)
+            .ok_or_else(|| Error::WalletIo(format!("Spending key {} could not be inserted", alias)))?;
+    }
+    save_wallet(sdk).await?;
+
+    info!("imported spending key");
+    Ok(viewing_key)
+}
+
+/// Generate a fresh payment address from a viewing key (or its alias) and store it under `alias`.
+///
+/// Fails if the alias is already taken, unless `alias_force` is set.
+pub async fn generate_payment_address<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    viewing_key: &str,
+    alias_force: bool,
+) -> Result<PaymentAddress>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let viewing_key = resolve_viewing_key(sdk, viewing_key).await?;
+
+    if !alias_force && sdk.wallet().await.find_payment_addr(alias).is_some() {
+        return Err(Error::AliasTaken(alias.to_string()));
+    }
+
+    // Generate the shielded payment address
+    let (div, _g_d) = find_valid_diversifier(&mut OsRng);
+    let masp_payment_addr = viewing_key
+        .as_viewing_key()
+        .to_payment_address(div)
+        .ok_or_else(|| Error::Shielded("Unable to generate a PaymentAddress".to_string()))?;
+    let payment_addr = PaymentAddress::from(masp_payment_addr);
+
+    sdk.wallet_mut().await
+        .insert_payment_addr(alias.to_string(), payment_addr.clone(), alias_force)
+        .ok_or_else(|| Error::WalletIo("Payment address could not be inserted".to_string()))?;
+    save_wallet(sdk).await?;
+
+    Ok(payment_addr)
+}
+
+/// Look up an alias in the wallet, falling back to parsing a raw address.
+pub async fn resolve_address<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, value: &str) -> Result<Address>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if let Some(address) = find_address(sdk, value).await {
+        return Ok(address);
+    }
+    Address::from_str(value).map_err(|e| Error::invalid("address or unknown alias", format!("{}: {}", value, e)))
+}
+
+/// Resolve a token alias or address, defaulting to the native token.
+pub async fn resolve_token<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, token: Option<&str>) -> Result<Address>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    match token {
+        Some(token) => resolve_address(sdk, token).await,
+        None => Ok(sdk.native_token()),
+    }
+}
+
+/// Public key stored under `alias`.
+pub async fn find_public_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, alias: &str) -> Result<common::PublicKey>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    sdk.wallet()
+        .await
+        .find_public_key(alias)
+        .map_err(|_| Error::UnknownKey(alias.to_string()))
+}
+
+/// Look up a public key alias in the wallet, falling back to parsing a raw key.
+pub async fn resolve_public_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, value: &str) -> Result<common::PublicKey>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if let Ok(public_key) = find_public_key(sdk, value).await {
+        return Ok(public_key);
+    }
+    common::PublicKey::from_str(value)
+        .map_err(|e| Error::invalid("public key or unknown alias", format!("{}: {}", value, e)))
+}
+
+/// Look up a viewing key alias in the wallet, falling back to parsing a raw key.
+pub async fn resolve_viewing_key<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, value: &str) -> Result<ExtendedViewingKey>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    if let Ok(viewing_key) = sdk.wallet().await.find_viewing_key(value) {
+        return Ok(*viewing_key);
+    }
+    ExtendedViewingKey::from_str(value)
+        .map_err(|e| Error::invalid("viewing key or unknown alias", format!("{}: {}", value, e)))
+}
+
+/// The wallet's public keys that control `owner`.
+///
+/// That is the key of an implicit address, or for an established account the keys
+/// of its on-chain key set that the wallet holds. Empty when the wallet has none.
+pub async fn public_keys_of<C, U, V, I>(sdk: &NamadaImpl<C, U, V, I>, owner: &Address) -> Result<Vec<common::PublicKey>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let wallet_keys: Vec<common::PublicKey> = sdk.wallet().await.get_public_keys().into_values().collect();
+    keys_of(sdk, owner, &wallet_keys).await
+}
+
+/// The keys controlling `owner` that `signer` can sign with, as [`public_keys_of`]
+/// but looked up in the signer rather than the wallet.
+///
+/// Fails with [`Error::WatchOnly`] when there is none, nothing could be signed for `owner`.
+pub async fn signing_keys_of<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    signer: &dyn Signer,
+    owner: &Address,
+) -> Result<Vec<common::PublicKey>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let keys = keys_of(sdk, owner, &signer.public_keys().await?).await?;
+    if keys.is_empty() {
+        return Err(Error::WatchOnly(owner.clone()));
+    }
+    Ok(keys)
+}
+
+// The keys among `known` that control `owner`, in the order of its key set
+async fn keys_of<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    owner: &Address,
+    known: &[common::PublicKey],
+) -> Result<Vec<common::PublicKey>>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    let candidates: Vec<PublicKeyHash> = match owner {
+        Address::Implicit(implicit) => vec![implicit.0.clone()],
+        Address::Established(_) => match query::get_account_info(sdk, owner).await? {
+            Some(account) => {
+                let mut indexed: Vec<_> = account.public_keys_map.idx_to_pk.iter().collect();
+                indexed.sort_by_key(|(index, _)| **index);
+                indexed.into_iter().map(|(_, pk)| PublicKeyHash::from(pk)).collect()
+            }
+            None => Vec::new(),
+        },
+        Address::Internal(_) => Vec::new(),
+    };
+
+    Ok(candidates
+        .iter()
+        .filter_map(|pkh| known.iter().find(|key| PublicKeyHash::from(*key) == *pkh).cloned())
+        .collect())
+}
@@ -0,0 +1,220 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+
+use namada_sdk::wallet::{LoadStoreError, Store, Wallet, WalletIo, WalletStorage};
+use namada_sdk::zeroize::Zeroizing;
+use rand_core::OsRng;
+
+use crate::error::{Error, Result};
+use crate::lock::DirLock;
+use crate::mnemonic::{PendingMnemonic, VerifiedMnemonic};
+use crate::store::WALLET_FILE;
+
+/// Tries the user gets to re-enter the checked words of a new mnemonic.
+const BACKUP_CHECK_ATTEMPTS: usize = 3;
+
+/// Password used to encrypt and unlock keys without prompting, for scripted runs.
+/// Set it to an empty string to store new keys unencrypted.
+pub const ENV_WALLET_PASSWORD: &str = "NAMADA_WALLET_PASSWORD";
+
+/// Filesystem wallet storage that prompts on the terminal for passwords.
+///
+/// Signing with an encrypted key asks for its password through
+/// [`WalletIo::read_password`], unless `NAMADA_WALLET_PASSWORD` is set. Those
+/// prompts can not return an error, when the terminal can not be read the error
+/// is reported and the process exits.
+/// `wallet.toml` is read and written under the wallet dir's [`DirLock`], and
+/// replaced in one rename.
+#[derive(Clone, Debug)]
+pub struct CliWalletUtils {
+    store_dir: PathBuf,
+}
+
+impl CliWalletUtils {
+    pub fn new(store_dir: PathBuf) -> Self {
+        CliWalletUtils { store_dir }
+    }
+}
+
+impl WalletStorage for CliWalletUtils {
+    fn save<U>(&self, wallet: &Wallet<U>) -> std::result::Result<(), LoadStoreError> {
+        let save = || -> Result<()> {
+            let _lock = DirLock::acquire(&self.store_dir)?;
+            write_atomic(&self.store_dir.join(WALLET_FILE), &wallet.store().encode())
+        };
+        save().map_err(|e| LoadStoreError::StoreNewWallet(e.to_string()))
+    }
+
+    fn load<U: WalletIo>(&self, wallet: &mut Wallet<U>) -> std::result::Result<(), LoadStoreError> {
+        let path = self.store_dir.join(WALLET_FILE);
+        let read_error = |e: String| LoadStoreError::ReadWallet(path.display().to_string(), e);
+        let _lock = DirLock::acquire(&self.store_dir).map_err(|e| read_error(e.to_string()))?;
+        let data = fs::read(&path).map_err(|e| read_error(e.to_string()))?;
+        *wallet.store_mut() = Store::decode(data).map_err(LoadStoreError::Decode)?;
+        Ok(())
+    }
+}
+
+impl WalletIo for CliWalletUtils {
+    type Rng = OsRng;
+
+    fn read_password(confirm: bool, target_key: Option<&str>) -> Zeroizing<String> {
+        prompt_password(confirm, target_key).unwrap_or_else(|e| exit_on(e))
+    }
+
+    fn read_mnemonic_passphrase(confirm: bool) -> Zeroizing<String> {
+        read_passphrase(confirm).unwrap_or_else(|e| exit_on(e))
+    }
+
+    fn read_alias(prompt_msg: &str) -> String {
+        read_line(&format!("Choose an alias for {}: ", prompt_msg)).unwrap_or_else(|e| exit_on(e))
+    }
+}
+
+/// Ask for the password of `target_key`, confirmed when `confirm` is set, unless
+/// `NAMADA_WALLET_PASSWORD` is set.
+pub fn prompt_password(confirm: bool, target_key: Option<&str>) -> Result<Zeroizing<String>> {
+    if let Ok(password) = env::var(ENV_WALLET_PASSWORD) {
+        return Ok(Zeroizing::new(password));
+    }
+    let prompt = match target_key {
+        Some(key) => format!("Enter the password for {}: ", key),
+        None => "Enter your encryption password: ".to_string(),
+    };
+    loop {
+        let password = read_hidden(&prompt)?;
+        if !confirm || read_hidden("Confirm the password: ")? == password {
+            return Ok(password);
+        }
+        eprintln!("Passwords did not match, try again.");
+    }
+}
+
+/// Ask for the password to encrypt new keys with, confirmed.
+///
+/// `None` when the user leaves it empty, so the keys are stored unencrypted.
+pub fn prompt_new_password() -> Result<Option<Zeroizing<String>>> {
+    if let Ok(password) = env::var(ENV_WALLET_PASSWORD) {
+        return Ok((!password.is_empty()).then(|| Zeroizing::new(password)));
+    }
+    loop {
+        let password = read_hidden("Encryption password (empty to store the key unencrypted): ")?;
+        if password.is_empty() {
+            return Ok(None);
+        }
+        if read_hidden("Confirm the password: ")? == password {
+            return Ok(Some(password));
+        }
+        eprintln!("Passwords did not match, try again.");
+    }
+}
+
+/// Ask for a secret, such as a raw key to import, without echoing it.
+pub fn prompt_secret(what: &str) -> Result<Zeroizing<String>> {
+    read_hidden(&format!("Enter the {}: ", what))
+}
+
+/// Read a secret, such as a raw key to import, from the file at `path`, or from
+/// stdin when `path` is `-`. Surrounding whitespace is dropped.
+pub fn read_secret(what: &'static str, path: &Path) -> Result<Zeroizing<String>> {
+    let mut content = Zeroizing::new(String::new());
+    let read = if path == Path::new("-") {
+        io::stdin().read_to_string(&mut content)
+    } else {
+        fs::File::open(path).and_then(|mut file| file.read_to_string(&mut content))
+    };
+    read.map_err(|e| Error::invalid(what, format!("unable to read {}: {}", path.display(), e)))?;
+    Ok(Zeroizing::new(content.trim().to_string()))
+}
+
+/// Ask for an optional BIP39 passphrase, confirmed.
+pub fn prompt_mnemonic_passphrase() -> Result<Zeroizing<String>> {
+    read_passphrase(true)
+}
+
+/// Show a new mnemonic, then ask for some of its words back before it may be stored.
+///
+/// The screen is cleared before the check so the words cannot just be copied. Like
+/// every prompt, the words are written to stderr, never to stdout with the reports.
+pub fn prompt_backup_check(pending: &PendingMnemonic) -> Result<VerifiedMnemonic> {
+    eprintln!("Write down your mnemonic, it is the only way to recover the key:\n");
+    for (i, word) in pending.phrase().split_whitespace().enumerate() {
+        eprintln!("{:>4}. {}", i + 1, word);
+    }
+    read_line("\nPress Enter once it is written down...")?;
+    eprint!("\x1B[2J\x1B[H");
+
+    for attempt in 1..=BACKUP_CHECK_ATTEMPTS {
+        let answers = pending
+            .challenge()
+            .iter()
+            .map(|i| read_line(&format!("Enter word #{}: ", i + 1)))
+            .collect::<Result<Vec<String>>>()?;
+        let answers: Vec<&str> = answers.iter().map(String::as_str).collect();
+        match pending.verify(&answers) {
+            Ok(verified) => return Ok(verified),
+            Err(e) if attempt == BACKUP_CHECK_ATTEMPTS => return Err(e),
+            Err(_) => eprintln!("Those words do not match, try again."),
+        }
+    }
+    Err(Error::MnemonicNotVerified)
+}
+
+/// Replace the file at `path` with `content` in one rename, so a crash never leaves it half written.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    fs::create_dir_all(dir).map_err(|e| Error::WalletIo(format!("Unable to create {}: {}", dir.display(), e)))?;
+
+    let mut tmp_name = path.file_name().unwrap_or_default().to_os_string();
+    tmp_name.push(".tmp");
+    let tmp = dir.join(tmp_name);
+
+    let write = || -> io::Result<()> {
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(content)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    };
+    write().map_err(|e| {
+        let _ = fs::remove_file(&tmp);
+        Error::WalletIo(format!("Unable to write {}: {}", path.display(), e))
+    })
+}
+
+fn read_passphrase(confirm: bool) -> Result<Zeroizing<String>> {
+    loop {
+        let passphrase = read_hidden("Enter the BIP39 passphrase (empty for none): ")?;
+        if !confirm || read_hidden("Confirm the BIP39 passphrase: ")? == passphrase {
+            return Ok(passphrase);
+        }
+        eprintln!("Passphrases did not match, try again.");
+    }
+}
+
+// One trimmed line of stdin, an error once it is closed rather than endless empty answers
+fn read_line(prompt: &str) -> Result<String> {
+    eprint!("{}", prompt);
+    io::stderr()
+        .flush()
+        .map_err(|e| Error::WalletIo(format!("Unable to write to stderr: {}", e)))?;
+    let mut input = String::new();
+    match io::stdin().read_line(&mut input) {
+        Ok(0) => Err(Error::invalid("input", "stdin was closed")),
+        Ok(_) => Ok(input.trim().to_string()),
+        Err(e) => Err(Error::WalletIo(format!("Unable to read stdin: {}", e))),
+    }
+}
+
+fn read_hidden(prompt: &str) -> Result<Zeroizing<String>> {
+    rpassword::prompt_password(prompt)
+        .map(Zeroizing::new)
+        .map_err(|e| Error::WalletIo(format!("Unable to read from the terminal: {}", e)))
+}
+
+// The `WalletIo` prompts have no way to fail
+fn exit_on(e: Error) -> ! {
+    eprintln!("{}", e);
+    std::process::exit(1);
+}
@@ -0,0 +1,88 @@
+//! Watch-only entries: transparent addresses and viewing keys stored without any
+//! secret, to follow accounts whose keys are kept elsewhere.
+
+use std::str::FromStr;
+
+use namada_core::address::Address;
+use namada_sdk::masp::ShieldedUtils;
+use namada_sdk::{
+    chain::BlockHeight,
+    io::{Client, Io},
+    wallet::{WalletIo, WalletStorage},
+    ExtendedViewingKey, MaybeSend, MaybeSync, Namada, NamadaImpl,
+};
+use tracing::{info, instrument};
+
+use crate::error::{Error, Result};
+use crate::wallet::{check_alias, save_wallet};
+
+/// Store the transparent `address` under `alias`.
+///
+/// Fails if the alias is already taken by an address, unless `force` is set.
+#[instrument(skip_all, fields(alias = alias))]
+pub async fn import_address<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    address: &str,
+    force: bool,
+) -> Result<Address>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_alias(alias)?;
+    let address = Address::from_str(address.trim()).map_err(|e| Error::invalid("address", format!("{}: {}", address, e)))?;
+
+    {
+        let mut wallet = sdk.wallet_mut().await;
+        if !force && wallet.find_address(alias).is_some() {
+            return Err(Error::AliasTaken(alias.to_string()));
+        }
+        wallet
+            .insert_address(alias.to_string(), address.clone(), true)
+            .ok_or_else(|| Error::WalletIo(format!("Address {} could not be inserted", alias)))?;
+    }
+    save_wallet(sdk).await?;
+
+    info!(%address, "watching address");
+    Ok(address)
+}
+
+/// Store the extended `viewing_key` under `alias`, with the height shielded syncs
+/// of it start at (`birthday`), if known.
+///
+/// Fails if the alias is already taken by a viewing key, unless `force` is set.
+#[instrument(skip_all, fields(alias = alias, birthday = birthday))]
+pub async fn import_viewing_key<C, U, V, I>(
+    sdk: &NamadaImpl<C, U, V, I>,
+    alias: &str,
+    viewing_key: &str,
+    birthday: Option<u64>,
+    force: bool,
+) -> Result<ExtendedViewingKey>
+where
+    C: Client + MaybeSync + MaybeSend,
+    U: WalletIo + WalletStorage + MaybeSync + MaybeSend,
+    V: ShieldedUtils + MaybeSync + MaybeSend,
+    I: Io + MaybeSync + MaybeSend,
+{
+    check_alias(alias)?;
+    let viewing_key = ExtendedViewingKey::from_str(viewing_key.trim())
+        .map_err(|e| Error::invalid("viewing key", e))?;
+
+    {
+        let mut wallet = sdk.wallet_mut().await;
+        if !force && wallet.find_viewing_key(alias).is_ok() {
+            return Err(Error::AliasTaken(alias.to_string()));
+        }
+        wallet
+            .insert_viewing_key(alias.to_string(), viewing_key, birthday.map(BlockHeight), true)
+            .ok_or_else(|| Error::WalletIo(format!("Viewing key {} could not be inserted", alias)))?;
+    }
+    save_wallet(sdk).await?;
+
+    info!("watching viewing key");
+    Ok(viewing_key)
+}
@@ -0,0 +1,391 @@
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+use namada_core::address::Address;
+use namada_core::borsh::{BorshSerialize, BorshSerializeExt};
+use namada_core::chain::BlockHeight;
+use namada_core::hash::Hash;
+use namada_sdk::events::extend::Info;
+use namada_sdk::events::Event;
+use namada_sdk::queries::{Client, EncodedResponseQuery};
+use namada_sdk::tx::Tx;
+use namada_tx::data::{BatchedTxResult, ResultCode, TxResult};
+use namada_tx::event::{new_tx_event, Batch, Code, GasUsed};
+use tendermint::Time;
+use tendermint_rpc::{Error as RpcError, Request, Response, SimpleRequest};
+
+type Matcher = Box<dyn Fn(&str) -> bool + Send + Sync>;
+type Reply = Box<dyn Fn(&str, &[String]) -> Option<Vec<u8>> + Send + Sync>;
+
+/// Stand-in for a Tendermint RPC endpoint.
+///
+/// ABCI queries are answered from rules matched against the query path, RPC
+/// methods (`status`, `broadcast_tx_sync`, ...) from scripted JSON results.
+/// Unscripted requests fail, like a node that does not know the path.
+#[derive(Clone, Default)]
+pub struct MockClient {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    queries: Vec<(Matcher, Reply)>,
+    methods: Vec<(String, String)>,
+    seen_paths: Vec<String>,
+    broadcasts: Vec<String>,
+}
+
+impl MockClient {
+    /// Answer ABCI queries whose path is exactly `path` with `value`.
+    pub fn respond(&self, path: &str, value: impl BorshSerialize) -> &Self {
+        let path = path.to_string();
+        self.respond_when(move |p| p == path, value)
+    }
+
+    /// Answer ABCI queries whose path satisfies `matcher` with `value`.
+    ///
+    /// Later rules take precedence, so a test can override a default.
+    pub fn respond_when(
+        &self,
+        matcher: impl Fn(&str) -> bool + Send + Sync + 'static,
+        value: impl BorshSerialize,
+    ) -> &Self {
+        let data = value.serialize_to_vec();
+        self.rule(Box::new(matcher), Box::new(move |_, _| Some(data.clone())))
+    }
+
+    /// Answer ABCI queries whose path satisfies `matcher` with what `reply` makes
+    /// of the path and the broadcasts received so far. The query fails when it
+    /// returns `None`.
+    pub fn respond_with<T: BorshSerialize>(
+        &self,
+        matcher: impl Fn(&str) -> bool + Send + Sync + 'static,
+        reply: impl Fn(&str, &[String]) -> Option<T> + Send + Sync + 'static,
+    ) -> &Self {
+        self.rule(
+            Box::new(matcher),
+            Box::new(move |path, broadcasts| reply(path, broadcasts).map(|value| value.serialize_to_vec())),
+        )
+    }
+
+    /// Report each broadcast tx as applied when its hash is queried, with
+    /// `initialized_accounts` created by it.
+    pub fn apply_broadcasts(&self, initialized_accounts: Vec<Address>) -> &Self {
+        self.respond_with(
+            |path| path.starts_with("/shell/applied/"),
+            move |path, broadcasts| {
+                let hash = path.rsplit('/').next()?;
+                broadcasts
+                    .iter()
+                    .filter_map(|params| broadcast_tx(params))
+                    .find(|tx| tx.header_hash().to_string().eq_ignore_ascii_case(hash))
+                    .map(|tx| Some(applied_event(&tx, &initialized_accounts)))
+            },
+        )
+    }
+
+    fn rule(&self, matcher: Matcher, reply: Reply) -> &Self {
+        self.state.lock().unwrap().queries.insert(0, (matcher, reply));
+        self
+    }
+
+    /// Answer the RPC `method` with the JSON `result`.
+    pub fn respond_rpc(&self, method: &str, result: String) -> &Self {
+        let mut state = self.state.lock().unwrap();
+        state.methods.retain(|(m, _)| m != method);
+        state.methods.push((method.to_string(), result));
+        self
+    }
+
+    /// Script `/status` for a node on `network` whose latest block is `block_age_secs` old.
+    pub fn set_status(&self, network: &str, height: u64, block_age_secs: u64, catching_up: bool) -> &Self {
+        self.respond_rpc("status", status_json(network, height, block_age_secs, catching_up))
+    }
+
+    /// ABCI query paths requested so far.
+    pub fn seen_paths(&self) -> Vec<String> {
+        self.state.lock().unwrap().seen_paths.clone()
+    }
+
+    /// Raw JSON of every `broadcast_tx_*` request received.
+    pub fn broadcasts(&self) -> Vec<String> {
+        self.state.lock().unwrap().broadcasts.clone()
+    }
+
+    /// The txs broadcast so far, in order.
+    pub fn broadcast_txs(&self) -> Vec<Tx> {
+        self.broadcasts().iter().filter_map(|params| broadcast_tx(params)).collect()
+    }
+
+    /// Serve the same rules over HTTP JSON-RPC, for a process that connects to
+    /// a node by URL. Returns the URL to use as its RPC address.
+    pub async fn serve(&self) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let client = self.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let client = client.clone();
+                tokio::spawn(async move { client.serve_connection(stream).await });
+            }
+        });
+        url
+    }
+
+    async fn serve_connection(&self, mut stream: TcpStream) {
+        let Some(body) = read_http_body(&mut stream).await else {
+            return;
+        };
+        let Ok(request) = serde_json::from_str::<serde_json::Value>(&body) else {
+            return;
+        };
+        let id = request["id"].clone();
+        let method = request["method"].as_str().unwrap_or_default().to_string();
+
+        let result = if method == "abci_query" {
+            let path = request["params"]["path"].as_str().unwrap_or_default().to_string();
+            // Unscripted paths fail with a non-zero code, as a node rejects unknown queries
+            let (code, value, info) = match self.query(&path) {
+                Some(data) => (0, data, String::new()),
+                None => (1, Vec::new(), format!("unscripted query {}", path)),
+            };
+            Some(serde_json::json!({
+                "response": {
+                    "code": code,
+                    "log": "",
+                    "info": info,
+                    "index": "0",
+                    "key": "",
+                    "value": base64(&value),
+                    "proofOps": null,
+                    "height": "0",
+                    "codespace": ""
+                }
+            }))
+        } else {
+            self.method(&method, request["params"].to_string())
+                .and_then(|result| serde_json::from_str(&result).ok())
+        };
+        let body = match result {
+            Some(result) => serde_json::json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            None => serde_json::json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("unscripted method {}", method), "data": "" }
+            }),
+        }
+        .to_string();
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            body.len(),
+            body
+        );
+        let _ = stream.write_all(response.as_bytes()).await;
+        let _ = stream.shutdown().await;
+    }
+
+    // The scripted answer to the ABCI query `path`, recording it
+    fn query(&self, path: &str) -> Option<Vec<u8>> {
+        let mut state = self.state.lock().unwrap();
+        let State { queries, seen_paths, broadcasts, .. } = &mut *state;
+        seen_paths.push(path.to_string());
+        queries
+            .iter()
+            .find(|(matcher, _)| matcher(path))
+            .and_then(|(_, reply)| reply(path, broadcasts))
+    }
+
+    // The scripted JSON result of the RPC `method`, recording broadcasts
+    fn method(&self, method: &str, params: String) -> Option<String> {
+        let mut state = self.state.lock().unwrap();
+        if method.starts_with("broadcast_tx") {
+            state.broadcasts.push(params);
+        }
+        state
+            .methods
+            .iter()
+            .find(|(m, _)| m == method)
+            .map(|(_, result)| result.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl Client for MockClient {
+    type Error = std::io::Error;
+
+    async fn request(
+        &self,
+        path: String,
+        _data: Option<Vec<u8>>,
+        _height: Option<BlockHeight>,
+        _prove: bool,
+    ) -> Result<EncodedResponseQuery, Self::Error> {
+        let data = self
+            .query(&path)
+            .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, format!("unscripted query {}", path)))?;
+
+        Ok(EncodedResponseQuery {
+            data,
+            ..Default::default()
+        })
+    }
+
+    async fn perform<R>(&self, request: R) -> Result<R::Output, RpcError>
+    where
+        R: SimpleRequest,
+    {
+        let method = request.method().to_string();
+        let params = request.into_json();
+
+        let result = self
+            .method(&method, params)
+            .ok_or_else(|| RpcError::client_internal(format!("unscripted method {}", method)))?;
+
+        let body = format!(r#"{{"jsonrpc":"2.0","id":"","result":{}}}"#, result);
+        Ok(R::Response::from_string(body)?.into())
+    }
+}
+
+/// JSON result of `/status` as returned by CometBFT.
+pub fn status_json(network: &str, height: u64, block_age_secs: u64, catching_up: bool) -> String {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64;
+    let block_time = Time::from_unix_timestamp(now - block_age_secs as i64, 0)
+        .unwrap()
+        .to_rfc3339();
+    let hash = "0".repeat(64);
+
+    format!(
+        r#"{{
+            "node_info": {{
+                "protocol_version": {{"p2p": "8", "block": "11", "app": "0"}},
+                "id": "{node_id}",
+                "listen_addr": "tcp://0.0.0.0:26656",
+                "network": "{network}",
+                "version": "0.37.11",
+                "channels": "40202122233038606100",
+                "moniker": "mock-node",
+                "other": {{"tx_index": "on", "rpc_address": "tcp://0.0.0.0:26657"}}
+            }},
+            "sync_info": {{
+                "latest_block_hash": "{hash}",
+                "latest_app_hash": "{hash}",
+                "latest_block_height": "{height}",
+                "latest_block_time": "{block_time}",
+                "earliest_block_hash": "{hash}",
+                "earliest_app_hash": "{hash}",
+                "earliest_block_height": "1",
+                "earliest_block_time": "{block_time}",
+                "catching_up": {catching_up}
+            }},
+            "validator_info": {{
+                "address": "{node_id}",
+                "pub_key": {{"type": "tendermint/PubKeyEd25519", "value": "{pub_key}"}},
+                "voting_power": "0"
+            }}
+        }}"#,
+        node_id = "A".repeat(40),
+        pub_key = "A".repeat(43) + "=",
+    )
+}
+
+/// JSON result of a successful `broadcast_tx_sync`.
+pub fn broadcast_ok_json() -> String {
+    format!(
+        r#"{{"code": 0, "data": "", "log": "", "codespace": "", "hash": "{}"}}"#,
+        "A".repeat(64)
+    )
+}
+
+/// The event of `tx` applied at height 100, its inner txs accepted.
+pub fn applied_event(tx: &Tx, initialized_accounts: &[Address]) -> Event {
+    let mut result = TxResult::<String>::default();
+    // Keyed apart from the inner tx hashes, or the SDK would prompt for aliases
+    // of the new accounts, which the operations store themselves
+    result.batch_results.0.insert(
+        Hash::zero(),
+        Ok(BatchedTxResult {
+            initialized_accounts: initialized_accounts.to_vec(),
+            ..Default::default()
+        }),
+    );
+    let mut event = new_tx_event(tx, 100);
+    event
+        .extend(Code(ResultCode::Ok))
+        .extend(GasUsed(Default::default()))
+        .extend(Info(String::new()))
+        .extend(Batch(&result));
+    event
+}
+
+// The tx of a `broadcast_tx_*` request. Requests made through `Client` are
+// recorded whole, those served over HTTP by their params only.
+fn broadcast_tx(params: &str) -> Option<Tx> {
+    let request: serde_json::Value = serde_json::from_str(params).ok()?;
+    let tx = request["params"]["tx"].as_str().or_else(|| request["tx"].as_str())?;
+    Tx::try_from(decode_base64(tx)?.as_slice()).ok()
+}
+
+// The body of one HTTP request, read up to its Content-Length
+async fn read_http_body(stream: &mut TcpStream) -> Option<String> {
+    let mut data = Vec::new();
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = stream.read(&mut buf).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        data.extend_from_slice(&buf[..n]);
+        let Some(end) = data.windows(4).position(|window| window == b"\r\n\r\n") else {
+            continue;
+        };
+        let head = String::from_utf8_lossy(&data[..end]).to_lowercase();
+        let length: usize = head
+            .lines()
+            .find_map(|line| line.strip_prefix("content-length:"))
+            .and_then(|length| length.trim().parse().ok())
+            .unwrap_or(0);
+        if data.len() >= end + 4 + length {
+            return String::from_utf8(data[end + 4..end + 4 + length].to_vec()).ok();
+        }
+    }
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+fn decode_base64(encoded: &str) -> Option<Vec<u8>> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
+    let (mut bits, mut n) = (0u32, 0u32);
+    for c in encoded.bytes().filter(|c| *c != b'=') {
+        let value = ALPHABET.iter().position(|a| *a == c)? as u32;
+        bits = (bits << 6) | value;
+        n += 6;
+        if n >= 8 {
+            n -= 8;
+            decoded.push((bits >> n) as u8);
+            bits &= (1 << n) - 1;
+        }
+    }
+    Some(decoded)
+}
@@ -0,0 +1,109 @@
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+
+const API_PREFIX: &str = "/api/v1";
+
+/// Local HTTP server speaking the subset of the MASP indexer API used by
+/// `IndexerMaspClient`. Routes map an endpoint (without the `/api/v1` prefix
+/// and query string) to a JSON body; anything else is a 404.
+#[derive(Clone)]
+pub struct MockIndexer {
+    addr: SocketAddr,
+    routes: Arc<Mutex<BTreeMap<String, String>>>,
+    requests: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockIndexer {
+    /// Start an indexer for an empty chain at `height`.
+    pub async fn start(height: u64) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let indexer = MockIndexer {
+            addr: listener.local_addr().unwrap(),
+            routes: Default::default(),
+            requests: Default::default(),
+        };
+        indexer.set_height(height);
+        indexer.route("/tx", r#"{"txs": []}"#.to_string());
+        indexer.route(
+            "/commitment-tree",
+            format!(r#"{{"commitment_tree": [], "block_height": {}}}"#, height),
+        );
+        indexer.route(
+            "/witness-map",
+            format!(r#"{{"witnesses": [], "block_height": {}}}"#, height),
+        );
+        indexer.route(
+            "/notes-index",
+            format!(r#"{{"notes_index": [], "block_height": {}}}"#, height),
+        );
+        indexer.route(
+            "/block-index",
+            format!(r#"{{"block_height": {}, "index": []}}"#, height),
+        );
+
+        let server = indexer.clone();
+        tokio::spawn(async move {
+            loop {
+                let Ok((mut stream, _)) = listener.accept().await else {
+                    return;
+                };
+                let server = server.clone();
+                tokio::spawn(async move {
+                    let mut buf = vec![0u8; 8192];
+                    let Ok(n) = stream.read(&mut buf).await else {
+                        return;
+                    };
+                    let request = String::from_utf8_lossy(&buf[..n]);
+                    let target = request.split_whitespace().nth(1).unwrap_or("/").to_string();
+                    let response = server.handle(&target);
+                    let _ = stream.write_all(response.as_bytes()).await;
+                    let _ = stream.shutdown().await;
+                });
+            }
+        });
+
+        indexer
+    }
+
+    /// Base URL to hand to `shielded_sync`.
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, API_PREFIX)
+    }
+
+    pub fn set_height(&self, height: u64) {
+        self.route("/height", format!(r#"{{"block_height": {}}}"#, height));
+    }
+
+    pub fn route(&self, endpoint: &str, body: String) {
+        self.routes.lock().unwrap().insert(endpoint.to_string(), body);
+    }
+
+    /// Endpoints requested so far, without the API prefix and query string.
+    pub fn requests(&self) -> Vec<String> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    fn handle(&self, target: &str) -> String {
+        let path = target.split('?').next().unwrap_or(target);
+        let endpoint = path.strip_prefix(API_PREFIX).unwrap_or(path).to_string();
+        self.requests.lock().unwrap().push(endpoint.clone());
+
+        match self.routes.lock().unwrap().get(&endpoint) {
+            Some(body) => http_response("200 OK", body),
+            None => http_response("404 Not Found", r#"{"message": "not found"}"#),
+        }
+    }
+}
+
+fn http_response(status: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
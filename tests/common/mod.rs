@@ -0,0 +1,140 @@
+//! Offline test harness: a scripted RPC client, a local MASP indexer and a
+//! throwaway wallet/shielded dir, wired into a `NamadaImpl`.
+
+#![allow(dead_code)]
+
+pub mod mock_client;
+pub mod mock_indexer;
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use std::sync::Once;
+
+use namada_core::address::Address;
+use namada_core::chain::{BlockHeight, Epoch};
+use namada_core::hash::Hash;
+use namada_core::masp::MaspEpoch;
+use namada_core::time::{DateTimeUtc, DurationSecs};
+use namada_sdk::account::{Account, AccountPublicKeysMap};
+use namada_sdk::chain::ChainId;
+use namada_sdk::io::StdIo;
+use namada_sdk::key::common;
+use namada_sdk::masp::fs::FsShieldedUtils;
+use namada_sdk::token;
+use namada_sdk::NamadaImpl;
+use tempfile::TempDir;
+use wallet_example::CliWalletUtils;
+
+use mock_client::broadcast_ok_json;
+pub use mock_client::MockClient;
+pub use mock_indexer::MockIndexer;
+
+pub const CHAIN_ID: &str = "localnet.3c9a2fe70462d";
+pub const NATIVE_TOKEN: &str = "tnam1qy440ynh9fwrx8aewjvvmu38zxqgukgc259fzp6h";
+pub const OWNER: &str = "tnam1qqteapc3ycthpehxtqadv6nx2grr5gptzs2ptyvy";
+pub const TARGET: &str = "tnam1qpu63hxasnmsq25juqfcql287mvkgjx3vuvgzktz";
+pub const MNEMONIC: &str = "abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon abandon art";
+
+pub type TestSdk = NamadaImpl<MockClient, CliWalletUtils, FsShieldedUtils, StdIo>;
+
+/// A mock node and indexer plus a temporary dir holding the wallet and MASP context.
+pub struct TestEnv {
+    pub client: MockClient,
+    pub indexer: MockIndexer,
+    pub dir: TempDir,
+}
+
+impl TestEnv {
+    /// A healthy node on [`CHAIN_ID`] at height 100 and an indexer at the same height.
+    pub async fn new() -> Self {
+        fake_masp_params();
+
+        let client = MockClient::default();
+        client
+            .respond("/shell/native_token", Address::from_str(NATIVE_TOKEN).unwrap())
+            .respond("/shell/masp_epoch", MaspEpoch::new(7))
+            .set_status(CHAIN_ID, 100, 5, false);
+
+        TestEnv {
+            client,
+            indexer: MockIndexer::start(100).await,
+            dir: tempfile::tempdir().unwrap(),
+        }
+    }
+
+    /// Build an SDK context on top of the mocks, loading the wallet if one was saved.
+    pub async fn sdk(&self) -> TestSdk {
+        let wallet = CliWalletUtils::new(self.dir.path().join("wallet"));
+        let shielded = FsShieldedUtils::new(self.dir.path().join("masp"));
+        let sdk = NamadaImpl::new(self.client.clone(), wallet, shielded, StdIo)
+            .await
+            .unwrap()
+            .chain_id(ChainId::from_str(CHAIN_ID).unwrap());
+        let _ = sdk.wallet_mut().await.load();
+        sdk
+    }
+
+    /// Script what a node answers while txs are built, broadcast and applied:
+    /// the epochs and last block, a minimum gas price in the native token,
+    /// token denominations, existing addresses and wasm code, an accepting
+    /// `broadcast_tx_sync` and the applied event of every broadcast tx.
+    ///
+    /// Fee payers still need a balance, see [`TestEnv::set_balance`].
+    pub fn accept_txs(&self) -> &Self {
+        let gas_price = BTreeMap::from([(Address::from_str(NATIVE_TOKEN).unwrap(), token::Amount::from_u64(1))]);
+        let gas_cost_key = namada_parameters::storage::get_gas_cost_key().to_string();
+        let block_duration_key = namada_parameters::storage::get_max_block_duration_key().to_string();
+        self.client
+            .respond("/shell/epoch", Epoch(5))
+            .respond("/shell/last_block", Some((BlockHeight(100), DateTimeUtc::now())))
+            .respond_when(move |path| path.ends_with(&gas_cost_key), gas_price)
+            .respond_when(move |path| path.ends_with(&block_duration_key), DurationSecs(5))
+            .respond_when(|path| path.contains("denomination"), Some(token::Denomination(6)))
+            .respond_when(|path| path.starts_with("/shell/has_key/"), true)
+            .respond_when(|path| path.starts_with("/shell/value/") && path.contains(".wasm"), Hash::sha256(b"wasm"))
+            .respond_rpc("broadcast_tx_sync", broadcast_ok_json())
+            .apply_broadcasts(Vec::new());
+        self
+    }
+
+    /// Script the on-chain account of `owner`, revealed with `public_keys`.
+    pub fn set_account(&self, owner: &Address, public_keys: Vec<common::PublicKey>) {
+        self.set_multisig(owner, public_keys, 1);
+    }
+
+    /// Script the on-chain account of `owner`, needing `threshold` signatures of `public_keys`.
+    pub fn set_multisig(&self, owner: &Address, public_keys: Vec<common::PublicKey>, threshold: u8) {
+        let account = Account {
+            public_keys_map: AccountPublicKeysMap::from_iter(public_keys),
+            threshold,
+            address: owner.clone(),
+        };
+        let path = format!("/shell/account/{}", owner);
+        self.client.respond(&path, Some(account));
+    }
+
+    /// Script the transparent balance of `token` held by `owner`.
+    pub fn set_balance(&self, token: &Address, owner: &Address, amount: token::Amount) {
+        let (token, owner) = (token.to_string(), owner.to_string());
+        self.client
+            .respond_when(move |path| path.contains("balance") && path.contains(&token) && path.contains(&owner), amount);
+    }
+}
+
+/// `FsShieldedUtils::new` downloads the MASP parameters when they are missing.
+/// Proofs are made by the SDK's mock prover (its `testing` feature), so empty
+/// files are enough.
+fn fake_masp_params() {
+    static PARAMS: Once = Once::new();
+    PARAMS.call_once(|| {
+        let dir = std::env::temp_dir().join("wallet-example-test-masp-params");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in ["masp-spend.params", "masp-output.params", "masp-convert.params"] {
+            let path = dir.join(name);
+            if !path.exists() {
+                std::fs::write(path, []).unwrap();
+            }
+        }
+        std::env::set_var("NAMADA_MASP_PARAMS_DIR", dir);
+    });
+}
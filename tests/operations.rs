@@ -0,0 +1,1617 @@
+//! End-to-end runs of the menu operations against the offline harness in `common`.
+
+mod common;
+
+use std::str::FromStr;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use common::{TestEnv, TestSdk, CHAIN_ID, MNEMONIC, NATIVE_TOKEN, OWNER, TARGET};
+use namada_core::address::{Address, EstablishedAddress};
+use namada_core::borsh::BorshDeserialize;
+use namada_sdk::account::{Account, AccountPublicKeysMap, UpdateAccount};
+use namada_sdk::chain::ChainId;
+use namada_sdk::key::{common, SchemeType, SigScheme};
+use namada_sdk::signing::SigningTxData;
+use namada_sdk::tx::{ProcessTxResponse, Tx};
+use namada_sdk::wallet::{Store, Wallet};
+use namada_sdk::{token, PaymentAddress};
+use namada_sdk::zeroize::Zeroizing;
+use namada_tx::data::{ResultCode, TxResult};
+use namada_tx::{Authorization, Section};
+use tokio::net::UnixListener;
+use wallet_example::backup::{self, Backup, BackupEntry, RestoreMode};
+use wallet_example::home::WalletHome;
+use wallet_example::lock::{DirLock, WalletLock, LOCK_FILE};
+use wallet_example::inventory::{self, Custody, EntryKind, InventoryFilter};
+use wallet_example::mnemonic::{MnemonicVault, PendingMnemonic, CHECKED_WORDS};
+use wallet_example::multisig::{self, Proposal, SignatureFile, PROPOSAL_VERSION};
+use wallet_example::plan::{self, OnError, Plan, Step, StepOutput};
+use wallet_example::signer::{self, KeySignature, LocalSigner, RemoteSigner, SigHash, Signer, SignerEndpoint, WalletSigner};
+use wallet_example::wallet::KeyOptions;
+use wallet_example::wallet_utils::{read_secret, ENV_WALLET_PASSWORD};
+use wallet_example::{account, aliases, node, query, recovery, shielded, transfer, wallet, watch, CliWalletUtils, Config, Error};
+
+const SHIELDED_TARGET: &str = "znam1jk5dkka9gj8wqtkky5tgzy76heapcdg8r3aqn9syr9k3nmx6ms8wn3hdew79tptg9kfds960a2u";
+
+#[tokio::test]
+async fn create_wallet_stores_a_findable_key() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    let pending = PendingMnemonic::generate(24).unwrap();
+    let mnemonic = pending.verify(&challenge_answers(&pending)).unwrap();
+    wallet::create_wallet(&sdk, "alice".to_string(), &mnemonic, &KeyOptions::default()).await.unwrap();
+    assert!(wallet::find_address(&sdk, "alice").await.is_some());
+
+    // The wallet was saved and is picked up by a fresh context
+    let reloaded = env.sdk().await;
+    assert_eq!(
+        wallet::find_address(&reloaded, "alice").await,
+        wallet::find_address(&sdk, "alice").await
+    );
+}
+
+#[test]
+fn mnemonics_have_the_requested_word_count() {
+    for words in [12, 15, 18, 21, 24] {
+        let pending = PendingMnemonic::generate(words).unwrap();
+        assert_eq!(pending.phrase().split_whitespace().count(), words);
+        assert_eq!(pending.challenge().len(), CHECKED_WORDS);
+    }
+    let err = PendingMnemonic::generate(13).err().unwrap();
+    assert!(matches!(err, Error::InvalidInput { what: "word count", .. }), "{err}");
+}
+
+#[test]
+fn backup_check_rejects_wrong_words() {
+    let pending = PendingMnemonic::generate(12).unwrap();
+    let mut answers = challenge_answers(&pending);
+    answers[0] = "notaword";
+
+    let err = pending.verify(&answers).err().unwrap();
+    assert!(matches!(err, Error::MnemonicNotVerified), "{err}");
+    let err = pending.verify(&answers[1..]).err().unwrap();
+    assert!(matches!(err, Error::MnemonicNotVerified), "{err}");
+
+    // A failed check can be retried
+    assert!(pending.verify(&challenge_answers(&pending)).is_ok());
+}
+
+#[test]
+fn stored_mnemonics_need_their_password() {
+    let dir = tempfile::tempdir().unwrap();
+    let vault = MnemonicVault::new(dir.path());
+    vault.store("alice", MNEMONIC, "hunter2").unwrap();
+
+    let content = std::fs::read_to_string(dir.path().join("mnemonics.toml")).unwrap();
+    assert!(!content.contains("abandon"));
+
+    assert_eq!(vault.reveal("alice", "hunter2").unwrap().as_str(), MNEMONIC);
+    let err = vault.reveal("alice", "wrong").unwrap_err();
+    assert!(matches!(err, Error::Decryption(_)), "{err}");
+    assert!(vault.reveal("bob", "hunter2").is_err());
+}
+
+#[tokio::test]
+async fn add_key_derives_the_same_address_from_the_same_mnemonic() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    wallet::add_key(&sdk, "first".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "second".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+
+    let first = wallet::find_address(&sdk, "first").await.unwrap();
+    let second = wallet::find_address(&sdk, "second").await.unwrap();
+    assert_eq!(first, second);
+}
+
+#[tokio::test]
+async fn add_key_rejects_an_invalid_mnemonic() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    let err = wallet::add_key(&sdk, "bob".to_string(), "not a mnemonic", &KeyOptions::default()).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidMnemonic(_)), "{err}");
+    assert!(wallet::find_address(&sdk, "bob").await.is_none());
+}
+
+#[tokio::test]
+async fn accounts_are_derived_at_their_index_or_path() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let account = |account| KeyOptions { account, ..Default::default() };
+    let path = |path: &str| KeyOptions { derivation_path: Some(path.to_string()), ..Default::default() };
+
+    wallet::add_key(&sdk, "default".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "second".to_string(), MNEMONIC, &account(1)).await.unwrap();
+
+    let default = wallet::find_address(&sdk, "default").await.unwrap();
+    let second = wallet::find_address(&sdk, "second").await.unwrap();
+    assert_ne!(default, second);
+    assert_eq!(wallet::derive_address(MNEMONIC, &account(0)).unwrap(), default);
+    assert_eq!(wallet::derive_address(MNEMONIC, &account(1)).unwrap(), second);
+    assert_eq!(wallet::derive_address(MNEMONIC, &path("m/44'/877'/0'/0'/1'")).unwrap(), second);
+
+    let err = wallet::derive_address(MNEMONIC, &path("m/44'/877'/0'/0/1")).unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "derivation path", .. }), "{err}");
+    let err = wallet::add_key(&sdk, "bad".to_string(), MNEMONIC, &path("not a path")).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "derivation path", .. }), "{err}");
+}
+
+#[tokio::test]
+async fn derived_keys_do_not_overwrite_a_taken_alias() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let account = |account| KeyOptions { account, ..Default::default() };
+
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &account(0)).await.unwrap();
+    let err = wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &account(1)).await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(ref alias) if alias == "alice"), "{err}");
+    assert_eq!(wallet::find_address(&sdk, "alice").await, Some(wallet::derive_address(MNEMONIC, &account(0)).unwrap()));
+
+    let pending = PendingMnemonic::generate(24).unwrap();
+    let mnemonic = pending.verify(&challenge_answers(&pending)).unwrap();
+    let err = wallet::create_wallet(&sdk, "alice".to_string(), &mnemonic, &account(0)).await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+
+    wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &account(0)).await.unwrap();
+    let err = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &account(0)).await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(ref alias) if alias == "shielded"), "{err}");
+
+    // Forcing replaces the key stored under the alias
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions { force: true, ..account(1) }).await.unwrap();
+    assert_eq!(wallet::find_address(&sdk, "alice").await, Some(wallet::derive_address(MNEMONIC, &account(1)).unwrap()));
+}
+
+#[tokio::test]
+async fn secp256k1_keys_get_their_own_addresses() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let secp = |account| KeyOptions { scheme: SchemeType::Secp256k1, account, ..Default::default() };
+
+    wallet::add_key(&sdk, "ed".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "eth".to_string(), MNEMONIC, &secp(0)).await.unwrap();
+
+    let ed = wallet::find_address(&sdk, "ed").await.unwrap();
+    let eth = wallet::find_address(&sdk, "eth").await.unwrap();
+    assert_ne!(ed, eth);
+    assert_eq!(wallet::derive_address(MNEMONIC, &secp(0)).unwrap(), eth);
+    assert_ne!(wallet::derive_address(MNEMONIC, &secp(1)).unwrap(), eth);
+
+    let public_key = sdk.wallet().await.find_public_key("eth").unwrap();
+    assert!(matches!(public_key, common::PublicKey::Secp256k1(_)));
+    assert_eq!(Address::from(&public_key), eth);
+
+    // Secp256k1 paths may have unhardened components, unlike Ed25519 ones
+    let path = KeyOptions { derivation_path: Some("m/44'/60'/0'/0/0".to_string()), ..secp(0) };
+    assert_eq!(wallet::derive_address(MNEMONIC, &path).unwrap(), eth);
+}
+
+#[tokio::test]
+async fn recovery_imports_used_accounts_until_the_gap_limit() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let token = wallet::resolve_token(&sdk, None).await.unwrap();
+    let address = |account| wallet::derive_address(MNEMONIC, &KeyOptions { account, ..Default::default() }).unwrap();
+
+    // Unknown accounts with no balance, except a revealed account 0 and a funded account 2
+    env.client
+        .respond_when(|path| path.starts_with("/shell/account/"), None::<Account>)
+        .respond_when(|path| path.contains("balance"), token::Amount::zero());
+    env.set_account(&address(0), vec![]);
+    env.set_balance(&token, &address(2), token::Amount::native_whole(5));
+    env.set_balance(&token, &address(5), token::Amount::native_whole(5));
+
+    let accounts = recovery::recover_accounts(&sdk, "restored", MNEMONIC, &KeyOptions::default(), &[], 2)
+        .await
+        .unwrap();
+
+    // Account 5 is past two unused accounts and is not reached
+    let found: Vec<_> = accounts.iter().map(|a| (a.index, a.alias.as_str(), a.revealed)).collect();
+    assert_eq!(found, vec![(0, "restored", true), (2, "restored-2", false)]);
+    assert_eq!(wallet::find_address(&sdk, "restored").await, Some(address(0)));
+    assert_eq!(wallet::find_address(&sdk, "restored-2").await, Some(address(2)));
+    assert!(wallet::find_address(&sdk, "restored-1").await.is_none());
+    assert!(!env.client.seen_paths().iter().any(|path| path.contains(&address(5).to_string())));
+
+    // Scanning again keeps the accounts already stored
+    let again = recovery::recover_accounts(&sdk, "restored", MNEMONIC, &KeyOptions::default(), &[], 2)
+        .await
+        .unwrap();
+    assert_eq!(again.len(), 2);
+
+    // An alias held by another entry stops the recovery before anything is stored
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    other
+        .client
+        .respond_when(|path| path.starts_with("/shell/account/"), None::<Account>)
+        .respond_when(|path| path.contains("balance"), token::Amount::zero());
+    other.set_account(&address(0), vec![]);
+    other.set_balance(&token, &address(2), token::Amount::native_whole(5));
+    wallet::add_key(&other_sdk, "restored-2".to_string(), MNEMONIC, &KeyOptions { account: 9, ..Default::default() })
+        .await
+        .unwrap();
+    let err = recovery::recover_accounts(&other_sdk, "restored", MNEMONIC, &KeyOptions::default(), &[], 2)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(ref alias) if alias == "restored-2"), "{err}");
+    assert!(wallet::find_address(&other_sdk, "restored").await.is_none());
+    assert_eq!(
+        wallet::find_address(&other_sdk, "restored-2").await,
+        Some(wallet::derive_address(MNEMONIC, &KeyOptions { account: 9, ..Default::default() }).unwrap())
+    );
+}
+
+#[tokio::test]
+async fn bip39_passphrase_changes_the_derived_address() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let with_passphrase = KeyOptions {
+        passphrase: Zeroizing::new("extra words".to_string()),
+        ..Default::default()
+    };
+
+    wallet::add_key(&sdk, "plain".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "salted".to_string(), MNEMONIC, &with_passphrase).await.unwrap();
+
+    let plain = wallet::find_address(&sdk, "plain").await.unwrap();
+    let salted = wallet::find_address(&sdk, "salted").await.unwrap();
+    assert_ne!(plain, salted);
+}
+
+#[tokio::test]
+async fn encrypted_keys_need_their_password() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let options = KeyOptions {
+        password: Some(Zeroizing::new("hunter2".to_string())),
+        ..Default::default()
+    };
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &options).await.unwrap();
+
+    let wallet_file = std::fs::read_to_string(env.dir.path().join("wallet").join("wallet.toml")).unwrap();
+    assert!(wallet_file.contains("encrypted:"));
+
+    let mut wallet = sdk.wallet_mut().await;
+    assert!(wallet
+        .find_secret_key("alice", Some(Zeroizing::new("wrong".to_string())))
+        .is_err());
+    assert!(wallet
+        .find_secret_key("alice", Some(Zeroizing::new("hunter2".to_string())))
+        .is_ok());
+}
+
+#[tokio::test]
+async fn spending_key_and_payment_address() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    let alias = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let viewing_key = sdk.wallet().await.find_viewing_key(&alias).unwrap().to_string();
+
+    let payment_addr = wallet::generate_payment_address(&sdk, "pay", &viewing_key, false)
+        .await
+        .unwrap();
+    assert_eq!(sdk.wallet().await.find_payment_addr("pay"), Some(&payment_addr));
+
+    // A second address under the same alias needs force
+    let err = wallet::generate_payment_address(&sdk, "pay", &viewing_key, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(ref alias) if alias == "pay"), "{err}");
+    wallet::generate_payment_address(&sdk, "pay", &viewing_key, true)
+        .await
+        .unwrap();
+
+    // The viewing key can be given by its alias
+    let by_alias = wallet::resolve_viewing_key(&sdk, &alias).await.unwrap();
+    assert_eq!(by_alias.to_string(), viewing_key);
+    wallet::generate_payment_address(&sdk, "pay", &alias, true).await.unwrap();
+    let err = wallet::resolve_viewing_key(&sdk, "nobody").await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn raw_secret_keys_are_imported_with_their_address() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let secp = KeyOptions { scheme: SchemeType::Secp256k1, ..Default::default() };
+    wallet::add_key(&sdk, "ed".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "eth".to_string(), MNEMONIC, &secp).await.unwrap();
+    let ed_key = sdk.wallet_mut().await.find_secret_key("ed", None).unwrap().to_string();
+    let eth_key = sdk.wallet_mut().await.find_secret_key("eth", None).unwrap().to_string();
+
+    // Read from a file as `key import --secret-key-file` does, never from argv
+    let key_file = env.dir.path().join("ed.key");
+    std::fs::write(&key_file, format!("{}\n", ed_key)).unwrap();
+    let read_key = read_secret("secret key", &key_file).unwrap();
+    assert_eq!(read_key.as_str(), ed_key);
+    let err = read_secret("secret key", &env.dir.path().join("missing.key")).unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "secret key", .. }), "{err}");
+
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    let address = wallet::import_secret_key(&other_sdk, "ed", &read_key, SchemeType::Ed25519, None, false)
+        .await
+        .unwrap();
+    assert_eq!(Some(address), wallet::find_address(&sdk, "ed").await);
+    let owner = wallet::find_address(&other_sdk, "ed").await.unwrap();
+    assert!(wallet::signing_keys_of(&other_sdk, &WalletSigner::new(&other_sdk), &owner).await.is_ok());
+
+    // The bare key needs its scheme
+    let address = wallet::import_secret_key(&other_sdk, "eth", &eth_key[2..], SchemeType::Secp256k1, None, false)
+        .await
+        .unwrap();
+    assert_eq!(Some(address), wallet::find_address(&sdk, "eth").await);
+
+    let err = wallet::import_secret_key(&other_sdk, "ed", &eth_key, SchemeType::Ed25519, None, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+    let err = wallet::import_secret_key(&other_sdk, "bad", "00abcd", SchemeType::Ed25519, None, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "secret key", .. }), "{err}");
+}
+
+#[tokio::test]
+async fn raw_spending_keys_are_imported_with_their_viewing_key_and_birthday() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let alias = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let spending_key = sdk.wallet().await.find_spending_key(&alias, None).unwrap().to_string();
+    let viewing_key = sdk.wallet().await.find_viewing_key(&alias).unwrap().to_string();
+
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    let imported = wallet::import_spending_key(&other_sdk, "imported", &spending_key, Some(42), None, false)
+        .await
+        .unwrap();
+    assert_eq!(imported.to_string(), viewing_key);
+    assert_eq!(other_sdk.wallet().await.find_birthday("imported").map(|height| height.0), Some(42));
+    let filter = InventoryFilter { kinds: vec![EntryKind::SpendingKey], alias: None };
+    let entries = inventory::list_wallet(&other_sdk, &filter).await;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].custody, Custody::Secret);
+
+    shielded::shielded_sync(&other_sdk, &other.indexer.url(), "imported").await.unwrap();
+
+    let err = wallet::import_spending_key(&other_sdk, "imported", &spending_key, None, None, false)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+    let err = wallet::import_spending_key(&other_sdk, "vk", &viewing_key, None, None, false).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "spending key", .. }), "{err}");
+}
+
+#[tokio::test]
+async fn signing_keys_are_the_ones_controlling_the_source() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "bob".to_string(), MNEMONIC, &KeyOptions { account: 1, ..Default::default() }).await.unwrap();
+    let alice = wallet::find_address(&sdk, "alice").await.unwrap();
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    let bob_key = wallet::find_public_key(&sdk, "bob").await.unwrap();
+    assert!(matches!(wallet::find_public_key(&sdk, "nobody").await, Err(Error::UnknownKey(_))));
+
+    assert_eq!(wallet::public_keys_of(&sdk, &alice).await.unwrap(), vec![alice_key.clone()]);
+    let stranger = Address::from_str(TARGET).unwrap();
+    assert!(wallet::public_keys_of(&sdk, &stranger).await.unwrap().is_empty());
+
+    // Of an established account, only the keys the wallet holds
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    wallet::add_key(&other_sdk, "mallory".to_string(), MNEMONIC, &KeyOptions { account: 5, ..Default::default() })
+        .await
+        .unwrap();
+    let foreign_key = wallet::find_public_key(&other_sdk, "mallory").await.unwrap();
+    let multisig = Address::Established(EstablishedAddress::from([7u8; 20]));
+    env.set_account(&multisig, vec![foreign_key, bob_key.clone()]);
+    assert_eq!(wallet::public_keys_of(&sdk, &multisig).await.unwrap(), vec![bob_key]);
+}
+
+#[tokio::test]
+async fn account_updates_diff_the_key_set_and_need_the_current_keys() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    for (alias, account) in [("alice", 0), ("bob", 1), ("carol", 2)] {
+        wallet::add_key(&sdk, alias.to_string(), MNEMONIC, &KeyOptions { account, ..Default::default() }).await.unwrap();
+    }
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    let bob_key = wallet::find_public_key(&sdk, "bob").await.unwrap();
+    let carol_key = wallet::find_public_key(&sdk, "carol").await.unwrap();
+    let shared = Address::Established(EstablishedAddress::from([9u8; 20]));
+    env.set_multisig(&shared, vec![alice_key.clone(), bob_key], 2);
+
+    // Bob's key is replaced by Carol's, given as a raw key, and one signature is enough
+    let keys = vec!["alice".to_string(), carol_key.to_string()];
+    let update = account::prepare_account_update(&sdk, &shared.to_string(), &keys, Some(1), None).await.unwrap();
+    assert_eq!(update.public_keys, vec![alice_key.clone(), carol_key]);
+    assert_eq!((update.diff.threshold_before, update.diff.threshold_after), (2, 1));
+    let changes: Vec<_> = update
+        .diff
+        .keys
+        .iter()
+        .map(|key| (key.alias.as_deref(), key.index_before, key.index_after))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![(Some("alice"), Some(0), Some(0)), (Some("carol"), None, Some(1)), (Some("bob"), Some(1), None)]
+    );
+
+    let err = account::prepare_account_update(&sdk, &shared.to_string(), &keys, Some(3), None).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "threshold", .. }), "{err}");
+    let same = vec!["alice".to_string(), "bob".to_string()];
+    let err = account::prepare_account_update(&sdk, &shared.to_string(), &same, None, None).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "account update", .. }), "{err}");
+    let twice = vec!["alice".to_string(), alice_key.to_string()];
+    let err = account::prepare_account_update(&sdk, &shared.to_string(), &twice, None, None).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "public keys", .. }), "{err}");
+    let err = account::prepare_account_update(&sdk, "alice", &keys, None, None).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "account", .. }), "{err}");
+
+    // Two of the current keys must sign, the wallet only holds Alice's
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    wallet::add_key(&other_sdk, "mallory".to_string(), MNEMONIC, &KeyOptions { account: 5, ..Default::default() })
+        .await
+        .unwrap();
+    let foreign_key = wallet::find_public_key(&other_sdk, "mallory").await.unwrap();
+    env.set_multisig(&shared, vec![alice_key, foreign_key], 2);
+    let update = account::prepare_account_update(&sdk, &shared.to_string(), &keys, Some(1), None).await.unwrap();
+    let err = account::submit_account_update(&sdk, &WalletSigner::new(&sdk), &update).await.unwrap_err();
+    assert!(matches!(err, Error::ThresholdNotMet { threshold: 2, held: 1, .. }), "{err}");
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn account_updates_are_signed_by_the_current_keys_and_applied() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    funded_alice(&env, &sdk).await;
+    for (alias, account) in [("bob", 1), ("carol", 2)] {
+        wallet::add_key(&sdk, alias.to_string(), MNEMONIC, &KeyOptions { account, ..Default::default() }).await.unwrap();
+    }
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    let bob_key = wallet::find_public_key(&sdk, "bob").await.unwrap();
+    let carol_key = wallet::find_public_key(&sdk, "carol").await.unwrap();
+    let shared = Address::Established(EstablishedAddress::from([9u8; 20]));
+    env.set_multisig(&shared, vec![alice_key.clone(), bob_key], 2);
+
+    let keys = vec!["alice".to_string(), "carol".to_string()];
+    let update = account::prepare_account_update(&sdk, &shared.to_string(), &keys, Some(1), None).await.unwrap();
+    let submission = account::submit_account_update(&sdk, &WalletSigner::new(&sdk), &update).await.unwrap();
+    assert!(submission.reveal.is_empty());
+    assert_applied(&env, &submission.response, 0);
+
+    // The tx sets the new key set and threshold on the account
+    let txs = env.client.broadcast_txs();
+    assert_eq!(txs.len(), 1);
+    let data = txs[0].data(txs[0].first_commitments().unwrap()).unwrap();
+    let applied = UpdateAccount::try_from_slice(&data).unwrap();
+    assert_eq!(applied.addr, shared);
+    assert_eq!(applied.public_keys, vec![alice_key, carol_key]);
+    assert_eq!(applied.threshold, Some(1));
+}
+
+#[tokio::test]
+async fn multisig_signatures_are_exchanged_as_files_and_checked_against_the_account() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    for (alias, account) in [("alice", 0), ("bob", 1), ("carol", 2)] {
+        wallet::add_key(&sdk, alias.to_string(), MNEMONIC, &KeyOptions { account, ..Default::default() }).await.unwrap();
+    }
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    let bob_key = wallet::find_public_key(&sdk, "bob").await.unwrap();
+    let carol_key = wallet::find_public_key(&sdk, "carol").await.unwrap();
+    let account_keys = vec![alice_key.clone(), bob_key.clone(), carol_key.clone()];
+    let key_names: Vec<String> = account_keys.iter().map(|key| key.to_string()).collect();
+
+    // Creating the account is checked before anything is built
+    let err = account::create_account(&sdk, &signer, "alice", "alice", &key_names, 2, None).await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+    let err = account::create_account(&sdk, &signer, "treasury", "alice", &key_names, 4, None).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "threshold", .. }), "{err}");
+    assert!(env.client.broadcasts().is_empty());
+
+    let treasury = Address::Established(EstablishedAddress::from([7u8; 20]));
+    let mut tx = Tx::new(ChainId::from_str(CHAIN_ID).unwrap(), None);
+    tx.add_data(vec![1u8, 2, 3]);
+    let proposal = Proposal {
+        version: PROPOSAL_VERSION,
+        chain_id: CHAIN_ID.to_string(),
+        account: treasury.to_string(),
+        public_keys: key_names.clone(),
+        threshold: 2,
+        fee_payer: alice_key.to_string(),
+        tx_hash: tx.raw_header_hash().to_string(),
+        tx,
+    };
+    let path = env.dir.path().join("payout.json");
+    proposal.write(&path).unwrap();
+    let proposal = Proposal::read(&path).unwrap();
+
+    // Bob signs with his key only, without a node, and sends the file back
+    let signatures = multisig::sign_proposal(&sdk, &signer, &proposal, &["bob".to_string()]).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].public_key(), &bob_key);
+    let signature_path = multisig::signature_path(&path, &bob_key);
+    assert_eq!(signature_path.parent(), path.parent());
+    signatures[0].write(&signature_path).unwrap();
+    let bob_signature = SignatureFile::read(&signature_path).unwrap();
+    assert_eq!(bob_signature.tx_hash, proposal.tx_hash);
+
+    // Without a key filter every key of the account the wallet holds signs
+    assert_eq!(multisig::sign_proposal(&sdk, &signer, &proposal, &[]).await.unwrap().len(), 3);
+
+    // The submitter holds none of the account keys
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    let other_signer = WalletSigner::new(&other_sdk);
+    wallet::add_key(&other_sdk, "mallory".to_string(), MNEMONIC, &KeyOptions { account: 5, ..Default::default() })
+        .await
+        .unwrap();
+    let foreign_key = wallet::find_public_key(&other_sdk, "mallory").await.unwrap();
+    other.set_multisig(&treasury, account_keys.clone(), 2);
+
+    let err = multisig::sign_proposal(&other_sdk, &other_signer, &proposal, &[]).await.unwrap_err();
+    assert!(matches!(err, Error::WatchOnly(_)), "{err}");
+    let err = multisig::sign_proposal(&sdk, &signer, &proposal, &[foreign_key.to_string()]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "key", .. }), "{err}");
+
+    // The same signer twice counts once
+    let twice = vec![bob_signature.clone(), bob_signature.clone()];
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &twice).await.unwrap_err();
+    assert!(matches!(err, Error::ThresholdNotMet { threshold: 2, held: 1, .. }), "{err}");
+
+    // Signatures of other txs or by other keys are refused
+    let other_tx = SignatureFile { tx_hash: "00".repeat(32), ..bob_signature.clone() };
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &[other_tx]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "signature", .. }), "{err}");
+    let mut foreign = bob_signature.clone();
+    foreign.signature.pubkey = foreign_key.clone();
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &[foreign]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "signature", .. }), "{err}");
+
+    // A signature only counts for the key and account slot that made it
+    let mut swapped = bob_signature.clone();
+    swapped.signature.pubkey = carol_key.clone();
+    swapped.signature.index = Some((treasury.clone(), 2));
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &[swapped]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "signature", .. }), "{err}");
+    let mut moved = bob_signature.clone();
+    moved.signature.index = Some((treasury.clone(), 0));
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &[moved]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "signature", .. }), "{err}");
+
+    // So are proposals whose tx was changed, made on another chain or for an older key set
+    let mut tampered = proposal.clone();
+    tampered.tx.add_data(vec![4u8]);
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &tampered, &[bob_signature.clone()]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "proposal", .. }), "{err}");
+    let elsewhere = Proposal { chain_id: "other-chain.123".to_string(), ..proposal.clone() };
+    let err = multisig::sign_proposal(&sdk, &signer, &elsewhere, &[]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "proposal", .. }), "{err}");
+    // A tx for another chain is refused even when the proposal names this one and its hash matches
+    let mut relabelled = proposal.clone();
+    relabelled.tx.header.chain_id = ChainId::from_str("other-chain.123").unwrap();
+    relabelled.tx_hash = relabelled.tx.raw_header_hash().to_string();
+    let err = multisig::sign_proposal(&sdk, &signer, &relabelled, &[]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "proposal", .. }), "{err}");
+    let relabelled = Proposal { chain_id: "other-chain.123".to_string(), ..relabelled };
+    let err = multisig::sign_proposal(&sdk, &signer, &relabelled, &[]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "proposal", .. }), "{err}");
+    other.set_multisig(&treasury, vec![alice_key, bob_key, foreign_key], 2);
+    let err = multisig::submit_proposal(&other_sdk, &other_signer, &proposal, &[bob_signature]).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "proposal", .. }), "{err}");
+    assert!(other.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn multisig_transfers_are_proposed_signed_and_submitted() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    funded_alice(&env, &sdk).await;
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+
+    // Bob and Carol keep their keys in another wallet
+    let cosigners = TestEnv::new().await;
+    let cosigners_sdk = cosigners.sdk().await;
+    for (alias, account) in [("bob", 1), ("carol", 2)] {
+        wallet::add_key(&cosigners_sdk, alias.to_string(), MNEMONIC, &KeyOptions { account, ..Default::default() })
+            .await
+            .unwrap();
+    }
+    let bob_key = wallet::find_public_key(&cosigners_sdk, "bob").await.unwrap();
+    let carol_key = wallet::find_public_key(&cosigners_sdk, "carol").await.unwrap();
+
+    let treasury = Address::Established(EstablishedAddress::from([7u8; 20]));
+    let account_keys = vec![alice_key.clone(), bob_key.clone(), carol_key];
+    env.set_multisig(&treasury, account_keys.clone(), 2);
+    env.set_balance(&sdk.native_token(), &treasury, token::Amount::native_whole(10));
+
+    let target = Address::from_str(TARGET).unwrap();
+    let proposal = multisig::propose_transfer(&sdk, &treasury.to_string(), target, "1", sdk.native_token(), "alice")
+        .await
+        .unwrap();
+    assert_eq!(proposal.threshold, 2);
+    assert!(env.client.broadcasts().is_empty());
+
+    let cosigner = WalletSigner::new(&cosigners_sdk);
+    let signatures = multisig::sign_proposal(&cosigners_sdk, &cosigner, &proposal, &["bob".to_string()]).await.unwrap();
+    assert_eq!(signatures.len(), 1);
+    assert_eq!(signatures[0].public_key(), &bob_key);
+
+    // The threshold on chain counts, not the one recorded in the proposal
+    env.set_multisig(&treasury, account_keys.clone(), 3);
+    let err = multisig::submit_proposal(&sdk, &signer, &proposal, &signatures).await.unwrap_err();
+    assert!(matches!(err, Error::ThresholdNotMet { threshold: 3, held: 2, .. }), "{err}");
+    assert!(env.client.broadcasts().is_empty());
+
+    // Bob's signature and Alice's own make two of three
+    env.set_multisig(&treasury, account_keys, 2);
+    let submission = multisig::submit_proposal(&sdk, &signer, &proposal, &signatures).await.unwrap();
+    assert_applied(&env, &submission.response, 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+    assert_eq!(env.client.broadcast_txs()[0].raw_header_hash().to_string(), proposal.tx_hash);
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn txs_are_signed_by_a_signer_in_another_process() {
+    // Signs as `claimed` with another key
+    struct Forger {
+        claimed: common::PublicKey,
+        key: common::SecretKey,
+    }
+
+    #[async_trait]
+    impl Signer for Forger {
+        async fn public_keys(&self) -> wallet_example::Result<Vec<common::PublicKey>> {
+            Ok(vec![self.claimed.clone()])
+        }
+
+        async fn sign(&self, hashes: &[SigHash], _signing_data: &SigningTxData) -> wallet_example::Result<Vec<KeySignature>> {
+            Ok(hashes
+                .iter()
+                .map(|hash| KeySignature {
+                    public_key: self.claimed.clone(),
+                    signature: common::SigScheme::sign(&self.key, hash.hash),
+                })
+                .collect())
+        }
+    }
+
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "mallory".to_string(), MNEMONIC, &KeyOptions { account: 5, ..Default::default() })
+        .await
+        .unwrap();
+    let alice_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    let alice_secret = sdk.wallet_mut().await.find_secret_key("alice", None).unwrap();
+    let mallory_secret = sdk.wallet_mut().await.find_secret_key("mallory", None).unwrap();
+    let owner = wallet::find_address(&sdk, "alice").await.unwrap();
+
+    // A stand-in for the signer process, holding Alice's key only
+    let socket = env.dir.path().join("signer.sock");
+    let listener = UnixListener::bind(&socket).unwrap();
+    tokio::spawn(signer::serve_unix(listener, Arc::new(LocalSigner::new(vec![alice_secret]))));
+    let endpoint = SignerEndpoint::from_str(&format!("unix:{}", socket.display())).unwrap();
+    let remote = RemoteSigner::new(endpoint);
+    assert_eq!(remote.public_keys().await.unwrap(), vec![alice_key.clone()]);
+
+    // A wallet that only watches Alice can still sign for her through it
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    watch::import_address(&other_sdk, "alice", &owner.to_string(), false).await.unwrap();
+    let err = wallet::signing_keys_of(&other_sdk, &WalletSigner::new(&other_sdk), &owner).await.unwrap_err();
+    assert!(matches!(err, Error::WatchOnly(_)), "{err}");
+    let keys = wallet::signing_keys_of(&other_sdk, &remote, &owner).await.unwrap();
+    assert_eq!(keys, vec![alice_key.clone()]);
+
+    let signing_data = SigningTxData {
+        owner: Some(owner.clone()),
+        public_keys: keys,
+        threshold: 1,
+        account_public_keys_map: Some(AccountPublicKeysMap::from_iter(vec![alice_key.clone()])),
+        fee_payer: alice_key.clone(),
+    };
+    let mut tx = Tx::new(ChainId::from_str(CHAIN_ID).unwrap(), None);
+    tx.add_data(vec![1u8, 2, 3]);
+    signer::sign_tx(&remote, &mut tx, &signing_data, Vec::new()).await.unwrap();
+    let authorizations: Vec<&Authorization> = tx
+        .sections
+        .iter()
+        .filter_map(|section| match section {
+            Section::Authorization(authorization) => Some(authorization),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(authorizations.len(), 1);
+    assert_eq!(authorizations[0].targets, vec![tx.raw_header_hash()]);
+    let signature = &authorizations[0].signatures[&0];
+    common::SigScheme::verify_signature(&alice_key, &authorizations[0].get_raw_hash(), signature).unwrap();
+
+    // Signatures are verified before they are attached
+    let forger = Forger { claimed: alice_key.clone(), key: mallory_secret };
+    let mut tx = Tx::new(ChainId::from_str(CHAIN_ID).unwrap(), None);
+    tx.add_data(vec![1u8, 2, 3]);
+    let err = signer::sign_tx(&forger, &mut tx, &signing_data, Vec::new()).await.unwrap_err();
+    assert!(matches!(err, Error::Signer(_)), "{err}");
+
+    // So are those of co-signers, made beforehand
+    let presigned = signer::sign_raw_header(&remote, &tx, &signing_data, &signing_data.public_keys).await.unwrap();
+    let mut forged = presigned.clone();
+    forged[0].signature = common::SigScheme::sign(&forger.key, tx.raw_header_hash());
+    let err = signer::sign_tx(&remote, &mut tx.clone(), &signing_data, forged).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "signature", .. }), "{err}");
+    signer::sign_tx(&forger, &mut tx, &signing_data, presigned).await.unwrap();
+
+    // Keys are never sent off the machine
+    let err = SignerEndpoint::from_str("http://10.0.0.7:8080/sign").unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err}");
+    assert!(SignerEndpoint::from_str("http://127.0.0.1:8080/sign").is_ok());
+    assert!(other.client.broadcasts().is_empty());
+
+    // The watch-only wallet sends Alice's transfer with the signatures made in the other process
+    other.accept_txs();
+    other.set_account(&owner, vec![alice_key]);
+    other.set_balance(&other_sdk.native_token(), &owner, token::Amount::native_whole(1_000_000));
+    let target = Address::from_str(TARGET).unwrap();
+    let submission = transfer::send_transparent_token(&other_sdk, &remote, "alice", target, "1", other_sdk.native_token())
+        .await
+        .unwrap();
+    assert_applied(&other, &submission.response, 0);
+    assert_eq!(other.client.broadcasts().len(), 1);
+}
+
+#[tokio::test]
+async fn wallet_inventory_lists_every_entry_with_its_custody() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let encrypted = KeyOptions {
+        password: Some(Zeroizing::new("hunter2".to_string())),
+        ..Default::default()
+    };
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "bob".to_string(), MNEMONIC, &KeyOptions { account: 1, ..encrypted }).await.unwrap();
+    let shielded = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let viewing_key = sdk.wallet().await.find_viewing_key(&shielded).unwrap().to_string();
+    wallet::generate_payment_address(&sdk, "pay", &viewing_key, false).await.unwrap();
+
+    let entries = inventory::list_wallet(&sdk, &InventoryFilter::default()).await;
+    let entry = |kind, alias: &str| entries.iter().find(|e| e.kind == kind && e.alias == alias).unwrap();
+
+    let alice = entry(EntryKind::Address, "alice");
+    assert_eq!(alice.value, wallet::find_address(&sdk, "alice").await.unwrap().to_string());
+    assert_eq!(alice.address_type.as_deref(), Some("implicit"));
+    assert_eq!(alice.custody, Custody::Secret);
+    assert_eq!(alice.derivation_path.as_deref(), Some("m/44'/877'/0'/0'/0'"));
+    assert_eq!(entry(EntryKind::PublicKey, "bob").custody, Custody::EncryptedSecret);
+    assert_eq!(entry(EntryKind::SpendingKey, "shielded").custody, Custody::Secret);
+    assert_eq!(entry(EntryKind::ViewingKey, "shielded").value, viewing_key);
+    assert_eq!(entry(EntryKind::PaymentAddress, "pay").custody, Custody::ReceiveOnly);
+
+    // Spending keys are listed but never printed
+    assert!(entries.iter().all(|e| !e.value.starts_with("zsknam")));
+
+    let filter = InventoryFilter {
+        kinds: vec![EntryKind::Address, EntryKind::PublicKey],
+        alias: Some("ALI".to_string()),
+    };
+    let filtered = inventory::list_wallet(&sdk, &filter).await;
+    let found: Vec<_> = filtered.iter().map(|e| (e.kind, e.alias.as_str())).collect();
+    assert_eq!(found, vec![(EntryKind::Address, "alice"), (EntryKind::PublicKey, "alice")]);
+}
+
+#[tokio::test]
+async fn entries_are_renamed_with_their_keys_and_mnemonic() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let wallet_dir = env.dir.path().join("wallet");
+    let password = || Some(Zeroizing::new("hunter2".to_string()));
+    let options = KeyOptions { password: password(), ..Default::default() };
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &options).await.unwrap();
+    wallet::add_key(&sdk, "bob".to_string(), MNEMONIC, &KeyOptions { account: 1, ..Default::default() }).await.unwrap();
+    let vault = MnemonicVault::new(&wallet_dir);
+    vault.store("alice", MNEMONIC, "hunter2").unwrap();
+    let address = wallet::find_address(&sdk, "alice").await.unwrap();
+
+    // Taken aliases are refused and nothing changes
+    let err = aliases::rename_alias(&sdk, &wallet_dir, &[], "alice", "Bob").await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+    assert_eq!(wallet::find_address(&sdk, "alice").await, Some(address.clone()));
+
+    let kinds = aliases::rename_alias(&sdk, &wallet_dir, &[], "alice", "carol").await.unwrap();
+    assert_eq!(kinds, vec![EntryKind::Address, EntryKind::PublicKey]);
+    assert!(wallet::find_address(&sdk, "alice").await.is_none());
+    assert_eq!(wallet::find_address(&sdk, "carol").await, Some(address.clone()));
+    assert!(sdk.wallet_mut().await.find_secret_key("carol", password()).is_ok());
+    assert_eq!(vault.reveal("carol", "hunter2").unwrap().as_str(), MNEMONIC);
+
+    // The file was rewritten, a fresh context sees the new alias
+    let reloaded = env.sdk().await;
+    assert_eq!(wallet::find_address(&reloaded, "carol").await, Some(address));
+
+    let err = aliases::rename_alias(&sdk, &wallet_dir, &[], "nobody", "dave").await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "alias", .. }), "{err}");
+}
+
+#[tokio::test]
+async fn entries_of_one_kind_are_renamed_or_deleted_alone() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let wallet_dir = env.dir.path().join("wallet");
+    let shielded = wallet::create_spending_key(&sdk, "default".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let viewing_key = sdk.wallet().await.find_viewing_key(&shielded).unwrap().to_string();
+    let payment_addr = wallet::generate_payment_address(&sdk, "default", &viewing_key, false).await.unwrap();
+
+    let kinds = aliases::rename_alias(&sdk, &wallet_dir, &[EntryKind::PaymentAddress], "default", "shop")
+        .await
+        .unwrap();
+    assert_eq!(kinds, vec![EntryKind::PaymentAddress]);
+    assert_eq!(sdk.wallet().await.find_payment_addr("shop"), Some(&payment_addr));
+    assert!(sdk.wallet().await.find_viewing_key("default").is_ok());
+
+    let kinds = aliases::delete_alias(&sdk, &wallet_dir, &[], "default").await.unwrap();
+    assert_eq!(kinds, vec![EntryKind::SpendingKey, EntryKind::ViewingKey]);
+    assert!(sdk.wallet().await.find_viewing_key("default").is_err());
+    assert!(sdk.wallet().await.find_payment_addr("shop").is_some());
+
+    let reloaded = env.sdk().await;
+    assert!(reloaded.wallet().await.find_viewing_key("default").is_err());
+    assert!(!wallet_dir.join("wallet.toml.tmp").exists());
+}
+
+#[tokio::test]
+async fn backups_restore_keys_mnemonics_and_shielded_context() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let (wallet_dir, masp_dir) = (env.dir.path().join("wallet"), env.dir.path().join("masp"));
+    let password = || Some(Zeroizing::new("hunter2".to_string()));
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions { password: password(), ..Default::default() })
+        .await
+        .unwrap();
+    MnemonicVault::new(&wallet_dir).store("alice", MNEMONIC, "hunter2").unwrap();
+    std::fs::create_dir_all(&masp_dir).unwrap();
+    std::fs::write(masp_dir.join("shielded.dat"), b"context").unwrap();
+    let address = wallet::find_address(&sdk, "alice").await.unwrap();
+
+    let file = env.dir.path().join("wallet.backup");
+    let summary = backup::export_backup(&sdk, &wallet_dir, Some(&masp_dir), &file, "backup-pw").await.unwrap();
+    assert!(summary.mnemonics);
+    assert_eq!(summary.shielded_files, vec!["shielded.dat"]);
+
+    let err = Backup::read(&file, "wrong").err().unwrap();
+    assert!(matches!(err, Error::Decryption(_)), "{err}");
+    let err = Backup::read(&wallet_dir.join("wallet.toml"), "backup-pw").err().unwrap();
+    assert!(matches!(err, Error::InvalidInput { what: "backup", .. }), "{err}");
+
+    // Only the current format, whose header is authenticated, is read
+    let mut data = std::fs::read(&file).unwrap();
+    assert_eq!(data[8], backup::BACKUP_VERSION);
+    data[8] = 1;
+    let downgraded = env.dir.path().join("downgraded.backup");
+    std::fs::write(&downgraded, data).unwrap();
+    let err = Backup::read(&downgraded, "backup-pw").err().unwrap();
+    assert!(matches!(err, Error::InvalidInput { what: "backup", .. }), "{err}");
+
+    let backup = Backup::read(&file, "backup-pw").unwrap();
+    assert_eq!(backup.chain_id(), CHAIN_ID);
+    let alice = |kind| BackupEntry { kind, alias: "alice".to_string() };
+    assert_eq!(backup.entries(), vec![alice(EntryKind::Address), alice(EntryKind::PublicKey)]);
+
+    // Into an empty wallet somewhere else
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    let (other_wallet, other_masp) = (other.dir.path().join("wallet"), other.dir.path().join("masp"));
+    let restored = backup::restore_backup(&other_sdk, &other_wallet, Some(&other_masp), &backup, RestoreMode::Merge)
+        .await
+        .unwrap();
+    assert_eq!(restored.imported, backup.entries());
+    assert!(restored.conflicts.is_empty());
+    assert_eq!(wallet::find_address(&other_sdk, "alice").await, Some(address.clone()));
+    assert!(other_sdk.wallet_mut().await.find_secret_key("alice", password()).is_ok());
+    assert_eq!(MnemonicVault::new(&other_wallet).reveal("alice", "hunter2").unwrap().as_str(), MNEMONIC);
+    assert_eq!(std::fs::read(other_masp.join("shielded.dat")).unwrap(), b"context");
+
+    // Overwriting replaces the whole shielded context, files the backup lacks included
+    std::fs::write(other_masp.join("speculative.dat"), b"stale").unwrap();
+    let restored = backup::restore_backup(&other_sdk, &other_wallet, Some(&other_masp), &backup, RestoreMode::Overwrite)
+        .await
+        .unwrap();
+    assert_eq!(restored.shielded_files, vec!["shielded.dat"]);
+    assert!(!other_masp.join("speculative.dat").exists());
+    assert_eq!(std::fs::read(other_masp.join("shielded.dat")).unwrap(), b"context");
+
+    // The shielded context of another chain is left out, the keys are still restored
+    let elsewhere = TestEnv::new().await;
+    let elsewhere_sdk = elsewhere.sdk().await.chain_id(ChainId::from_str("other-chain.123").unwrap());
+    let (elsewhere_wallet, elsewhere_masp) = (elsewhere.dir.path().join("wallet"), elsewhere.dir.path().join("masp"));
+    std::fs::create_dir_all(&elsewhere_masp).unwrap();
+    std::fs::write(elsewhere_masp.join("shielded.dat"), b"other chain").unwrap();
+    let restored =
+        backup::restore_backup(&elsewhere_sdk, &elsewhere_wallet, Some(&elsewhere_masp), &backup, RestoreMode::Overwrite)
+            .await
+            .unwrap();
+    assert!(restored.shielded_files.is_empty());
+    assert_eq!(wallet::find_address(&elsewhere_sdk, "alice").await, Some(address));
+    assert_eq!(std::fs::read(elsewhere_masp.join("shielded.dat")).unwrap(), b"other chain");
+}
+
+#[tokio::test]
+async fn merged_backups_keep_the_wallet_entries_unless_overwriting() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let wallet_dir = env.dir.path().join("wallet");
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    wallet::add_key(&sdk, "bob".to_string(), MNEMONIC, &KeyOptions { account: 1, ..Default::default() }).await.unwrap();
+    let alice = wallet::find_address(&sdk, "alice").await.unwrap();
+    let file = env.dir.path().join("wallet.backup");
+    backup::export_backup(&sdk, &wallet_dir, None, &file, "backup-pw").await.unwrap();
+    let backup = Backup::read(&file, "backup-pw").unwrap();
+    assert!(backup.shielded_files().is_empty());
+
+    // Another wallet already uses "alice" for another account
+    let other = TestEnv::new().await;
+    let other_sdk = other.sdk().await;
+    let other_wallet = other.dir.path().join("wallet");
+    wallet::add_key(&other_sdk, "alice".to_string(), MNEMONIC, &KeyOptions { account: 2, ..Default::default() })
+        .await
+        .unwrap();
+    let other_alice = wallet::find_address(&other_sdk, "alice").await.unwrap();
+
+    let merged = backup::restore_backup(&other_sdk, &other_wallet, None, &backup, RestoreMode::Merge).await.unwrap();
+    let entry = |kind, alias: &str| BackupEntry { kind, alias: alias.to_string() };
+    assert_eq!(merged.imported, vec![entry(EntryKind::Address, "bob"), entry(EntryKind::PublicKey, "bob")]);
+    assert_eq!(merged.conflicts, vec![entry(EntryKind::Address, "alice"), entry(EntryKind::PublicKey, "alice")]);
+    assert_eq!(wallet::find_address(&other_sdk, "alice").await, Some(other_alice));
+    assert!(wallet::find_address(&other_sdk, "bob").await.is_some());
+
+    let overwritten = backup::restore_backup(&other_sdk, &other_wallet, None, &backup, RestoreMode::Overwrite)
+        .await
+        .unwrap();
+    assert!(overwritten.conflicts.is_empty());
+    assert_eq!(wallet::find_address(&other_sdk, "alice").await, Some(alice.clone()));
+    let reloaded = other.sdk().await;
+    assert_eq!(wallet::find_address(&reloaded, "alice").await, Some(alice));
+}
+
+#[tokio::test]
+async fn reveal_status_follows_the_account_keys() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let owner = wallet::find_address(&sdk, "alice").await.unwrap();
+    let public_key = sdk.wallet().await.find_public_key("alice").unwrap();
+
+    env.set_account(&owner, vec![]);
+    assert!(!query::findifreveal(&sdk, &owner).await.unwrap());
+
+    env.set_account(&owner, vec![public_key]);
+    assert!(query::findifreveal(&sdk, &owner).await.unwrap());
+}
+
+#[tokio::test]
+async fn reveal_status_reports_rpc_failures() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    // Nothing scripted for this account
+    let owner = Address::from_str(OWNER).unwrap();
+    let err = query::findifreveal(&sdk, &owner).await.unwrap_err();
+    assert!(matches!(err, Error::Rpc(_)), "{err}");
+}
+
+#[tokio::test]
+async fn balance_of_an_address() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let owner = Address::from_str(OWNER).unwrap();
+    let token = wallet::resolve_token(&sdk, None).await.unwrap();
+    assert_eq!(token.to_string(), NATIVE_TOKEN);
+
+    env.set_balance(&token, &owner, token::Amount::native_whole(42));
+
+    let balance = query::get_token_balance(&sdk, &owner, &token).await.unwrap();
+    assert_eq!(balance, token::Amount::native_whole(42));
+}
+
+#[tokio::test]
+async fn masp_epoch() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    let epoch = query::query_and_print_masp_epoch(&sdk).await.unwrap();
+    assert_eq!(epoch.to_string(), "7");
+}
+
+#[tokio::test]
+async fn node_status_checks_chain_sync_and_block_age() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+
+    let status = node::verify_node(&sdk).await.unwrap();
+    assert_eq!(status.network, CHAIN_ID);
+    assert_eq!(status.latest_block_height, 100);
+
+    env.client.set_status("other-chain.3c9a2fe70462d", 100, 5, false);
+    let err = node::verify_node(&sdk).await.unwrap_err();
+    assert!(matches!(err, Error::ChainIdMismatch { .. }), "{err}");
+
+    env.client.set_status(CHAIN_ID, 100, 5, true);
+    let err = node::verify_node(&sdk).await.unwrap_err();
+    assert!(matches!(err, Error::NodeNotReady(_)), "{err}");
+
+    env.client.set_status(CHAIN_ID, 100, 3600, false);
+    let err = node::verify_node(&sdk).await.unwrap_err();
+    assert!(matches!(err, Error::NodeNotReady(_)), "{err}");
+}
+
+#[tokio::test]
+async fn transfers_from_an_unknown_alias_are_rejected() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    let token = sdk.native_token();
+    let target = Address::from_str(TARGET).unwrap();
+    let shielded_target = PaymentAddress::from_str(SHIELDED_TARGET).unwrap();
+
+    let err = transfer::send_transparent_token(&sdk, &signer, "nobody", target, "1", token.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownAlias(_)), "{err}");
+
+    let err = transfer::send_token_shielded(&sdk, &signer, "nobody", &shielded_target, "1", token.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownAlias(_)), "{err}");
+
+    let err = transfer::send_ibc_token(&sdk, &signer, "nobody", "cosmos1receiver", "channel-0", "1", token)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::UnknownAlias(_)), "{err}");
+
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn transfers_reject_invalid_input() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let token = sdk.native_token();
+    let target = Address::from_str(TARGET).unwrap();
+
+    let err = transfer::send_transparent_token(&sdk, &signer, "alice", target, "ten", token.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "amount", .. }), "{err}");
+
+    let err = transfer::send_ibc_token(&sdk, &signer, "alice", "cosmos1receiver", "not a channel", "1", token)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "channel id", .. }), "{err}");
+
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn nothing_is_broadcast_to_a_node_on_another_chain() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let owner = wallet::find_address(&sdk, "alice").await.unwrap();
+    let public_key = sdk.wallet().await.find_public_key("alice").unwrap();
+    env.set_account(&owner, vec![public_key]);
+    env.client.set_status("other-chain.3c9a2fe70462d", 100, 5, false);
+
+    let token = sdk.native_token();
+    let target = Address::from_str(TARGET).unwrap();
+    let shielded_target = PaymentAddress::from_str(SHIELDED_TARGET).unwrap();
+
+    assert!(transfer::send_transparent_token(&sdk, &signer, "alice", target, "1", token.clone())
+        .await
+        .is_err());
+    assert!(transfer::send_token_shielded(&sdk, &signer, "alice", &shielded_target, "1", token.clone())
+        .await
+        .is_err());
+    assert!(transfer::send_ibc_token(&sdk, &signer, "alice", "cosmos1receiver", "channel-0", "1", token)
+        .await
+        .is_err());
+
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn reveal_pk_txs_are_applied() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    let owner = funded_alice(&env, &sdk).await;
+    env.set_account(&owner, vec![]);
+
+    let responses = transfer::reveal_pk_if_needed(&sdk, &signer, &owner).await.unwrap();
+    assert_eq!(responses.len(), 1);
+    assert_applied(&env, &responses[0], 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+}
+
+#[tokio::test]
+async fn transparent_transfers_are_applied() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    funded_alice(&env, &sdk).await;
+    let target = Address::from_str(TARGET).unwrap();
+
+    let submission = transfer::send_transparent_token(&sdk, &signer, "alice", target, "1", sdk.native_token())
+        .await
+        .unwrap();
+    assert!(submission.reveal.is_empty());
+    assert_applied(&env, &submission.response, 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+}
+
+#[tokio::test]
+async fn shielding_transfers_are_applied() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    funded_alice(&env, &sdk).await;
+    let target = PaymentAddress::from_str(SHIELDED_TARGET).unwrap();
+
+    let submission = transfer::send_token_shielded(&sdk, &signer, "alice", &target, "1", sdk.native_token())
+        .await
+        .unwrap();
+    assert!(submission.reveal.is_empty());
+    assert_applied(&env, &submission.response, 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+}
+
+#[tokio::test]
+async fn ibc_transfers_are_applied() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    funded_alice(&env, &sdk).await;
+
+    let submission =
+        transfer::send_ibc_token(&sdk, &signer, "alice", "cosmos1receiver", "channel-0", "1", sdk.native_token())
+            .await
+            .unwrap();
+    assert!(submission.reveal.is_empty());
+    assert_applied(&env, &submission.response, 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+}
+
+#[tokio::test]
+async fn created_accounts_are_stored_under_their_alias() {
+    let env = TestEnv::new().await;
+    env.accept_txs();
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    funded_alice(&env, &sdk).await;
+    wallet::add_key(&sdk, "bob".to_string(), MNEMONIC, &KeyOptions { account: 1, ..Default::default() }).await.unwrap();
+    let treasury = Address::Established(EstablishedAddress::from([7u8; 20]));
+    env.client.apply_broadcasts(vec![treasury.clone()]);
+
+    let keys = vec!["alice".to_string(), "bob".to_string()];
+    let created = account::create_account(&sdk, &signer, "treasury", "alice", &keys, 2, None).await.unwrap();
+    assert_eq!(created.address, Some(treasury.clone()));
+    assert_applied(&env, &created.submission.response, 0);
+    assert_eq!(env.client.broadcasts().len(), 1);
+    let reloaded = env.sdk().await;
+    assert_eq!(wallet::find_address(&reloaded, "treasury").await, Some(treasury));
+}
+
+#[tokio::test]
+async fn ibc_memo_describes_the_transfer() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let owner = wallet::find_address(&sdk, "alice").await.unwrap();
+
+    let memo = transfer::generate_ibc_memo(&sdk, "alice", "cosmos1receiver", "channel-0", "10")
+        .await
+        .unwrap();
+    assert!(memo.contains(&owner.to_string()));
+    assert!(memo.contains("cosmos1receiver"));
+    assert!(memo.contains("channel-0"));
+}
+
+#[tokio::test]
+async fn shielded_sync_against_the_indexer() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let alias = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let spending_key = sdk.wallet().await.find_spending_key(&alias, None).unwrap().to_string();
+
+    shielded::shielded_sync(&sdk, &env.indexer.url(), &spending_key).await.unwrap();
+
+    assert!(env.indexer.requests().iter().any(|endpoint| endpoint == "/height"));
+}
+
+#[tokio::test]
+async fn watch_only_entries_are_queried_and_synced_but_never_sign() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let signer = WalletSigner::new(&sdk);
+    let token = sdk.native_token();
+    let owner = Address::from_str(OWNER).unwrap();
+
+    // Keys derived elsewhere, only their public parts are imported
+    let cold = TestEnv::new().await;
+    let cold_sdk = cold.sdk().await;
+    let shielded = wallet::create_spending_key(&cold_sdk, "cold".to_string(), MNEMONIC, &KeyOptions::default())
+        .await
+        .unwrap();
+    let viewing_key = cold_sdk.wallet().await.find_viewing_key(&shielded).unwrap().to_string();
+
+    let address = watch::import_address(&sdk, "treasury", OWNER, false).await.unwrap();
+    assert_eq!(address, owner);
+    let err = watch::import_address(&sdk, "treasury", TARGET, false).await.unwrap_err();
+    assert!(matches!(err, Error::AliasTaken(_)), "{err}");
+    let err = watch::import_address(&sdk, "other", "tnam1nope", false).await.unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "address", .. }), "{err}");
+    watch::import_viewing_key(&sdk, "treasury", &viewing_key, Some(50), false).await.unwrap();
+
+    let entries = inventory::list_wallet(&sdk, &InventoryFilter::default()).await;
+    assert!(entries.iter().all(|entry| entry.custody == Custody::WatchOnly), "{entries:?}");
+
+    env.set_balance(&token, &owner, token::Amount::native_whole(42));
+    let resolved = wallet::resolve_address(&sdk, "treasury").await.unwrap();
+    assert_eq!(query::get_token_balance(&sdk, &resolved, &token).await.unwrap(), token::Amount::native_whole(42));
+    wallet::generate_payment_address(&sdk, "treasury-pay", "treasury", false).await.unwrap();
+    shielded::shielded_sync(&sdk, &env.indexer.url(), "treasury").await.unwrap();
+
+    let target = Address::from_str(TARGET).unwrap();
+    let err = transfer::send_transparent_token(&sdk, &signer, "treasury", target, "1", token.clone())
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::WatchOnly(ref address) if *address == owner), "{err}");
+    let err = transfer::send_ibc_token(&sdk, &signer, "treasury", "cosmos1receiver", "channel-0", "1", token)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::WatchOnly(_)), "{err}");
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn dry_run_batches_sign_like_real_runs_and_broadcast_nothing() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await.dry_run(true);
+    let signer = WalletSigner::new(&sdk);
+    let owner = watch::import_address(&sdk, "treasury", OWNER, false).await.unwrap();
+    env.set_account(&owner, vec![]);
+    env.set_balance(&sdk.native_token(), &owner, token::Amount::native_whole(5));
+
+    // Simulating still needs the keys of every source, and a source revealed on chain
+    let plan = Plan {
+        on_error: OnError::Continue,
+        steps: vec![
+            Step::RevealPk { owner: "treasury".to_string() },
+            Step::Transfer {
+                source: "treasury".to_string(),
+                target: TARGET.to_string(),
+                token: None,
+                amount: "1".to_string(),
+            },
+            Step::Balance { owner: "treasury".to_string(), token: None },
+        ],
+    };
+    let results = plan::run_plan(&sdk, &signer, &plan).await;
+    assert!(matches!(results[0].outcome, Some(Err(Error::Unrevealed(_)))), "{:?}", results[0].outcome);
+    assert!(matches!(results[1].outcome, Some(Err(Error::UnverifiableDryRun(_)))), "{:?}", results[1].outcome);
+    assert!(matches!(results[2].outcome, Some(Ok(StepOutput::Balance { .. }))), "{:?}", results[2].outcome);
+
+    // A key that is only revealed by the dry run itself can not vouch for the transfer after it
+    wallet::add_key(&sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let alice = wallet::find_address(&sdk, "alice").await.unwrap();
+    env.set_account(&alice, vec![]);
+    let plan = Plan {
+        on_error: OnError::Continue,
+        steps: vec![Step::Transfer {
+            source: "alice".to_string(),
+            target: TARGET.to_string(),
+            token: None,
+            amount: "1".to_string(),
+        }],
+    };
+    let results = plan::run_plan(&sdk, &signer, &plan).await;
+    assert!(
+        matches!(results[0].outcome, Some(Err(Error::UnverifiableDryRun(ref address))) if *address == alice),
+        "{:?}",
+        results[0].outcome
+    );
+    assert!(env.client.broadcasts().is_empty());
+
+    // Once revealed, Alice's key signs the transfer and the node only simulates it
+    env.accept_txs();
+    // A dry run result is the batch result followed by the gas used
+    env.client.respond("/shell/dry_run_tx", (TxResult::<String>::default(), 0u64));
+    let public_key = wallet::find_public_key(&sdk, "alice").await.unwrap();
+    env.set_account(&alice, vec![public_key]);
+    env.set_balance(&sdk.native_token(), &alice, token::Amount::native_whole(1_000_000));
+    let results = plan::run_plan(&sdk, &signer, &plan).await;
+    assert!(
+        matches!(
+            results[0].outcome,
+            Some(Ok(StepOutput::Submitted(transfer::TxSubmission { response: ProcessTxResponse::DryRun(_), .. })))
+        ),
+        "{:?}",
+        results[0].outcome
+    );
+    assert!(env.client.seen_paths().iter().any(|path| path == "/shell/dry_run_tx"));
+    assert!(env.client.broadcasts().is_empty());
+}
+
+#[tokio::test]
+async fn shielded_sync_reports_indexer_failures() {
+    let env = TestEnv::new().await;
+    let sdk = env.sdk().await;
+    let alias = wallet::create_spending_key(&sdk, "shielded".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let spending_key = sdk.wallet().await.find_spending_key(&alias, None).unwrap().to_string();
+
+    // Nothing listens on the discard port
+    let err = shielded::shielded_sync(&sdk, "http://127.0.0.1:9/api/v1", &spending_key)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Indexer(_)), "{err}");
+
+    let err = shielded::shielded_sync(&sdk, &env.indexer.url(), "zsknam1invalid")
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "spending key", .. }), "{err}");
+
+    // A saved context that can not be read is not synced over from scratch
+    let masp_dir = env.dir.path().join("masp");
+    std::fs::create_dir_all(&masp_dir).unwrap();
+    std::fs::write(masp_dir.join("shielded.dat"), b"garbage").unwrap();
+    let err = shielded::shielded_sync(&sdk, &env.indexer.url(), &spending_key)
+        .await
+        .unwrap_err();
+    assert!(matches!(err, Error::Shielded(_)), "{err}");
+}
+
+#[test]
+fn named_wallets_are_created_and_opened_from_the_wallet_home() {
+    let dir = tempfile::tempdir().unwrap();
+    let home = WalletHome::new(dir.path().join("home"));
+    assert!(home.wallets().unwrap().is_empty());
+
+    home.create("savings").unwrap();
+    home.create("alice_2").unwrap();
+    assert_eq!(home.wallets().unwrap(), vec!["alice_2", "savings"]);
+
+    let err = home.create("savings").unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "wallet name", .. }), "{err}");
+    let err = home.create("../elsewhere").unwrap_err();
+    assert!(matches!(err, Error::InvalidInput { what: "wallet name", .. }), "{err}");
+    assert!(!dir.path().join("elsewhere").exists());
+
+    // Each wallet keeps a shielded context per network
+    let mut profile = Config::default().profile("housefire-cotton").unwrap();
+    profile.open_wallet(&home, Some("savings"), "housefire-cotton").unwrap();
+    assert_eq!(profile.wallet.as_deref(), Some("savings"));
+    assert_eq!(profile.wallet_dir, home.root().join("wallets").join("savings"));
+    assert_eq!(profile.masp_dir, profile.wallet_dir.join("masp").join("housefire-cotton"));
+
+    // Unknown wallets are not created behind the user's back
+    let err = profile.open_wallet(&home, Some("nobody"), "housefire-cotton").unwrap_err();
+    assert!(matches!(err, Error::Config(_)), "{err}");
+    assert!(!home.exists("nobody"));
+
+    let mut profile = Config::default().profile("housefire-cotton").unwrap();
+    profile.open_wallet(&home, None, "housefire-cotton").unwrap();
+    assert_eq!(profile.wallet.as_deref(), Some("default"));
+    assert_eq!(profile.wallet_dir, home.wallet_dir("default"));
+}
+
+#[test]
+fn wallet_dirs_set_in_the_config_are_kept() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("namada-wallet.toml");
+    std::fs::write(
+        &path,
+        format!(
+            r#"
+default_network = "local"
+wallet_home = '{home}'
+
+[networks.local]
+rpc_url = "http://127.0.0.1:26657"
+chain_id = "{CHAIN_ID}"
+indexer_url = "http://127.0.0.1:5000/api/v1"
+wallet_dir = '{wallet}'
+
+[networks.other]
+rpc_url = "http://127.0.0.1:26657"
+chain_id = "{CHAIN_ID}"
+indexer_url = "http://127.0.0.1:5000/api/v1"
+"#,
+            home = dir.path().join("home").display(),
+            wallet = dir.path().join("pinned").display(),
+        ),
+    )
+    .unwrap();
+    let config = Config::load_from(&path).unwrap();
+    let home = config.wallet_home(None).unwrap();
+    assert_eq!(home.root(), dir.path().join("home"));
+
+    let (_, profile) = config.active_profile_in(Some("local"), &home, None).unwrap();
+    assert_eq!(profile.wallet, None);
+    assert_eq!(profile.wallet_dir, dir.path().join("pinned"));
+    assert_eq!(profile.masp_dir, dir.path().join("pinned").join("masp").join("local"));
+
+    // A named wallet wins over the pinned dirs
+    home.create("savings").unwrap();
+    let (_, profile) = config.active_profile_in(Some("local"), &home, Some("savings")).unwrap();
+    assert_eq!(profile.wallet_dir, home.wallet_dir("savings"));
+
+    let (_, profile) = config.active_profile_in(Some("other"), &home, None).unwrap();
+    assert_eq!(profile.wallet_dir, home.wallet_dir("default"));
+    assert_eq!(profile.masp_dir, home.masp_dir("default", "other"));
+}
+
+#[test]
+fn wallet_dirs_are_locked_against_other_processes() {
+    use fs2::FileExt;
+    use std::io::Write;
+
+    let dir = tempfile::tempdir().unwrap();
+    let (wallet_dir, masp_dir) = (dir.path().join("wallet"), dir.path().join("masp"));
+
+    // Shared within the process, exclusive against any other open file
+    let lock = WalletLock::acquire(&wallet_dir, &masp_dir).unwrap();
+    let again = DirLock::acquire(&wallet_dir).unwrap();
+    let pid = std::fs::read_to_string(wallet_dir.join(LOCK_FILE)).unwrap();
+    assert_eq!(pid, std::process::id().to_string());
+    let other = std::fs::File::open(wallet_dir.join(LOCK_FILE)).unwrap();
+    assert!(other.try_lock_exclusive().is_err());
+    drop((lock, again));
+    other.try_lock_exclusive().unwrap();
+    other.unlock().unwrap();
+
+    // Another process holding the lock
+    let mut other = std::fs::OpenOptions::new().write(true).open(wallet_dir.join(LOCK_FILE)).unwrap();
+    other.lock_exclusive().unwrap();
+    other.set_len(0).unwrap();
+    other.write_all(b"4242").unwrap();
+
+    let err = WalletLock::acquire(&wallet_dir, &masp_dir).unwrap_err();
+    assert!(matches!(err, Error::Locked { pid: Some(4242), .. }), "{err}");
+    assert!(err.to_string().contains("in use by PID 4242"), "{err}");
+
+    // Saving goes through the lock too and leaves the file untouched
+    let wallet = Wallet::new(CliWalletUtils::new(wallet_dir.clone()), Store::default());
+    let err = wallet.save().unwrap_err();
+    assert!(err.to_string().contains("in use by PID 4242"), "{err}");
+    assert!(!wallet_dir.join("wallet.toml").exists());
+
+    other.unlock().unwrap();
+    wallet.save().unwrap();
+    assert!(wallet_dir.join("wallet.toml").exists());
+    assert!(!wallet_dir.join("wallet.toml.tmp").exists());
+    WalletLock::acquire(&wallet_dir, &masp_dir).unwrap();
+}
+
+#[tokio::test]
+async fn json_output_keeps_prompts_and_previews_off_stdout() {
+    let env = TestEnv::new().await;
+    let config = write_cli_config(&env, Some(&env.dir.path().join("wallet"))).await;
+
+    let (report, _) = run_json(&env, &config, &["key", "add", "--alias", "alice", "--mnemonic", MNEMONIC], "").await;
+    assert_eq!(report["operation"], "key-added", "{report}");
+    assert_eq!(report["alias"], "alice", "{report}");
+
+    // The entries to delete and the question are shown on stderr, the answer keeps them
+    let (report, stderr) = run_json(&env, &config, &["wallet", "delete", "--alias", "alice"], "n\n").await;
+    assert_eq!(report["operation"], "cancelled", "{report}");
+    assert!(stderr.contains("alice") && stderr.contains("[y/N]"), "{stderr}");
+    assert!(wallet::find_address(&env.sdk().await, "alice").await.is_some());
+}
+
+#[tokio::test]
+async fn a_wallet_left_in_the_old_default_dir_is_pointed_out() {
+    let env = TestEnv::new().await;
+    let config = write_cli_config(&env, None).await;
+
+    let (report, stderr) = run_json(&env, &config, &["wallets", "list"], "").await;
+    assert_eq!(report["operation"], "wallets", "{report}");
+    assert!(!stderr.contains("sdk-wallet"), "{stderr}");
+
+    let legacy = env.dir.path().join("sdk-wallet");
+    std::fs::create_dir_all(&legacy).unwrap();
+    std::fs::write(legacy.join("wallet.toml"), Store::default().encode()).unwrap();
+    let (report, stderr) = run_json(&env, &config, &["wallets", "list"], "").await;
+    assert_eq!(report["operation"], "wallets", "{report}");
+    assert!(stderr.contains("./sdk-wallet") && stderr.contains("wallet_dir"), "{stderr}");
+
+    // Nothing to say once the profile points at it
+    let config = write_cli_config(&env, Some(std::path::Path::new("./sdk-wallet"))).await;
+    let (_, stderr) = run_json(&env, &config, &["wallets", "list"], "").await;
+    assert!(!stderr.contains("is not used"), "{stderr}");
+}
+
+// A config for the binary with one network on the mock node, its wallet pinned to
+// `wallet_dir` or opened from the wallet home
+async fn write_cli_config(env: &TestEnv, wallet_dir: Option<&std::path::Path>) -> std::path::PathBuf {
+    let mut config = format!(
+        "default_network = \"localnet\"\n\n[networks.localnet]\nrpc_url = \"{}\"\nchain_id = \"{}\"\nindexer_url = \"{}\"\n",
+        env.client.serve().await,
+        CHAIN_ID,
+        env.indexer.url(),
+    );
+    if let Some(wallet_dir) = wallet_dir {
+        config.push_str(&format!("wallet_dir = \"{}\"\n", wallet_dir.display()));
+    }
+    let path = env.dir.path().join("namada-wallet.toml");
+    std::fs::write(&path, config).unwrap();
+    path
+}
+
+// Run the binary from the test dir against `config` in JSON mode with `input` on
+// stdin, returning its stdout parsed as the one JSON document it must be, and its stderr
+async fn run_json(env: &TestEnv, config: &std::path::Path, args: &[&str], input: &str) -> (serde_json::Value, String) {
+    let mut child = tokio::process::Command::new(env!("CARGO_BIN_EXE_wallet-example"))
+        .arg("--config")
+        .arg(config)
+        .args(["--output", "json"])
+        .args(args)
+        .current_dir(env.dir.path())
+        .env(ENV_WALLET_PASSWORD, "")
+        .env("NAMADA_WALLET_HOME", env.dir.path().join("home"))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .spawn()
+        .unwrap();
+    // Commands that ask nothing may exit before reading it
+    let _ = tokio::io::AsyncWriteExt::write_all(&mut child.stdin.take().unwrap(), input.as_bytes()).await;
+    let output = child.wait_with_output().await.unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
+    let report = serde_json::from_str(&stdout)
+        .unwrap_or_else(|e| panic!("stdout is not one JSON document ({e}):\n{stdout}\nstderr:\n{stderr}"));
+    (report, stderr)
+}
+
+// Alice's key, revealed on chain and holding enough of the native token for fees
+async fn funded_alice(env: &TestEnv, sdk: &TestSdk) -> Address {
+    wallet::add_key(sdk, "alice".to_string(), MNEMONIC, &KeyOptions::default()).await.unwrap();
+    let owner = wallet::find_address(sdk, "alice").await.unwrap();
+    let public_key = wallet::find_public_key(sdk, "alice").await.unwrap();
+    env.set_account(&owner, vec![public_key]);
+    env.set_balance(&sdk.native_token(), &owner, token::Amount::native_whole(1_000_000));
+    owner
+}
+
+// `response` reports the `index`th broadcast tx as applied
+fn assert_applied(env: &TestEnv, response: &ProcessTxResponse, index: usize) {
+    let ProcessTxResponse::Applied(applied) = response else {
+        panic!("not applied: {response:?}");
+    };
+    assert_eq!(applied.code, ResultCode::Ok);
+    assert_eq!(applied.hash, env.client.broadcast_txs()[index].header_hash().to_string());
+}
+
+// The words at the challenged positions, as a user who wrote the phrase down would type them
+fn challenge_answers(pending: &PendingMnemonic) -> Vec<&str> {
+    let words: Vec<&str> = pending.phrase().split_whitespace().collect();
+    pending.challenge().iter().map(|&i| words[i]).collect()
+}